@@ -55,7 +55,7 @@ impl TryFrom<&str> for Class {
 pub enum Signal {
     /// Digital; true for high, false for low
     Digital(bool),
-    /// Analog
+    /// Analog; voltage in millivolts
     Analog(u32),
 }
 
@@ -5,6 +5,9 @@ use std::fmt;
 use std::fmt::Display;
 use std::time::{Duration, Instant};
 
+use super::hal::{Level, Trigger};
+use super::test::{AppliedOperation, Response};
+use super::testbed::DEFAULT_DEVICE;
 use super::trace::SerialTrace;
 
 /** Defined response to look for from the device under test.
@@ -20,6 +23,57 @@ pub enum Criterion {
     Energy(EnergyCriterion),
     /// Serial-based activity tracing.
     SerialTrace(SerialTraceCriterion),
+    /// Sampled analog output.
+    Analog(AnalogCriterion),
+    /// Criterion on a named device under test rather than the default one.
+    Device(String, Box<Criterion>),
+}
+
+impl Criterion {
+    /** Apply the criterion to the named device under test.
+
+    Criteria apply to the [default device](crate::testbed::DEFAULT_DEVICE) unless given another one.
+     */
+    pub fn on(self, device: &str) -> Criterion {
+        Criterion::Device(device.to_string(), Box::new(self.into_target()))
+    }
+
+    /// Returns the name of the device under test the criterion applies to.
+    pub fn get_device(&self) -> &str {
+        match self {
+            Criterion::Device(ref device, _) => device,
+            _ => DEFAULT_DEVICE,
+        }
+    }
+
+    /// Returns the criterion without the device it applies to.
+    pub fn get_target(&self) -> &Criterion {
+        match self {
+            Criterion::Device(_, ref criterion) => criterion.get_target(),
+            criterion => criterion,
+        }
+    }
+
+    /** Returns how long after the start of the test the criterion can still be met.
+
+    Criteria without timing requirements return `None`;
+    they can be met at any point while the test runs.
+     */
+    pub fn get_window(&self) -> Option<Duration> {
+        match self {
+            Criterion::GPIO(ref c) => c.get_window(),
+            Criterion::SerialTrace(ref c) => c.get_window(),
+            Criterion::Device(_, ref c) => c.get_window(),
+            Criterion::Energy(_) | Criterion::Analog(_) => None,
+        }
+    }
+
+    fn into_target(self) -> Criterion {
+        match self {
+            Criterion::Device(_, criterion) => criterion.into_target(),
+            criterion => criterion,
+        }
+    }
 }
 
 impl Display for Criterion {
@@ -28,26 +82,485 @@ impl Display for Criterion {
             Criterion::GPIO(ref c) => write!(f, "GPIO activity: {}", c),
             Criterion::Energy(ref c) => write!(f, "Energy: {}", c),
             Criterion::SerialTrace(ref c) => write!(f, "Serial trace: {}", c),
+            Criterion::Analog(ref c) => write!(f, "Analog: {}", c),
+            Criterion::Device(ref device, ref c) => write!(f, "{} (device '{}')", c, device),
         }
     }
 }
 
-/// Trackable GPIO activity.
+/** Trackable GPIO activity.
+
+Device pins are watched for edges while the test runs;
+levels between edges follow from the level the pin starts the test at.
+ */
 #[allow(unused)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum GPIOCriterion {
     /// Any and all activity on a GPIO pin.
     Any(u8),
+    /// Number of edges on a pin (device pin, edges to count, required number).
+    Edges(u8, Trigger, Count),
+    /// Level of a pin at a point in time from the start of the test (device pin, time, level).
+    LevelAt(u8, Duration, Level),
+    /** Width of every pulse on a pin (device pin, pulse level, min, max).
+
+    A pulse starts with an edge to the pulse level and ends with the next edge back.
+    Pulses still going when the test ends are not judged; at least one pulse must complete.
+     */
+    PulseWidth(u8, Level, Duration, Duration),
+    /// Edges in order, each with optional timing requirements.
+    Sequence(EdgeSequence),
+    /// Time from each input the test drives on one pin to the response on another.
+    Latency(Latency),
+    /// Frequency, period jitter and duty cycle of a periodic signal on a pin.
+    Waveform(Waveform),
+}
+
+impl GPIOCriterion {
+    /// Returns the device pins the criterion watches.
+    pub fn get_pins(&self) -> Vec<u8> {
+        match self {
+            GPIOCriterion::Any(pin_no)
+            | GPIOCriterion::Edges(pin_no, _, _)
+            | GPIOCriterion::LevelAt(pin_no, _, _)
+            | GPIOCriterion::PulseWidth(pin_no, _, _, _) => vec![*pin_no],
+            GPIOCriterion::Sequence(sequence) => {
+                let mut pins: Vec<u8> = sequence.conditions.iter()
+                    .map(|condition| condition.pin_no)
+                    .collect();
+                pins.sort_unstable();
+                pins.dedup();
+                pins
+            },
+            GPIOCriterion::Latency(latency) => vec![latency.response_pin],
+            GPIOCriterion::Waveform(waveform) => vec![waveform.pin_no],
+        }
+    }
+
+    /// Returns how long after the start of the test the criterion can still be met, if it has timing requirements.
+    pub fn get_window(&self) -> Option<Duration> {
+        match self {
+            GPIOCriterion::LevelAt(_, time, _) => Some(*time),
+            GPIOCriterion::Sequence(sequence) => sequence.get_window(),
+            GPIOCriterion::Waveform(waveform) => waveform.window.map(|(_from, to)| to),
+            GPIOCriterion::Any(_)
+            | GPIOCriterion::Edges(..)
+            | GPIOCriterion::PulseWidth(..)
+            | GPIOCriterion::Latency(_) => None,
+        }
+    }
 }
 
 impl Display for GPIOCriterion {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             GPIOCriterion::Any(pin_no) => write!(f, "any output on device pin {}", pin_no),
+            GPIOCriterion::Edges(pin_no, edges, count) =>
+                write!(f, "{} {} on device pin {}", count, edges_name(*edges), pin_no),
+            GPIOCriterion::LevelAt(pin_no, time, level) =>
+                write!(f, "device pin {} {:?} at {:?}", pin_no, level, time),
+            GPIOCriterion::PulseWidth(pin_no, level, min, max) =>
+                write!(f, "{:?} pulses on device pin {} {:?} to {:?} wide", level, pin_no, min, max),
+            GPIOCriterion::Sequence(sequence) => write!(f, "edge sequence{}", sequence),
+            GPIOCriterion::Latency(latency) => write!(f, "{}", latency),
+            GPIOCriterion::Waveform(waveform) => write!(f, "{}", waveform),
         }
     }
 }
 
+/// Returns true if a pin changing to `level` is one of the `edges`.
+fn is_edge(edges: Trigger, level: Option<Level>) -> bool {
+    match edges {
+        Trigger::RisingEdge => level == Some(Level::High),
+        Trigger::FallingEdge => level == Some(Level::Low),
+        Trigger::Both => true,
+    }
+}
+
+fn edges_name(edges: Trigger) -> &'static str {
+    match edges {
+        Trigger::RisingEdge => "rising edges",
+        Trigger::FallingEdge => "falling edges",
+        Trigger::Both => "edges",
+    }
+}
+
+/// Number of occurrences required.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Count {
+    /// Exactly the given number
+    Exactly(u32),
+    /// The given number or more
+    AtLeast(u32),
+    /// The given number or fewer
+    AtMost(u32),
+}
+
+impl Count {
+    /// Returns true if `count` occurrences meet the requirement.
+    pub fn satisfied_by(&self, count: u32) -> bool {
+        match *self {
+            Count::Exactly(n) => count == n,
+            Count::AtLeast(n) => count >= n,
+            Count::AtMost(n) => count <= n,
+        }
+    }
+}
+
+impl Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Count::Exactly(n) => write!(f, "exactly {}", n),
+            Count::AtLeast(n) => write!(f, "at least {}", n),
+            Count::AtMost(n) => write!(f, "at most {}", n),
+        }
+    }
+}
+
+/// Component condition of an [`EdgeSequence`].
+#[derive(Clone, Debug)]
+pub struct EdgeCondition {
+    pin_no: u8,
+    level: Level,
+    timing: Option<(Timing, Duration)>,
+}
+
+impl EdgeCondition {
+    /// Create a condition met by an edge of the device pin to `level`.
+    pub fn new(pin_no: u8, level: Level) -> EdgeCondition {
+        EdgeCondition {
+            pin_no,
+            level,
+            timing: None,
+        }
+    }
+
+    /// Specify the timing requirements to meet the condition.
+    pub fn with_timing(self, time: Timing, tolerance: Duration) -> Self {
+        Self {
+            timing: Some((time, tolerance)),
+            ..self
+        }
+    }
+
+    /// Returns the device pin the edge is on.
+    pub fn get_pin(&self) -> u8 {
+        self.pin_no
+    }
+
+    /// Returns the level the edge goes to.
+    pub fn get_level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the time requirement.
+    pub fn get_offset(&self) -> Option<Timing> {
+        self.timing.as_ref()
+            .map(|(timing, _tolerance)| timing)
+            .copied()
+    }
+
+    /// Returns the timing tolerance.
+    pub fn get_tolerance(&self) -> Option<Duration> {
+        self.timing.as_ref()
+            .map(|(_timing, tolerance)| tolerance)
+            .copied()
+    }
+
+    /// Returns true if the response is an edge on the condition's pin to its level; timing is not checked.
+    pub fn satisfied_by(&self, response: &Response) -> bool {
+        response.get_pin() == self.pin_no
+            && response.get_level() == Some(self.level)
+    }
+}
+
+/// Ordered edges on one or more device pins, for [`GPIOCriterion::Sequence`].
+#[derive(Clone, Debug)]
+pub struct EdgeSequence {
+    conditions: Vec<EdgeCondition>,
+}
+
+impl EdgeSequence {
+    /// Create a new edge sequence.
+    pub fn new<'a, T>(conditions: T) -> EdgeSequence
+    where
+        T: IntoIterator<Item = &'a EdgeCondition>,
+    {
+        EdgeSequence {
+            conditions: conditions.into_iter()
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns how long after the start of the test the last timed condition can be met.
+    ///
+    /// Windows are worked out as for a [`SerialTraceCriterion::get_window`].
+    pub fn get_window(&self) -> Option<Duration> {
+        timed_window(&self.conditions)
+    }
+
+    /** Returns the [`Response`]s satisfying the sequence, in order.
+
+    Edges are matched the same way [`SerialTraceCriterion::align`] matches traces,
+    going back to try later edges for earlier conditions when the rest of the sequence cannot be met.
+     */
+    pub fn align<'a>(&self, t0: Instant, responses: &'a [Response]) -> Option<Vec<&'a Response>> {
+        timed_align(t0, t0, &self.conditions, responses)
+    }
+}
+
+impl Display for EdgeSequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for condition in &self.conditions {
+            write!(f, "\n  → device pin {} to {:?}", condition.pin_no, condition.level)?;
+
+            if let Some((timing, tolerance)) = condition.timing {
+                write!(f, " @ {:?}±{:?} from {}",
+                       timing.get_offset(),
+                       tolerance,
+                       match timing {
+                           Timing::Absolute(_) => "test start",
+                           Timing::Relative(_) => "last edge",
+                       })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/** Bounds on the time from a stimulus to the device's response, for [`GPIOCriterion::Latency`].
+
+Every input the test drives on the stimulus pin is an occurrence,
+including each edge of pulses and square waves.
+The response to an occurrence is the first edge on the response pin before the next occurrence;
+an occurrence without one is missed.
+ */
+#[derive(Clone, Debug)]
+pub struct Latency {
+    stimulus_pin: u8,
+    stimulus_edges: Trigger,
+    response_pin: u8,
+    response_edges: Trigger,
+    min: Duration,
+    max: Duration,
+}
+
+impl Latency {
+    /// Create a criterion for responses on `response_pin` within `min` to `max` of any input on `stimulus_pin`.
+    pub fn new(stimulus_pin: u8, response_pin: u8, min: Duration, max: Duration) -> Latency {
+        Latency {
+            stimulus_pin,
+            stimulus_edges: Trigger::Both,
+            response_pin,
+            response_edges: Trigger::Both,
+            min,
+            max,
+        }
+    }
+
+    /// Only count inputs that drive the stimulus pin to the given edges.
+    pub fn with_stimulus_edges(self, edges: Trigger) -> Self {
+        Self {
+            stimulus_edges: edges,
+            ..self
+        }
+    }
+
+    /// Only count the given edges on the response pin as responses.
+    pub fn with_response_edges(self, edges: Trigger) -> Self {
+        Self {
+            response_edges: edges,
+            ..self
+        }
+    }
+
+    /// Returns the device pin the test drives.
+    pub fn get_stimulus_pin(&self) -> u8 {
+        self.stimulus_pin
+    }
+
+    /// Returns the device pin the device responds on.
+    pub fn get_response_pin(&self) -> u8 {
+        self.response_pin
+    }
+
+    /// Returns the shortest allowed latency.
+    pub fn get_min(&self) -> Duration {
+        self.min
+    }
+
+    /// Returns the longest allowed latency.
+    pub fn get_max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns true if the latency is within bounds.
+    pub fn allows(&self, latency: Duration) -> bool {
+        self.min <= latency && latency <= self.max
+    }
+
+    /** Returns the latency of each occurrence, in the order they were applied, or `None` for missed ones.
+
+    `applied` are the operations performed during the test on the device the criterion applies to,
+    and `responses` the pin changes seen on it.
+    Latencies are measured from when the input took effect.
+    Responses are looked for from when the input was scheduled,
+    so a response recorded while the input was still being driven counts as immediate.
+     */
+    pub fn measure<'a, T>(&self, applied: T, responses: &[Response]) -> Vec<Option<Duration>>
+    where
+        T: IntoIterator<Item = &'a AppliedOperation>,
+    {
+        let stimuli: Vec<&AppliedOperation> = applied.into_iter()
+            .filter(|applied| applied.get_operation().get_action().and_then(|action| action.get_pin())
+                    == Some(self.stimulus_pin))
+            // Only digital inputs make edges; analog ones leave the level unknown.
+            .filter(|applied| applied.get_level().is_some())
+            .filter(|applied| is_edge(self.stimulus_edges, applied.get_level()))
+            .collect();
+
+        stimuli.iter()
+            .enumerate()
+            .map(|(idx, stimulus)| {
+                let next = stimuli.get(idx + 1).map(|next| next.get_scheduled());
+                responses.iter()
+                    .filter(|response| response.get_pin() == self.response_pin)
+                    .filter(|response| is_edge(self.response_edges, response.get_level()))
+                    .map(Response::get_time)
+                    .find(|t| *t >= stimulus.get_scheduled() && next.is_none_or(|next| *t < next))
+                    .map(|t| t.saturating_duration_since(stimulus.get_applied()))
+            })
+            .collect()
+    }
+}
+
+impl Display for Latency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} on device pin {} within {:?} to {:?} of {} driven on device pin {}",
+               edges_name(self.response_edges),
+               self.response_pin,
+               self.min,
+               self.max,
+               edges_name(self.stimulus_edges),
+               self.stimulus_pin)
+    }
+}
+
+/** Expected shape of a periodic signal on a device pin, for [`GPIOCriterion::Waveform`].
+
+Periods run from one rising edge to the next, so the signal must rise at least twice within the window.
+Frequency tolerances are relative to the expected frequency,
+while duty cycle tolerances are in percentage points;
+a LED blinking at 2 Hz ± 5 % with a 50 % duty cycle is
+`Waveform::new(pin_no, 2.0, 5.0).with_duty_cycle(50.0, 5.0)`.
+ */
+#[derive(Clone, Debug)]
+pub struct Waveform {
+    pin_no: u8,
+    frequency: f64,
+    frequency_tolerance: f64,
+    duty_cycle: Option<(f64, f64)>,
+    max_jitter: Option<Duration>,
+    window: Option<(Duration, Duration)>,
+}
+
+impl Waveform {
+    /// Create a criterion for a signal on the device pin at `frequency` Hz, give or take `tolerance` percent.
+    pub fn new(pin_no: u8, frequency: f64, tolerance: f64) -> Waveform {
+        Waveform {
+            pin_no,
+            frequency,
+            frequency_tolerance: tolerance,
+            duty_cycle: None,
+            max_jitter: None,
+            window: None,
+        }
+    }
+
+    /// Require the signal to be high for `percent` of each period, give or take `tolerance` percentage points.
+    pub fn with_duty_cycle(self, percent: f64, tolerance: f64) -> Self {
+        Self {
+            duty_cycle: Some((percent, tolerance)),
+            ..self
+        }
+    }
+
+    /// Require the standard deviation of the period to be at most `jitter`.
+    pub fn with_max_jitter(self, jitter: Duration) -> Self {
+        Self {
+            max_jitter: Some(jitter),
+            ..self
+        }
+    }
+
+    /// Only analyze edges between `from` and `to` after the start of the test, instead of the whole test.
+    pub fn with_window(self, from: Duration, to: Duration) -> Self {
+        Self {
+            window: Some((from, to)),
+            ..self
+        }
+    }
+
+    /// Returns the device pin the signal is on.
+    pub fn get_pin(&self) -> u8 {
+        self.pin_no
+    }
+
+    /// Returns the expected frequency in Hz and its tolerance in percent.
+    pub fn get_frequency(&self) -> (f64, f64) {
+        (self.frequency, self.frequency_tolerance)
+    }
+
+    /// Returns the expected duty cycle and its tolerance, both in percent, if required.
+    pub fn get_duty_cycle(&self) -> Option<(f64, f64)> {
+        self.duty_cycle
+    }
+
+    /// Returns the largest allowed period jitter, if limited.
+    pub fn get_max_jitter(&self) -> Option<Duration> {
+        self.max_jitter
+    }
+
+    /// Returns the part of the test analyzed, from the start of the test, if limited.
+    pub fn get_window(&self) -> Option<(Duration, Duration)> {
+        self.window
+    }
+
+    /// Returns true if a measured frequency in Hz is within tolerance.
+    pub fn allows_frequency(&self, frequency: f64) -> bool {
+        (frequency - self.frequency).abs() <= self.frequency * self.frequency_tolerance / 100.0
+    }
+
+    /// Returns true if a measured duty cycle in percent is within tolerance, or no duty cycle is required.
+    pub fn allows_duty_cycle(&self, percent: f64) -> bool {
+        self.duty_cycle
+            .is_none_or(|(expected, tolerance)| (percent - expected).abs() <= tolerance)
+    }
+
+    /// Returns true if a measured period jitter is within bounds, or jitter is not limited.
+    pub fn allows_jitter(&self, jitter: Duration) -> bool {
+        self.max_jitter.is_none_or(|max| jitter <= max)
+    }
+}
+
+impl Display for Waveform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "device pin {} at {}Hz ± {}%", self.pin_no, self.frequency, self.frequency_tolerance)?;
+        if let Some((percent, tolerance)) = self.duty_cycle {
+            write!(f, ", {}% ± {}% duty", percent, tolerance)?;
+        }
+        if let Some(jitter) = self.max_jitter {
+            write!(f, ", at most {:?} jitter", jitter)?;
+        }
+        if let Some((from, to)) = self.window {
+            write!(f, " from {:?} to {:?}", from, to)?;
+        }
+        Ok(())
+    }
+}
+
 /// Timing requirement.
 #[derive(Copy, Clone, Debug)]
 pub enum Timing {
@@ -78,6 +591,126 @@ impl Display for Timing {
     }
 }
 
+/** Condition in an ordered criterion, optionally met at a [`Timing`] give or take a tolerance.
+
+A relative condition is timed from the event meeting the condition before it.
+When that condition is untimed, its event can come at any point in the test,
+so the window of the relative condition is open-ended and left to the test's tail.
+ */
+trait TimedCondition {
+    /// What the condition is met by.
+    type Event;
+
+    /// Returns the timing requirement and tolerance of the condition, if any.
+    fn timing(&self) -> Option<(Timing, Duration)>;
+
+    /// Returns true if `event` meets the condition, regardless of timing.
+    fn met_by(&self, event: &Self::Event) -> bool;
+
+    /// Returns when `event` happened.
+    fn time_of(event: &Self::Event) -> Instant;
+}
+
+impl TimedCondition for EdgeCondition {
+    type Event = Response;
+
+    fn timing(&self) -> Option<(Timing, Duration)> {
+        self.timing
+    }
+
+    fn met_by(&self, response: &Response) -> bool {
+        self.satisfied_by(response)
+    }
+
+    fn time_of(response: &Response) -> Instant {
+        response.get_time()
+    }
+}
+
+impl TimedCondition for SerialTraceCondition {
+    type Event = SerialTrace;
+
+    fn timing(&self) -> Option<(Timing, Duration)> {
+        self.timing
+    }
+
+    fn met_by(&self, trace: &SerialTrace) -> bool {
+        self.satisfied_by(trace)
+    }
+
+    fn time_of(trace: &SerialTrace) -> Instant {
+        trace.get_time()
+    }
+}
+
+/// Returns how long after the start of the test the last timed condition with a known window can be met, if any.
+fn timed_window<C: TimedCondition>(conditions: &[C]) -> Option<Duration> {
+    // Unknown after an untimed condition, until an absolute one.
+    let mut latest = Some(Duration::ZERO);
+    let mut window = None;
+    for condition in conditions {
+        latest = match condition.timing() {
+            Some((Timing::Absolute(offset), tolerance)) => Some(offset + tolerance),
+            Some((Timing::Relative(offset), tolerance)) => latest.map(|latest| latest + offset + tolerance),
+            None => None,
+        };
+        window = window.max(latest);
+    }
+    window
+}
+
+/** Attempt to satisfy `conditions` in order with `events`, the test having started at `t0` and the previous event at `tp`.
+
+# Algorithm overview
+
+Advances through:
+- ordering of conditions
+- sequence of events captured during the test
+
+For each condition, advances through the events to find a matching event.
+Upon finding a matching event, the function advances to the next condition.
+If a condition fails to find a matching event, then we back out to the previous condition.
+The previous condition seeks another matching event.
+If a condition advances to the last event and does not find a match, then the function returns `None`.
+ */
+fn timed_align<'a, C: TimedCondition>(t0: Instant,
+                                      tp: Instant,
+                                      conditions: &[C],
+                                      events: &'a [C::Event]) -> Option<Vec<&'a C::Event>>
+{
+    let condition = match conditions.first() {
+        Some(condition) => condition,
+        None => return Some(Vec::new()),
+    };
+
+    for (idx, event) in events.iter().enumerate() {
+        if !condition.met_by(event) {
+            continue;
+        }
+        let t = C::time_of(event);
+        let timing_matches = match condition.timing() {
+            Some((timing, tolerance)) => {
+                let t_req = match timing {
+                    Timing::Absolute(d) => t0 + d,
+                    Timing::Relative(d) => tp + d,
+                };
+                t_req.max(t) - t_req.min(t) < tolerance
+            },
+            None => true,
+        };
+
+        if timing_matches {
+            if let Some(rest) = timed_align(t0, t, &conditions[1..], &events[idx + 1..]) {
+                let mut matches = vec![event];
+                matches.extend(rest);
+                return Some(matches);
+            }
+        }
+    }
+
+    None
+}
+
 /// Energy criterion specification details.
 #[derive(Clone, Debug)]
 pub struct EnergyCriterion {
@@ -187,6 +820,150 @@ impl Display for EnergyStat {
     }
 }
 
+/** Analog criterion specification details.
+
+Samples of a device pin are reduced to a single value by an [`AnalogStat`],
+which is then checked against the optional bounds like an [`EnergyCriterion`].
+ */
+#[derive(Clone, Debug)]
+pub struct AnalogCriterion {
+    pin_no: u8,
+    stat: AnalogStat,
+    min: Option<f32>,
+    max: Option<f32>,
+}
+
+impl AnalogCriterion {
+    /// Create a new AnalogCriterion for the given device pin.
+    pub fn new(pin_no: u8, stat: AnalogStat) -> Self {
+        Self {
+            pin_no,
+            stat,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// Specify a minimum value for the criterion.
+    pub fn with_min(self, min: f32) -> Self {
+        Self {
+            min: Some(min),
+            ..self
+        }
+    }
+
+    /// Specify a maximum value for the criterion.
+    pub fn with_max(self, max: f32) -> Self {
+        Self {
+            max: Some(max),
+            ..self
+        }
+    }
+
+    /// Returns the device pin the criterion applies to.
+    pub fn get_pin(&self) -> u8 {
+        self.pin_no
+    }
+
+    /// Returns the analog statistic.
+    pub fn get_stat(&self) -> AnalogStat {
+        self.stat
+    }
+
+    /** Returns true if the given value violates the criterion.
+
+    If there is no part of the criterion can be violated this function will return None.
+     */
+    pub fn violated(&self, value: f32) -> Option<bool> {
+        if self.min.is_none() && self.max.is_none() {
+            None
+        } else {
+            let b = self.min.map(|min| value < min)
+                .unwrap_or(false)
+                ||
+                self.max.map(|max| value > max)
+                .unwrap_or(false);
+
+            Some(b)
+        }
+    }
+}
+
+impl Display for AnalogCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unit = self.stat.unit();
+
+        write!(f, "device pin {} {} ", self.pin_no, self.stat)?;
+        write!(f, "(min: {},", self.min.map(|x| format!("{:.2}{}", x, unit)).unwrap_or("-".to_string()))?;
+        write!(f, " max: {})", self.max.map(|x| format!("{:.2}{}", x, unit)).unwrap_or("-".to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Direction a signal passes a threshold in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Crossing {
+    /// From below the threshold to at or above it
+    Rising,
+    /// From at or above the threshold to below it
+    Falling,
+    /// Either direction
+    Either,
+}
+
+impl Display for Crossing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Crossing::Rising => write!(f, "rising"),
+            Crossing::Falling => write!(f, "falling"),
+            Crossing::Either => write!(f, "rising or falling"),
+        }
+    }
+}
+
+/// Trackable analog signal statistics; voltages are in millivolts.
+#[derive(Clone, Copy, Debug)]
+pub enum AnalogStat {
+    /// Track the lowest sampled voltage.
+    Min,
+    /// Track the highest sampled voltage.
+    Max,
+    /// Count the times the signal passes a threshold voltage.
+    Crossings(f32, Crossing),
+    /** Track how long after the start of the test the signal settles.
+
+    The signal has settled once it enters and then stays within the band around the target voltage:
+    `SettlingTime(target, band)` settles within `target ± band`.
+    The value is in milliseconds.
+     */
+    SettlingTime(f32, f32),
+}
+
+impl AnalogStat {
+    /// Returns the unit values of the statistic are in.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            AnalogStat::Min | AnalogStat::Max => "mV",
+            AnalogStat::Crossings(_, _) => "",
+            AnalogStat::SettlingTime(_, _) => "ms",
+        }
+    }
+}
+
+impl Display for AnalogStat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnalogStat::Min => write!(f, "min voltage"),
+            AnalogStat::Max => write!(f, "max voltage"),
+            AnalogStat::Crossings(threshold, direction) =>
+                write!(f, "{} crossings of {:.2}mV", direction, threshold),
+            AnalogStat::SettlingTime(target, band) =>
+                write!(f, "settling time to {:.2}±{:.2}mV", target, band),
+        }
+    }
+}
+
 /// Component condition of a [`SerialTraceCriterion`].
 #[allow(unused)]
 #[derive(Clone, Debug)]
@@ -269,68 +1046,20 @@ impl SerialTraceCriterion {
         }
     }
 
-    /// Returns the [`SerialTrace`]s satisfying the criterion.
-    pub fn align<'a>(&self, t0: Instant, traces: &'a [SerialTrace]) -> Option<Vec<&'a SerialTrace>> {
-        SerialTraceCriterion::rec_align(
-            t0, t0, self.conditions.as_slice(), traces)
-    }
-
-    /** Attempt to satisfy conditions with the provided [`SerialTrace`]s.
-
-    # Algorithm overview
-
-    Advances through:
-    - ordering of trace conditions
-    - sequence of trace events captured during the test
+    /** Returns how long after the start of the test the last timed condition can be met.
 
-    For each trace condition, advances through the trace events to find a matching trace event.
-    Upon finding a matching trace condition, the function advances to the next trace condition.
-    If a trace condition fails to find a matching trace event, then we back out to the previous trace condition.
-    The previous trace condition seeks another matching trace event.
-    If a trace condition advances to the last trace event and does not find a match, then the function returns false.
+    An absolute condition can be met up to its offset plus tolerance after the start of the test;
+    a relative condition up to its offset plus tolerance after the latest the previous condition can be met.
+    Relative conditions following an untimed condition have no known window and do not count.
+    Returns `None` if no condition has a known window.
      */
-    fn rec_align<'a>(t0: Instant,
-                     tp: Instant,
-                     conditions: &[SerialTraceCondition],
-                     events: &'a [SerialTrace]) -> Option<Vec<&'a SerialTrace>>
-    {
-        let mut matches = Vec::new();
-
-        if conditions.len() > 0 {
-            let condition = &conditions[0];
-            for (event, idx) in events.iter().zip(0..) {
-                if condition.satisfied_by(event) {
-                    let timing_matches: bool = {
-                        if let Some(timing) = condition.get_offset() {
-                            let t_req = match timing {
-                                Timing::Absolute(d) => t0 + d,
-                                Timing::Relative(d) => tp + d,
-                            };
-
-                            let since = t_req.max(event.get_time()) - t_req.min(event.get_time());
-                            since < condition.get_tolerance().unwrap()
-                        } else {
-                            true
-                        }
-                    };
-
-                    if timing_matches {
-                        let rest = SerialTraceCriterion::rec_align(
-                            t0, event.get_time(), &conditions[1..], &events[idx+1..]);
-                        if let Some(rest) = rest {
-                            matches.push(event);
-                            matches.extend(rest.into_iter());
-
-                            return Some(matches);
-                        }
-                    }
-                }
-            }
+    pub fn get_window(&self) -> Option<Duration> {
+        timed_window(&self.conditions)
+    }
 
-            None
-        } else {
-            Some(Vec::new())
-        }
+    /// Returns the [`SerialTrace`]s satisfying the criterion.
+    pub fn align<'a>(&self, t0: Instant, traces: &'a [SerialTrace]) -> Option<Vec<&'a SerialTrace>> {
+        timed_align(t0, t0, &self.conditions, traces)
     }
 }
 
@@ -1,17 +1,21 @@
 //! Process and evaluate test data.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::fmt::{self, Display};
 
 use crate::criteria::{
+    AnalogStat,
     Criterion,
+    Crossing,
     GPIOCriterion,
     EnergyStat,
 };
-use crate::testbed::Observation;
+use crate::hal::{Level, Trigger};
+use crate::test::{Execution, Response, Test};
+use crate::testbed::{DeviceObservation, Observation};
 
 /// Judged outcome.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Status {
     /// Execution finished without error.
     Complete,
@@ -45,6 +49,7 @@ pub struct Outcome<'a> {
     criterion: &'a Criterion,
     status: Status,
     message: Option<String>,
+    values: Vec<f64>,
 }
 
 impl<'a> Outcome<'a> {
@@ -58,9 +63,16 @@ impl<'a> Outcome<'a> {
             criterion,
             status,
             message,
+            values: Vec::new(),
         }
     }
 
+    /// Attach the quantities the criterion was judged on.
+    pub fn with_values(mut self, values: Vec<f64>) -> Self {
+        self.values = values;
+        self
+    }
+
     /// Return the criterion this Outcome is for.
     pub fn source_criterion(&self) -> &'a Criterion {
         self.criterion
@@ -75,6 +87,18 @@ impl<'a> Outcome<'a> {
     pub fn message(&self) -> Option<&String> {
         self.message.as_ref()
     }
+
+    /** Return the quantities the criterion was judged on.
+
+    Energy criteria measure their statistic (mJ or mJ/s),
+    analog criteria their statistic (mV, a number of crossings, or a settling time in ms),
+    serial trace criteria the offset of each aligned trace from the start of the test (ms),
+    and GPIO criteria a number of edges, each pulse width, edge offset or latency (ms),
+    or a signal's frequency (Hz), period jitter (ms) and duty cycle (%).
+     */
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
 }
 
 /// Result of evaluating test data.
@@ -98,6 +122,21 @@ impl<'a> Evaluation<'a> {
             data,
         }
     }
+
+    /// Return the overall outcome of the evaluation.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Return the outcomes of the evaluated criteria.
+    pub fn outcomes(&self) -> &[Outcome<'a>] {
+        &self.outcomes
+    }
+
+    /// Return the data that was evaluated.
+    pub fn source_observation(&self) -> &'a Observation<'a> {
+        self.data
+    }
 }
 
 impl<'a> Display for Evaluation<'a> {
@@ -177,13 +216,514 @@ impl Evaluator for StandardEvaluator {
     }
 }
 
+/** Distribution of a quantity measured several times, such as over repeated runs of a test.
+
+Samples that are not numbers are left out.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution {
+    // Kept sorted for percentiles.
+    samples: Vec<f64>,
+}
+
+impl Distribution {
+    /// Create a new `Distribution` of the given samples.
+    pub fn new<T>(samples: T) -> Distribution
+    where
+        T: IntoIterator<Item = f64>,
+    {
+        let mut samples: Vec<f64> = samples.into_iter()
+            .filter(|sample| !sample.is_nan())
+            .collect();
+        samples.sort_by(f64::total_cmp);
+        Distribution { samples }
+    }
+
+    /// Return the number of samples.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Return true if there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Return the smallest sample.
+    pub fn min(&self) -> Option<f64> {
+        self.samples.first().copied()
+    }
+
+    /// Return the largest sample.
+    pub fn max(&self) -> Option<f64> {
+        self.samples.last().copied()
+    }
+
+    /// Return the arithmetic mean of the samples.
+    pub fn mean(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.iter().sum::<f64>() / self.samples.len() as f64)
+    }
+
+    /// Return the sample standard deviation, which is 0 for a single sample.
+    pub fn stddev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        if self.samples.len() < 2 {
+            return Some(0.0);
+        }
+        let squares: f64 = self.samples.iter()
+            .map(|sample| (sample - mean).powi(2))
+            .sum();
+        Some((squares / (self.samples.len() - 1) as f64).sqrt())
+    }
+
+    /** Return the `p`th percentile (0 to 100) of the samples.
+
+    Values between samples are interpolated linearly.
+     */
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let rank = p.clamp(0.0, 100.0) / 100.0 * (self.samples.len() - 1) as f64;
+        let below = self.samples[rank.floor() as usize];
+        let above = self.samples[rank.ceil() as usize];
+        Some(below + (above - below) * rank.fract())
+    }
+}
+
+impl Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.mean(), self.stddev()) {
+            (Some(mean), Some(stddev)) => write!(
+                f, "n={}, mean {:.3}, stddev {:.3}, min {:.3}, p50 {:.3}, p90 {:.3}, p99 {:.3}, max {:.3}",
+                self.len(),
+                mean,
+                stddev,
+                self.min().unwrap(),
+                self.percentile(50.0).unwrap(),
+                self.percentile(90.0).unwrap(),
+                self.percentile(99.0).unwrap(),
+                self.max().unwrap()),
+            _ => write!(f, "no samples"),
+        }
+    }
+}
+
+/// Counts how many of `statuses` are `status`.
+fn count_status(statuses: &[Status], status: Status) -> usize {
+    statuses.iter()
+        .filter(|s| **s == status)
+        .count()
+}
+
+/// Outcomes of one criterion over repeated runs of a test.
+pub struct CriterionSummary<'a> {
+    criterion: &'a Criterion,
+    statuses: Vec<Status>,
+    values: Vec<Distribution>,
+}
+
+impl<'a> CriterionSummary<'a> {
+    /// Return the criterion this summary is for.
+    pub fn source_criterion(&self) -> &'a Criterion {
+        self.criterion
+    }
+
+    /// Return the number of runs the criterion was evaluated in with the given status.
+    pub fn count(&self, status: Status) -> usize {
+        count_status(&self.statuses, status)
+    }
+
+    /** Return the distribution of each quantity the criterion was judged on.
+
+    The distributions are in the order of [`Outcome::values`].
+     */
+    pub fn values(&self) -> &[Distribution] {
+        &self.values
+    }
+}
+
+/// Evaluations of every run of a repeated test, summarized.
+pub struct AggregateEvaluation<'a> {
+    test: &'a Test,
+    statuses: Vec<Status>,
+    criteria: Vec<CriterionSummary<'a>>,
+}
+
+impl<'a> AggregateEvaluation<'a> {
+    /// Return the test the runs are of.
+    pub fn source_test(&self) -> &'a Test {
+        self.test
+    }
+
+    /// Return the number of runs.
+    pub fn runs(&self) -> usize {
+        self.statuses.len()
+    }
+
+    /// Return the number of runs with the given overall status.
+    pub fn count(&self, status: Status) -> usize {
+        count_status(&self.statuses, status)
+    }
+
+    /// Return the fraction of runs that completed without failing a criterion.
+    pub fn pass_rate(&self) -> f64 {
+        if self.statuses.is_empty() {
+            return 0.0;
+        }
+        let passed = self.count(Status::Pass) + self.count(Status::Complete);
+        passed as f64 / self.runs() as f64
+    }
+
+    /// Return the summaries of the test's criteria, in the order they are defined.
+    pub fn criteria(&self) -> &[CriterionSummary<'a>] {
+        &self.criteria
+    }
+}
+
+impl<'a> Display for AggregateEvaluation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}\t{:.1}% passed ({} runs: {} passed, {} complete, {} failed, {} errors)",
+               self.test.get_id(),
+               self.pass_rate() * 100.0,
+               self.runs(),
+               self.count(Status::Pass),
+               self.count(Status::Complete),
+               self.count(Status::Fail),
+               self.count(Status::Error))?;
+
+        for summary in &self.criteria {
+            writeln!(f, "  - {} ({} passed, {} failed, {} errors)",
+                   summary.source_criterion(),
+                   summary.count(Status::Pass),
+                   summary.count(Status::Fail),
+                   summary.count(Status::Error))?;
+            for distribution in &summary.values {
+                writeln!(f, "    {}", distribution)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/** Summarize the evaluations of repeated runs of tests.
+
+Evaluations are grouped by test identifier, in the order each test is first seen.
+Runs that did not execute count towards the overall statuses but not towards the criteria.
+ */
+pub fn aggregate<'a>(evaluations: &[Evaluation<'a>]) -> Vec<AggregateEvaluation<'a>> {
+    let mut aggregates: Vec<AggregateEvaluation<'a>> = Vec::new();
+    let mut samples: Vec<Vec<Vec<Vec<f64>>>> = Vec::new();
+
+    for evaluation in evaluations {
+        let test = evaluation.source_observation().source_test();
+        let idx = match aggregates.iter().position(|aggregate| aggregate.test.get_id() == test.get_id()) {
+            Some(idx) => idx,
+            None => {
+                aggregates.push(AggregateEvaluation {
+                    test,
+                    statuses: Vec::new(),
+                    criteria: test.get_criteria().iter()
+                        .map(|criterion| CriterionSummary {
+                            criterion,
+                            statuses: Vec::new(),
+                            values: Vec::new(),
+                        })
+                        .collect(),
+                });
+                samples.push(vec![Vec::new(); test.get_criteria().len()]);
+                aggregates.len() - 1
+            },
+        };
+
+        aggregates[idx].statuses.push(evaluation.status());
+        let iter = aggregates[idx].criteria.iter_mut()
+            .zip(samples[idx].iter_mut())
+            .zip(evaluation.outcomes());
+        for ((summary, criterion_samples), outcome) in iter {
+            summary.statuses.push(outcome.status());
+            for (value_no, value) in outcome.values().iter().enumerate() {
+                if criterion_samples.len() <= value_no {
+                    criterion_samples.push(Vec::new());
+                }
+                criterion_samples[value_no].push(*value);
+            }
+        }
+    }
+
+    for (aggregate, test_samples) in aggregates.iter_mut().zip(samples) {
+        for (summary, criterion_samples) in aggregate.criteria.iter_mut().zip(test_samples) {
+            summary.values = criterion_samples.into_iter()
+                .map(Distribution::new)
+                .collect();
+        }
+    }
+
+    aggregates
+}
+
+/// Returns the times the samples pass `threshold` in the given direction.
+fn analog_crossings(samples: &[(Instant, f32)], threshold: f32, direction: Crossing) -> Vec<Instant> {
+    samples.windows(2)
+        .filter(|pair| {
+            let (before, after) = (pair[0].1, pair[1].1);
+            let rising = before < threshold && after >= threshold;
+            let falling = before >= threshold && after < threshold;
+            match direction {
+                Crossing::Rising => rising,
+                Crossing::Falling => falling,
+                Crossing::Either => rising || falling,
+            }
+        })
+        .map(|pair| pair[1].0)
+        .collect()
+}
+
+/** Returns when the samples enter `target ± band` for the last time.
+
+Returns None if the last sample is outside of the band.
+ */
+fn analog_settling(samples: &[(Instant, f32)], target: f32, band: f32) -> Option<Instant> {
+    let within = |v: f32| (v - target).abs() <= band;
+    let last_outside = samples.iter()
+        .rposition(|(_t, v)| !within(*v));
+
+    match last_outside {
+        Some(idx) => samples.get(idx + 1).map(|(t, _v)| *t),
+        None => samples.first().map(|(t, _v)| *t),
+    }
+}
+
+/// Returns the widths of the completed pulses at `level` on a device pin.
+fn gpio_pulse_widths(responses: &[Response], pin_no: u8, level: Level) -> Vec<Duration> {
+    let mut widths = Vec::new();
+    let mut start = None;
+    for response in responses.iter().filter(|response| response.get_pin() == pin_no) {
+        match (start, response.get_level()) {
+            (None, Some(l)) if l == level => start = Some(response.get_time()),
+            (Some(t), Some(l)) if l != level => {
+                widths.push(response.get_time().saturating_duration_since(t));
+                start = None;
+            },
+            _ => (),
+        }
+    }
+    widths
+}
+
+/** Frequency, period jitter and duty cycle of a periodic signal on a device pin.
+
+Periods run from one rising edge to the next.
+A period without a falling edge counts as high throughout.
+ */
+#[derive(Clone, Debug)]
+pub struct WaveformAnalysis {
+    periods: Distribution,
+    high_times: Distribution,
+}
+
+impl WaveformAnalysis {
+    /** Analyze the edges on the device pin from `from` to `to`.
+
+    Returns `None` if the pin did not rise at least twice, as there is no full period to analyze.
+     */
+    pub fn new(responses: &[Response], pin_no: u8, from: Instant, to: Instant) -> Option<WaveformAnalysis> {
+        let edges: Vec<&Response> = responses.iter()
+            .filter(|response| response.get_pin() == pin_no)
+            .filter(|response| from <= response.get_time() && response.get_time() <= to)
+            .collect();
+        let rising: Vec<Instant> = edges.iter()
+            .filter(|response| response.get_level() == Some(Level::High))
+            .map(|response| response.get_time())
+            .collect();
+        if rising.len() < 2 {
+            return None;
+        }
+
+        let mut periods = Vec::new();
+        let mut high_times = Vec::new();
+        for period in rising.windows(2) {
+            let (start, end) = (period[0], period[1]);
+            let fall = edges.iter()
+                .filter(|response| response.get_level() == Some(Level::Low))
+                .map(|response| response.get_time())
+                .find(|t| start < *t && *t < end)
+                .unwrap_or(end);
+            periods.push((end - start).as_secs_f64() * 1000.0);
+            high_times.push((fall - start).as_secs_f64() * 1000.0);
+        }
+
+        Some(WaveformAnalysis {
+            periods: Distribution::new(periods),
+            high_times: Distribution::new(high_times),
+        })
+    }
+
+    /// Return the distribution of the period lengths, in milliseconds.
+    pub fn periods(&self) -> &Distribution {
+        &self.periods
+    }
+
+    /// Return the frequency in Hz, from the mean period.
+    pub fn frequency(&self) -> f64 {
+        1000.0 / self.periods.mean().unwrap()
+    }
+
+    /// Return the period jitter, as the standard deviation of the period.
+    pub fn jitter(&self) -> Duration {
+        Duration::from_secs_f64(self.periods.stddev().unwrap() / 1000.0)
+    }
+
+    /// Return the share of the period the signal is high, in percent.
+    pub fn duty_cycle(&self) -> f64 {
+        self.high_times.mean().unwrap() / self.periods.mean().unwrap() * 100.0
+    }
+}
+
+impl Display for WaveformAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.3}Hz, {:?} jitter, {:.1}% duty over {} periods",
+               self.frequency(),
+               self.jitter(),
+               self.duty_cycle(),
+               self.periods.len())
+    }
+}
+
+/// Evaluate a GPIO criterion against the pin changes seen on a device.
+fn evaluate_gpio(criterion: &GPIOCriterion,
+                 device: &DeviceObservation,
+                 execution: &Execution,
+                 runtime: Duration,
+                 values: &mut Vec<f64>) -> (Status, Option<String>)
+{
+    let t0 = execution.get_start();
+    let responses = device.gpio_responses().as_slice();
+    match criterion {
+        GPIOCriterion::Any(_pin) => (Status::Complete, None),
+
+        GPIOCriterion::Edges(pin_no, edges, count) => {
+            let seen = responses.iter()
+                .filter(|response| response.get_pin() == *pin_no)
+                .filter(|response| match edges {
+                    Trigger::RisingEdge => response.get_level() == Some(Level::High),
+                    Trigger::FallingEdge => response.get_level() == Some(Level::Low),
+                    Trigger::Both => true,
+                })
+                .count() as u32;
+
+            values.push(seen as f64);
+            let status = if count.satisfied_by(seen) { Status::Pass } else { Status::Fail };
+            (status, Some(format!("{} edges", seen)))
+        },
+
+        GPIOCriterion::LevelAt(pin_no, time, level) => {
+            match device.level_at(*pin_no, t0 + *time) {
+                Some(seen) => {
+                    let status = if seen == *level { Status::Pass } else { Status::Fail };
+                    (status, Some(format!("{:?} at {:?}", seen, time)))
+                },
+                None => (Status::Error, Some(format!("level of device pin {} unknown", pin_no))),
+            }
+        },
+
+        GPIOCriterion::PulseWidth(pin_no, level, min, max) => {
+            let widths = gpio_pulse_widths(responses, *pin_no, *level);
+            values.extend(widths.iter().map(|width| width.as_secs_f64() * 1000.0));
+            match (widths.iter().min(), widths.iter().max()) {
+                (Some(shortest), Some(longest)) => {
+                    let status = if shortest >= min && longest <= max { Status::Pass } else { Status::Fail };
+                    (status, Some(format!("{} pulses, {:?} to {:?} wide", widths.len(), shortest, longest)))
+                },
+                _ => (Status::Fail, Some("no complete pulses".to_string())),
+            }
+        },
+
+        GPIOCriterion::Sequence(sequence) => {
+            if let Some(aligned) = sequence.align(t0, responses) {
+                values.extend(aligned.iter()
+                    .map(|response| response.get_offset(t0).as_secs_f64() * 1000.0));
+                let offsets: Vec<String> = aligned.iter()
+                    .map(|response| format!("@{:?}", response.get_offset(t0)))
+                    .collect();
+                (Status::Pass, Some(format!("Satisfied by: {}", offsets.join(" → "))))
+            } else {
+                (Status::Fail, None)
+            }
+        },
+
+        GPIOCriterion::Latency(latency) => {
+            let applied = execution.get_applied_operations().iter()
+                .filter(|applied| applied.get_operation().get_device() == device.name());
+            let occurrences = latency.measure(applied, responses);
+            if occurrences.is_empty() {
+                let message = format!("no input on device pin {}", latency.get_stimulus_pin());
+                return (Status::Fail, Some(message));
+            }
+
+            let latencies: Vec<Duration> = occurrences.iter().flatten().copied().collect();
+            let missed = occurrences.len() - latencies.len();
+            values.extend(latencies.iter().map(|latency| latency.as_secs_f64() * 1000.0));
+
+            let status = if missed == 0 && latencies.iter().all(|l| latency.allows(*l)) {
+                Status::Pass
+            } else {
+                Status::Fail
+            };
+            let distribution = Distribution::new(latencies.iter().map(|l| l.as_secs_f64() * 1000.0));
+            (status, Some(format!("{} of {} responded, latency (ms): {}",
+                                  latencies.len(), occurrences.len(), distribution)))
+        },
+
+        GPIOCriterion::Waveform(waveform) => {
+            let (from, to) = match waveform.get_window() {
+                Some((from, to)) => (t0 + from, t0 + to),
+                // Responses are collected for the whole runtime, after the last operation too.
+                None => (t0, t0 + runtime),
+            };
+            match WaveformAnalysis::new(responses, waveform.get_pin(), from, to) {
+                Some(analysis) => {
+                    values.push(analysis.frequency());
+                    values.push(analysis.jitter().as_secs_f64() * 1000.0);
+                    values.push(analysis.duty_cycle());
+                    let status = if waveform.allows_frequency(analysis.frequency())
+                        && waveform.allows_jitter(analysis.jitter())
+                        && waveform.allows_duty_cycle(analysis.duty_cycle())
+                    {
+                        Status::Pass
+                    } else {
+                        Status::Fail
+                    };
+                    (status, Some(analysis.to_string()))
+                },
+                None => (Status::Fail, Some(format!("no full period on device pin {}", waveform.get_pin()))),
+            }
+        },
+    }
+}
+
 /// Evaluate criterion defined within Clockwise.
 pub fn evaluate<'a>(criterion: &'a Criterion, data: &Observation<'a>) -> Outcome<'a> {
-    let (status, message) = match criterion {
+    let device = match data.device(criterion.get_device()) {
+        Some(device) => device,
+        None => {
+            let message = format!("no device '{}' on the testbed", criterion.get_device());
+            return Outcome::new(criterion, Status::Error, Some(message));
+        },
+    };
+
+    // Quantities the criterion is judged on, for aggregating repeated runs.
+    let mut values = Vec::new();
+    let (status, message) = match criterion.get_target() {
         Criterion::GPIO(criterion) => {
-            match criterion {
-                GPIOCriterion::Any(_pin) => (Status::Complete, None),
-            }
+            let execution = data.execution_result()
+                .as_ref()
+                .expect("Attempted to evaluate GPIO criterion when execution result failed");
+            evaluate_gpio(criterion, device, execution, data.source_test().max_runtime(), &mut values)
         },
 
         Criterion::Energy(criterion) => {
@@ -221,6 +761,7 @@ pub fn evaluate<'a>(criterion: &'a Criterion, data: &Observation<'a>) -> Outcome
                         Status::Complete
                     };
 
+                    values.push(total);
                     (status, Some(format!("{:.2}mJ consumed", total)))
                 },
 
@@ -239,6 +780,7 @@ pub fn evaluate<'a>(criterion: &'a Criterion, data: &Observation<'a>) -> Outcome
                         Status::Complete
                     };
 
+                    values.push(avg as f64);
                     (status, Some(format!("{:.2}mJ/s average", avg)))
                 },
 
@@ -259,6 +801,7 @@ pub fn evaluate<'a>(criterion: &'a Criterion, data: &Observation<'a>) -> Outcome
                         Status::Complete
                     };
 
+                    values.push(max as f64);
                     (status, Some(format!("{:.2}mJ/s max", max)))
                 },
 
@@ -283,18 +826,86 @@ pub fn evaluate<'a>(criterion: &'a Criterion, data: &Observation<'a>) -> Outcome
                         Status::Complete
                     };
 
+                    values.push(min as f64);
                     (status, Some(format!("{:.2}mJ/s min", min)))
                 },
             }
         },
 
+        Criterion::Analog(criterion) => {
+            let execution_t0 = data.execution_result()
+                .as_ref()
+                .expect("Attempted to evaluate analog criterion when execution result failed")
+                .get_start();
+            // Should exist in map because criterion stated it should be sampled.
+            let samples = data.analog_samples()
+                .get(&criterion.get_pin())
+                .map(|samples| samples.as_slice())
+                .unwrap_or(&[]);
+
+            if samples.is_empty() {
+                (Status::Error, Some("no samples taken".to_string()))
+            } else {
+                let (value, message) = match criterion.get_stat() {
+                    AnalogStat::Min => {
+                        let min = samples.iter()
+                            .map(|(_t, sample)| *sample)
+                            .fold(f32::MAX, f32::min);
+                        (Some(min), format!("{:.2}mV min", min))
+                    },
+
+                    AnalogStat::Max => {
+                        let max = samples.iter()
+                            .map(|(_t, sample)| *sample)
+                            .fold(f32::MIN, f32::max);
+                        (Some(max), format!("{:.2}mV max", max))
+                    },
+
+                    AnalogStat::Crossings(threshold, direction) => {
+                        let crossings = analog_crossings(samples, threshold, direction);
+                        let mut message = format!("{} crossings", crossings.len());
+                        for (t, no) in crossings.iter().zip(0..) {
+                            message.push_str(if no == 0 { ": " } else { ", " });
+                            message.push_str(&format!("@{:?}", t.saturating_duration_since(execution_t0)));
+                        }
+                        (Some(crossings.len() as f32), message)
+                    },
+
+                    AnalogStat::SettlingTime(target, band) => {
+                        match analog_settling(samples, target, band) {
+                            Some(t) => {
+                                let settling_time = t.saturating_duration_since(execution_t0);
+                                (Some(settling_time.as_secs_f32() * 1000.0),
+                                 format!("settled after {:?}", settling_time))
+                            },
+                            None => (None, "did not settle".to_string()),
+                        }
+                    },
+                };
+
+                values.extend(value.map(f64::from));
+                let status = match value {
+                    Some(value) => match criterion.violated(value) {
+                        Some(true) => Status::Fail,
+                        Some(false) => Status::Pass,
+                        None => Status::Complete,
+                    },
+                    None => Status::Fail,
+                };
+
+                (status, Some(message))
+            }
+        },
+
         Criterion::SerialTrace(trace_criterion) => {
             let execution_t0 = data.execution_result()
                 .as_ref()
                 .expect("Attempted to evaluate serial tracing criterion when execution result failed")
                 .get_start();
-            if let Some(aligned_traces) = trace_criterion.align(execution_t0, data.traces().as_slice()) {
+            if let Some(aligned_traces) = trace_criterion.align(execution_t0, device.traces().as_slice()) {
                 let count = aligned_traces.len();
+                values.extend(aligned_traces.iter()
+                    .map(|t| t.get_offset(execution_t0).as_secs_f64() * 1000.0));
                 let mut message = "Satisfied by: ".to_string();
                 let it = aligned_traces.into_iter()
                     .map(|t| format!("@{:?}", t.get_offset(execution_t0)));
@@ -309,7 +920,10 @@ pub fn evaluate<'a>(criterion: &'a Criterion, data: &Observation<'a>) -> Outcome
                 (Status::Fail, None)
             }
         },
+
+        Criterion::Device(_, _) => unreachable!("criterion target is on a device"),
     };
 
     Outcome::new(criterion, status, message)
+        .with_values(values)
 }
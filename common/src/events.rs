@@ -0,0 +1,173 @@
+/*! Device events seen while a test runs.
+
+The threads observing a device under test record what they see into its [`DeviceEvents`] as it happens,
+so wait steps in a test's timeline can block until the device does something
+instead of relying on fixed delays.
+ */
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Instant;
+
+use clockwise_shared::mem::CounterId;
+
+use crate::hal::Level;
+use crate::mem::{MemoryTrace, StreamOperation};
+
+/// Device event a wait step waits for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WaitCondition {
+    /// Output pin of the device reaches a level (device pin, level).
+    PinLevel(u8, Level),
+    /// Bytes arrive over the device's serial tracing UART.
+    SerialTrace(Vec<u8>),
+    /// Memory counter of the device reaches at least a value.
+    MemoryCounter(CounterId, u32),
+}
+
+impl Display for WaitCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use WaitCondition::*;
+        match self {
+            PinLevel(pin_no, level) => write!(f, "pin {} {:?}", pin_no, level),
+            SerialTrace(bytes) => write!(f, "serial trace \"{}\"", bytes.escape_ascii()),
+            MemoryCounter(counter, value) => write!(f, "{} reaches {}", counter, value),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct EventLog {
+    levels: HashMap<u8, (Level, Instant)>,
+    serial: Vec<u8>,
+    /// Length of `serial` after each read and when the read happened.
+    serial_reads: Vec<(usize, Instant)>,
+    /// Serial data up to here was matched by an earlier wait.
+    serial_consumed: usize,
+    counters: HashMap<CounterId, (u32, Instant)>,
+}
+
+impl EventLog {
+    /// Returns when `condition` was first met, if it has been, and how much serial data meeting it consumes.
+    fn met(&self, condition: &WaitCondition) -> Option<(Instant, usize)> {
+        match condition {
+            WaitCondition::PinLevel(pin_no, level) => match self.levels.get(pin_no) {
+                Some((current, since)) if current == level => Some((*since, self.serial_consumed)),
+                _ => None,
+            },
+            WaitCondition::SerialTrace(bytes) => {
+                let unmatched = &self.serial[self.serial_consumed..];
+                let end = if bytes.is_empty() {
+                    self.serial_consumed
+                } else {
+                    self.serial_consumed
+                        + unmatched.windows(bytes.len())
+                            .position(|window| window == bytes.as_slice())?
+                        + bytes.len()
+                };
+                self.serial_reads.iter()
+                    .find(|(len, _t)| *len >= end)
+                    .map(|(_len, t)| (*t, end))
+            },
+            WaitCondition::MemoryCounter(counter, value) => match self.counters.get(counter) {
+                Some((current, since)) if current >= value => Some((*since, self.serial_consumed)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/** Events seen on one device under test during the current test.
+
+Pin levels are recorded by device-side pin number;
+the testbed-to-device pin mapping given at creation translates the pins the observer sees.
+ */
+#[derive(Debug)]
+pub struct DeviceEvents {
+    host_target_map: HashMap<u8, u8>,
+    log: Mutex<EventLog>,
+    changed: Condvar,
+}
+
+impl DeviceEvents {
+    /// Create an empty event record for a device wired according to `host_target_map`.
+    pub fn new(host_target_map: &HashMap<u8, u8>) -> DeviceEvents {
+        DeviceEvents {
+            host_target_map: host_target_map.clone(),
+            log: Mutex::new(EventLog::default()),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Forget all events, before a new test starts.
+    pub fn clear(&self) {
+        *self.lock() = EventLog::default();
+    }
+
+    /// Record the level of testbed pin `pin_no` changing at `time`.
+    pub fn record_level(&self, pin_no: u8, level: Level, time: Instant) {
+        if let Some(t_pin) = self.host_target_map.get(&pin_no) {
+            let mut log = self.lock();
+            // Keep when the level was reached if nothing changed.
+            match log.levels.get(t_pin) {
+                Some((current, _since)) if *current == level => (),
+                _ => { log.levels.insert(*t_pin, (level, time)); },
+            }
+            self.changed.notify_all();
+        }
+    }
+
+    /// Record bytes received over the serial tracing UART at `time`.
+    pub fn record_serial(&self, bytes: &[u8], time: Instant) {
+        let mut log = self.lock();
+        log.serial.extend_from_slice(bytes);
+        let len = log.serial.len();
+        log.serial_reads.push((len, time));
+        self.changed.notify_all();
+    }
+
+    /// Record a memory counter update.
+    pub fn record_counter(&self, trace: &MemoryTrace) {
+        let mut log = self.lock();
+        let value = match trace.operation() {
+            StreamOperation::Set => trace.value(),
+            StreamOperation::Add => log.counters.get(trace.counter())
+                .map(|(value, _since)| *value)
+                .unwrap_or(0)
+                .saturating_add(trace.value()),
+        };
+        log.counters.insert(*trace.counter(), (value, trace.time()));
+        self.changed.notify_all();
+    }
+
+    /** Wait until `condition` is met or `deadline` passes.
+
+    Returns when the condition was met, which may be before this is called,
+    or `None` if the deadline passed first.
+    Serial data matched by a wait is not matched again,
+    so consecutive waits for the same bytes wait for separate occurrences.
+     */
+    pub fn wait_for(&self, condition: &WaitCondition, deadline: Instant) -> Option<Instant> {
+        let mut log = self.lock();
+        loop {
+            if let Some((t, serial_consumed)) = log.met(condition) {
+                log.serial_consumed = serial_consumed;
+                return Some(t);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            log = self.changed.wait_timeout(log, deadline - now)
+                .map(|(log, _timeout)| log)
+                .unwrap_or_else(|e| e.into_inner().0);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, EventLog> {
+        // The log stays consistent even if a recording thread panicked.
+        self.log.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
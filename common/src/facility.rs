@@ -16,3 +16,22 @@ pub trait EnergyMetering: Debug + Send {
         Duration::from_millis(0)
     }
 }
+
+/// Trait providing voltage readings of a device pin.
+pub trait AnalogInput: Debug + Send {
+    /// Returns a voltage reading in millivolts.
+    fn voltage(&self) -> Result<f32, String>;
+}
+
+/** Trait for driving a voltage onto a device pin.
+
+Voltages are given in millivolts, matching [`Signal::Analog`](crate::comm::Signal::Analog).
+Requests above [`AnalogOutput::max_voltage`] are clamped to it.
+ */
+pub trait AnalogOutput: Debug + Send {
+    /// Returns the highest voltage the output can produce in millivolts.
+    fn max_voltage(&self) -> u32;
+
+    /// Drive the output to the given voltage in millivolts.
+    fn set_voltage(&mut self, millivolts: u32) -> Result<(), String>;
+}
@@ -0,0 +1,310 @@
+/*! Linux GPIO character device (`/dev/gpiochipN`) I/O through `gpio-cdev`.
+
+Pin numbers are line offsets on the chip.
+Edges are reported with the timestamp the kernel recorded for the line event,
+so responses do not pick up the latency of waking the observing thread.
+Kernels before 5.7 stamp line events with the real-time clock;
+those edges fall back to the time the event was read.
+
+Serial ports are opened as plain Linux TTYs,
+I2C uses the `/dev/i2c-N` bus given to [`GpioChip::with_i2c_bus`],
+and SPI buses are the host's `/dev/spidevB.C` devices.
+
+Any chip works, including the ones created by the `gpio-sim` and `gpio-mockup` kernel modules.
+ */
+
+use std::any::Any;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use gpio_cdev::{
+    Chip,
+    EventRequestFlags,
+    EventType,
+    Line,
+    LineEventHandle,
+    LineHandle,
+    LineRequestFlags,
+};
+use rppal::i2c::I2c;
+
+use crate::io::{IOError, Result, SerialConfig, UART};
+
+use super::{
+    Backend,
+    I2cBus,
+    InputPin,
+    Level,
+    OutputPin,
+    RaspberryPi,
+    SerialPort,
+    SpiBus,
+    Trigger,
+};
+use super::rpi::{RpiI2c, RpiSpi};
+
+/// Consumer label the testbed's line requests show up with.
+const CONSUMER: &str = "clockwise-testbed";
+
+fn from_value(value: u8) -> Level {
+    if value == 0 { Level::Low } else { Level::High }
+}
+
+fn poisoned<T>(_e: T) -> IOError {
+    IOError::Backend("GPIO line handle is poisoned".to_string())
+}
+
+/// Returns the current time of the monotonic clock in nanoseconds.
+fn monotonic_now() -> u64 {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // CLOCK_MONOTONIC is always available, so this cannot fail.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts); }
+    (ts.tv_sec as u64) * 1_000_000_000 + (ts.tv_nsec as u64)
+}
+
+/// Convert a line event timestamp to an [`Instant`].
+fn event_instant(timestamp: u64) -> Instant {
+    let now = Instant::now();
+    let elapsed = monotonic_now().checked_sub(timestamp)
+        .map(Duration::from_nanos);
+
+    match elapsed {
+        // Timestamps from the real-time clock are far ahead of the monotonic clock.
+        Some(elapsed) => now.checked_sub(elapsed).unwrap_or(now),
+        None => now,
+    }
+}
+
+/// Line driven through a GPIO character device.
+#[derive(Debug)]
+pub struct CdevOutputPin {
+    pin_no: u8,
+    handle: LineHandle,
+}
+
+impl OutputPin for CdevOutputPin {
+    fn pin(&self) -> u8 {
+        self.pin_no
+    }
+
+    fn set_high(&mut self) -> Result<()> {
+        Ok(self.handle.set_value(1)?)
+    }
+
+    fn set_low(&mut self) -> Result<()> {
+        Ok(self.handle.set_value(0)?)
+    }
+
+    fn level(&self) -> Result<Level> {
+        Ok(from_value(self.handle.get_value()?))
+    }
+}
+
+/// How an input line is currently requested from the kernel.
+#[derive(Debug)]
+enum Request {
+    /// Plain input
+    Level(LineHandle),
+    /// Input reporting edges
+    Events(LineEventHandle),
+}
+
+/** Line read through a GPIO character device.
+
+A line can only be requested once, so enabling or disabling interrupts
+releases the line and requests it again with the new configuration.
+ */
+#[derive(Debug)]
+pub struct CdevInputPin {
+    pin_no: u8,
+    line: Line,
+    request: Mutex<Option<Request>>,
+}
+
+impl CdevInputPin {
+    fn request(&self) -> Result<MutexGuard<'_, Option<Request>>> {
+        self.request.lock().map_err(poisoned)
+    }
+
+    /// Release the line and request it again with `new`.
+    fn rerequest<F>(&mut self, new: F) -> Result<()>
+    where
+        F: FnOnce(&Line) -> std::result::Result<Request, gpio_cdev::Error>,
+    {
+        let request = self.request.get_mut().map_err(poisoned)?;
+        *request = None;
+        *request = Some(new(&self.line)?);
+        Ok(())
+    }
+}
+
+impl InputPin for CdevInputPin {
+    fn pin(&self) -> u8 {
+        self.pin_no
+    }
+
+    fn read(&self) -> Result<Level> {
+        let value = match self.request()?.as_ref() {
+            Some(Request::Level(handle)) => handle.get_value()?,
+            Some(Request::Events(handle)) => handle.get_value()?,
+            None => return Err(IOError::Backend(format!("line {} is not requested", self.pin_no))),
+        };
+        Ok(from_value(value))
+    }
+
+    fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+        let events = match trigger {
+            Trigger::RisingEdge => EventRequestFlags::RISING_EDGE,
+            Trigger::FallingEdge => EventRequestFlags::FALLING_EDGE,
+            Trigger::Both => EventRequestFlags::BOTH_EDGES,
+        };
+        self.rerequest(|line| {
+            line.events(LineRequestFlags::INPUT, events, CONSUMER)
+                .map(Request::Events)
+        })
+    }
+
+    fn clear_interrupt(&mut self) -> Result<()> {
+        self.rerequest(|line| {
+            line.request(LineRequestFlags::INPUT, 0, CONSUMER)
+                .map(Request::Level)
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/** GPIO chip exposed through the Linux GPIO character device.
+
+```ignore
+let backend = GpioChip::new("/dev/gpiochip0")?.with_i2c_bus(1);
+let mapping = Mapping::new(device, &pins, &[])?
+    .with_backend(Arc::new(backend));
+```
+ */
+#[derive(Debug)]
+pub struct GpioChip {
+    chip: Mutex<Chip>,
+    i2c_bus: Option<u8>,
+}
+
+impl GpioChip {
+    /// Open the GPIO chip at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<GpioChip> {
+        Ok(GpioChip {
+            chip: Mutex::new(Chip::new(path)?),
+            i2c_bus: None,
+        })
+    }
+
+    /// Use the I2C bus at `/dev/i2c-<bus>`.
+    pub fn with_i2c_bus(self, bus: u8) -> Self {
+        Self {
+            i2c_bus: Some(bus),
+            ..self
+        }
+    }
+
+    /// Returns the path of the chip.
+    pub fn path(&self) -> Result<PathBuf> {
+        Ok(self.chip.lock().map_err(poisoned)?.path().to_path_buf())
+    }
+
+    fn line(&self, pin_no: u8) -> Result<Line> {
+        Ok(self.chip.lock().map_err(poisoned)?.get_line(pin_no as u32)?)
+    }
+}
+
+impl Backend for GpioChip {
+    fn output_pin(&self, pin_no: u8) -> Result<Box<dyn OutputPin>> {
+        let handle = self.line(pin_no)?.request(LineRequestFlags::OUTPUT, 0, CONSUMER)?;
+        Ok(Box::new(CdevOutputPin { pin_no, handle }))
+    }
+
+    fn input_pin(&self, pin_no: u8) -> Result<Box<dyn InputPin>> {
+        let line = self.line(pin_no)?;
+        let handle = line.request(LineRequestFlags::INPUT, 0, CONSUMER)?;
+        Ok(Box::new(CdevInputPin {
+            pin_no,
+            line,
+            request: Mutex::new(Some(Request::Level(handle))),
+        }))
+    }
+
+    fn poll_interrupts(&self,
+                       pins: &[&dyn InputPin],
+                       timeout: Option<Duration>) -> Result<Option<(u8, Level, Instant)>>
+    {
+        let mut requests = Vec::new();
+        for pin in pins {
+            let cdev_pin = pin.as_any()
+                .downcast_ref::<CdevInputPin>()
+                .ok_or_else(|| IOError::Backend(format!("pin {} was not acquired from a GPIO chip", pin.pin())))?;
+            requests.push((cdev_pin.pin_no, cdev_pin.request()?));
+        }
+
+        let mut fds: Vec<libc::pollfd> = requests.iter()
+            .map(|(_pin_no, request)| {
+                // Pins without interrupts enabled never become ready.
+                let fd = match request.as_ref() {
+                    Some(Request::Events(handle)) => handle.as_raw_fd(),
+                    _ => -1,
+                };
+                libc::pollfd { fd, events: libc::POLLIN, revents: 0 }
+            })
+            .collect();
+
+        let timeout_ms = match timeout {
+            // Round up so a short timeout does not become a busy loop.
+            Some(timeout) => timeout.as_micros().div_ceil(1000).min(libc::c_int::MAX as u128) as libc::c_int,
+            None => -1,
+        };
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms) };
+        if ready < 0 {
+            let e = std::io::Error::last_os_error();
+            return if e.kind() == std::io::ErrorKind::Interrupted {
+                Ok(None)
+            } else {
+                Err(IOError::Backend(format!("failed to poll GPIO lines: {}", e)))
+            };
+        }
+
+        let ready_idx = fds.iter().position(|fd| fd.revents & libc::POLLIN != 0);
+        match ready_idx {
+            Some(idx) => {
+                let (pin_no, request) = &mut requests[idx];
+                if let Some(Request::Events(handle)) = request.as_mut() {
+                    let event = handle.get_event()?;
+                    let level = match event.event_type() {
+                        EventType::RisingEdge => Level::High,
+                        EventType::FallingEdge => Level::Low,
+                    };
+                    Ok(Some((*pin_no, level, event_instant(event.timestamp()))))
+                } else {
+                    Ok(None)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn serial(&self, uart: &UART, config: &SerialConfig) -> Result<Box<dyn SerialPort>> {
+        // The Raspberry Pi's UARTs are ordinary TTYs.
+        RaspberryPi::new().serial(uart, config)
+    }
+
+    fn i2c(&self) -> Result<Box<dyn I2cBus>> {
+        let bus = self.i2c_bus
+            .ok_or_else(|| IOError::Backend("no I2C bus configured for the GPIO chip".to_string()))?;
+        Ok(Box::new(RpiI2c(I2c::with_bus(bus)?)))
+    }
+
+    fn spi(&self, bus: u8, chip_select: u8) -> Result<Box<dyn SpiBus>> {
+        // spidev numbering is the same on any host.
+        Ok(Box::new(RpiSpi::open(bus, chip_select)?))
+    }
+}
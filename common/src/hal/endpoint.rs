@@ -0,0 +1,270 @@
+/*! Serial endpoints that are not UARTs.
+
+These stand in for a UART when the serial data comes from somewhere other than the testbed's own hardware:
+a pseudo-terminal fed by a local process, or a TCP connection to a remote serial bridge such as `ser2net`.
+They work with any [`Backend`](super::Backend).
+ */
+
+use std::fs::{self, File};
+use std::io::{self as std_io, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::fs::{symlink, OpenOptionsExt};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use crate::io::{IOError, Result, SerialConfig};
+
+use super::SerialPort;
+
+/// Returns the timeout as milliseconds for `poll`.
+fn poll_timeout(timeout: Duration) -> libc::c_int {
+    // Round up so a short timeout does not become a busy loop.
+    timeout.as_micros().div_ceil(1000).min(libc::c_int::MAX as u128) as libc::c_int
+}
+
+/// Wait up to `timeout` for data to read from `fd`.
+fn wait_readable(fd: RawFd, timeout: Duration) -> std_io::Result<bool> {
+    wait_ready(fd, libc::POLLIN, timeout)
+}
+
+/// Wait up to `timeout` for room to write to `fd`.
+fn wait_writable(fd: RawFd, timeout: Duration) -> std_io::Result<bool> {
+    wait_ready(fd, libc::POLLOUT, timeout)
+}
+
+/// Wait up to `timeout` for `fd` to be ready for one of `events`.
+fn wait_ready(fd: RawFd, events: libc::c_short, timeout: Duration) -> std_io::Result<bool> {
+    let mut pollfd = libc::pollfd { fd, events, revents: 0 };
+    let ready = unsafe { libc::poll(&mut pollfd, 1, poll_timeout(timeout)) };
+    if ready < 0 {
+        let e = std_io::Error::last_os_error();
+        if e.kind() == ErrorKind::Interrupted { Ok(false) } else { Err(e) }
+    } else {
+        Ok(ready > 0)
+    }
+}
+
+/// Check the result of a libc call that returns -1 on failure.
+fn check(ret: libc::c_int) -> std_io::Result<libc::c_int> {
+    if ret < 0 { Err(std_io::Error::last_os_error()) } else { Ok(ret) }
+}
+
+/** Pseudo-terminal pair.
+
+The testbed keeps the master side and links the slave side's device at a chosen path,
+so a local process can open that path and write to it like it would a UART.
+Endpoints [shared](PtyEndpoint::share) from one another use the same pseudo-terminal;
+the link is removed when the last of them is dropped.
+Line settings other than the read timeout do not apply to a pseudo-terminal.
+ */
+#[derive(Debug)]
+pub struct PtyEndpoint {
+    pty: Arc<Pty>,
+    timeout: Duration,
+    // Writes block until there is room unless a write timeout is set.
+    write_timeout: Option<Duration>,
+}
+
+/// Pseudo-terminal pair behind one or more [`PtyEndpoint`]s.
+#[derive(Debug)]
+pub(crate) struct Pty {
+    master: File,
+    // Held open so the master does not see a hang-up between writers.
+    _slave: File,
+    slave_path: PathBuf,
+    link: PathBuf,
+}
+
+impl PtyEndpoint {
+    /// Create a pseudo-terminal pair with the slave side linked at `link`.
+    pub fn open<P: AsRef<Path>>(link: P, config: &SerialConfig) -> Result<PtyEndpoint> {
+        let pty = Pty::create(link.as_ref())
+            .map_err(IOError::Endpoint)?;
+        Ok(PtyEndpoint {
+            pty: Arc::new(pty),
+            timeout: config.get_read_timeout(),
+            write_timeout: None,
+        })
+    }
+
+    /// Open another endpoint on the same pseudo-terminal, with the read timeout of `config`.
+    pub fn share(&self, config: &SerialConfig) -> PtyEndpoint {
+        PtyEndpoint {
+            pty: Arc::clone(&self.pty),
+            timeout: config.get_read_timeout(),
+            write_timeout: None,
+        }
+    }
+
+    /// Returns a handle to the pseudo-terminal that does not keep it open.
+    pub(crate) fn downgrade(&self) -> Weak<Pty> {
+        Arc::downgrade(&self.pty)
+    }
+
+    /// Open another endpoint on the pseudo-terminal behind `pty`, if it is still open.
+    pub(crate) fn upgrade(pty: &Weak<Pty>, config: &SerialConfig) -> Option<PtyEndpoint> {
+        pty.upgrade().map(|pty| PtyEndpoint { pty, timeout: config.get_read_timeout(), write_timeout: None })
+    }
+
+    /// Returns the path processes open to write to the endpoint.
+    pub fn link(&self) -> &Path {
+        &self.pty.link
+    }
+}
+
+impl Pty {
+    fn create(link: &Path) -> std_io::Result<Pty> {
+        let master = unsafe {
+            let fd = check(libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY))?;
+            File::from_raw_fd(fd)
+        };
+        check(unsafe { libc::grantpt(master.as_raw_fd()) })?;
+        check(unsafe { libc::unlockpt(master.as_raw_fd()) })?;
+
+        let mut name = [0 as libc::c_char; 128];
+        let ret = unsafe { libc::ptsname_r(master.as_raw_fd(), name.as_mut_ptr(), name.len()) };
+        if ret != 0 {
+            return Err(std_io::Error::from_raw_os_error(ret));
+        }
+        let slave_path = unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        let slave = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(&slave_path)?;
+
+        // Pass bytes through untouched.
+        unsafe {
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            check(libc::tcgetattr(slave.as_raw_fd(), &mut termios))?;
+            libc::cfmakeraw(&mut termios);
+            check(libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &termios))?;
+        }
+
+        // Replace a link left behind by an earlier run.
+        if fs::symlink_metadata(link).map(|m| m.file_type().is_symlink()).unwrap_or(false) {
+            fs::remove_file(link)?;
+        }
+        symlink(&slave_path, link)?;
+
+        Ok(Pty {
+            master,
+            _slave: slave,
+            slave_path: PathBuf::from(slave_path),
+            link: link.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        // Leave the link alone if a newer pseudo-terminal took it over.
+        if fs::read_link(&self.link).is_ok_and(|target| target == self.slave_path) {
+            let _ = fs::remove_file(&self.link);
+        }
+    }
+}
+
+impl SerialPort for PtyEndpoint {
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn set_write_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.write_timeout = Some(timeout);
+        Ok(())
+    }
+
+    fn flush_input(&mut self) -> Result<()> {
+        let mut discard = [0; 256];
+        while wait_readable(self.pty.master.as_raw_fd(), Duration::from_millis(0)).map_err(IOError::Endpoint)? {
+            (&self.pty.master).read(&mut discard).map_err(IOError::Endpoint)?;
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if wait_readable(self.pty.master.as_raw_fd(), self.timeout).map_err(IOError::Endpoint)? {
+            (&self.pty.master).read(buffer).map_err(IOError::Endpoint)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        if let Some(timeout) = self.write_timeout {
+            if !wait_writable(self.pty.master.as_raw_fd(), timeout).map_err(IOError::Endpoint)? {
+                return Ok(0);
+            }
+        }
+        (&self.pty.master).write(buffer).map_err(IOError::Endpoint)
+    }
+}
+
+/** Raw TCP connection to a serial bridge.
+
+Bytes are passed through as-is, as `ser2net` does in its raw mode.
+Line settings other than the read timeout are up to the bridge.
+ */
+#[derive(Debug)]
+pub struct TcpEndpoint {
+    stream: TcpStream,
+}
+
+impl TcpEndpoint {
+    /// Connect to the bridge at `address` (`host:port`).
+    pub fn connect(address: &str, config: &SerialConfig) -> Result<TcpEndpoint> {
+        let stream = TcpStream::connect(address).map_err(IOError::Endpoint)?;
+        stream.set_nodelay(true).map_err(IOError::Endpoint)?;
+        let mut endpoint = TcpEndpoint { stream };
+        endpoint.set_read_timeout(config.get_read_timeout())?;
+
+        Ok(endpoint)
+    }
+}
+
+impl SerialPort for TcpEndpoint {
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<()> {
+        // A zero timeout is not allowed for sockets; wait as little as possible instead.
+        let timeout = timeout.max(Duration::from_micros(1));
+        self.stream.set_read_timeout(Some(timeout)).map_err(IOError::Endpoint)
+    }
+
+    fn set_write_timeout(&mut self, timeout: Duration) -> Result<()> {
+        let timeout = timeout.max(Duration::from_micros(1));
+        self.stream.set_write_timeout(Some(timeout)).map_err(IOError::Endpoint)
+    }
+
+    fn flush_input(&mut self) -> Result<()> {
+        let mut discard = [0; 256];
+        while wait_readable(self.stream.as_raw_fd(), Duration::from_millis(0)).map_err(IOError::Endpoint)? {
+            if self.stream.read(&mut discard).map_err(IOError::Endpoint)? == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        match self.stream.read(buffer) {
+            Ok(0) if !buffer.is_empty() => Err(IOError::Endpoint(
+                std_io::Error::new(ErrorKind::UnexpectedEof, "serial bridge closed the connection"))),
+            Ok(read) => Ok(read),
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => Ok(0),
+            Err(e) => Err(IOError::Endpoint(e)),
+        }
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        match self.stream.write(buffer) {
+            Ok(written) => Ok(written),
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => Ok(0),
+            Err(e) => Err(IOError::Endpoint(e)),
+        }
+    }
+}
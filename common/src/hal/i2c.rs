@@ -0,0 +1,116 @@
+/*! Sharing one I2C bus between several peripherals.
+
+A [`SharedI2cBus`] wraps the bus a [`Backend`](super::Backend) opens.
+Each peripheral on it gets an [`I2cDevice`] for its own address,
+and every transaction locks the bus and addresses the peripheral before transferring,
+so drivers never see each other's slave address.
+ */
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::io::{IOError, Result};
+
+use super::I2cBus;
+
+fn poisoned<T>(_e: T) -> IOError {
+    IOError::Backend("I2C bus is poisoned".to_string())
+}
+
+#[derive(Debug)]
+struct BusState {
+    bus: Box<dyn I2cBus>,
+    /// Address the bus was last set to.
+    address: Option<u16>,
+}
+
+/// Lockable handle to an I2C bus that any number of peripherals can share.
+#[derive(Clone, Debug)]
+pub struct SharedI2cBus {
+    state: Arc<Mutex<BusState>>,
+}
+
+impl SharedI2cBus {
+    /// Share `bus` between peripherals.
+    pub fn new(bus: Box<dyn I2cBus>) -> SharedI2cBus {
+        SharedI2cBus {
+            state: Arc::new(Mutex::new(BusState { bus, address: None })),
+        }
+    }
+
+    /// Returns a handle to the peripheral at `address`.
+    pub fn device(&self, address: u16) -> I2cDevice {
+        I2cDevice {
+            address,
+            bus: self.clone(),
+        }
+    }
+}
+
+/// Peripheral at one address on a [`SharedI2cBus`].
+#[derive(Clone, Debug)]
+pub struct I2cDevice {
+    address: u16,
+    bus: SharedI2cBus,
+}
+
+impl I2cDevice {
+    /// Returns the address of the peripheral.
+    pub fn address(&self) -> u16 {
+        self.address
+    }
+
+    /** Lock the bus and address the peripheral.
+
+    Other peripherals cannot use the bus until the returned transaction is dropped,
+    so a sequence of transfers through it is not interleaved with theirs.
+     */
+    pub fn transaction(&self) -> Result<I2cTransaction<'_>> {
+        let mut state = self.bus.state.lock().map_err(poisoned)?;
+        if state.address != Some(self.address) {
+            // Forget the address if setting it fails partway.
+            state.address = None;
+            state.bus.set_slave_address(self.address)?;
+            state.address = Some(self.address);
+        }
+
+        Ok(I2cTransaction { state })
+    }
+
+    /// Read data from the peripheral into `buffer`, returning the number of bytes read.
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize> {
+        self.transaction()?.read(buffer)
+    }
+
+    /// Write data to the peripheral, returning the number of bytes written.
+    pub fn write(&self, buffer: &[u8]) -> Result<usize> {
+        self.transaction()?.write(buffer)
+    }
+
+    /// Write data to the peripheral and then read its reply without releasing the bus.
+    pub fn write_read(&self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()> {
+        self.transaction()?.write_read(write_buffer, read_buffer)
+    }
+}
+
+/// Exclusive use of a [`SharedI2cBus`], addressed to one peripheral.
+#[derive(Debug)]
+pub struct I2cTransaction<'a> {
+    state: MutexGuard<'a, BusState>,
+}
+
+impl I2cTransaction<'_> {
+    /// Read data from the peripheral into `buffer`, returning the number of bytes read.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        self.state.bus.read(buffer)
+    }
+
+    /// Write data to the peripheral, returning the number of bytes written.
+    pub fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        self.state.bus.write(buffer)
+    }
+
+    /// Write data to the peripheral and then read its reply without releasing the bus.
+    pub fn write_read(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()> {
+        self.state.bus.write_read(write_buffer, read_buffer)
+    }
+}
@@ -0,0 +1,214 @@
+/*! Hardware abstraction for testbed I/O.
+
+The testbed drives and observes the device under test through the traits defined here.
+A [`Backend`] hands out digital pins, serial ports, I2C buses, and SPI buses;
+[`io::Mapping`](crate::io::Mapping) uses one to acquire the I/O the mapping allows.
+
+Besides the Raspberry Pi ([`rpi`]), any Linux host exposing a GPIO character device can be used ([`cdev`]),
+and a scripted [`sim::SimulatedDevice`] can stand in for real hardware.
+Serial data can also come from a pseudo-terminal or a TCP serial bridge ([`endpoint`]).
+Peripherals on one I2C bus share it through [`i2c::SharedI2cBus`].
+ */
+
+pub mod cdev;
+pub mod endpoint;
+pub mod i2c;
+pub mod rpi;
+pub mod sim;
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::ops::Not;
+use std::time::{Duration, Instant};
+
+use crate::io::{Result, SerialConfig, UART};
+
+pub use cdev::GpioChip;
+pub use endpoint::{PtyEndpoint, TcpEndpoint};
+pub use i2c::{I2cDevice, SharedI2cBus};
+pub use rpi::RaspberryPi;
+pub use sim::SimulatedDevice;
+
+/// Logic level of a digital pin.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Level {
+    /// Logic low
+    Low,
+    /// Logic high
+    High,
+}
+
+impl From<bool> for Level {
+    fn from(high: bool) -> Level {
+        if high { Level::High } else { Level::Low }
+    }
+}
+
+impl From<Level> for bool {
+    fn from(level: Level) -> bool {
+        level == Level::High
+    }
+}
+
+impl Not for Level {
+    type Output = Level;
+
+    fn not(self) -> Level {
+        match self {
+            Level::Low => Level::High,
+            Level::High => Level::Low,
+        }
+    }
+}
+
+/// Condition that raises an interrupt on an input pin.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Trigger {
+    /// Low-to-high transitions
+    RisingEdge,
+    /// High-to-low transitions
+    FallingEdge,
+    /// Any transition
+    Both,
+}
+
+/// Digital pin driven by the testbed.
+pub trait OutputPin: Debug + Send {
+    /// Returns the testbed-side number of the pin.
+    fn pin(&self) -> u8;
+
+    /// Drive the pin high.
+    fn set_high(&mut self) -> Result<()>;
+
+    /// Drive the pin low.
+    fn set_low(&mut self) -> Result<()>;
+
+    /// Returns the level the pin is driven to.
+    fn level(&self) -> Result<Level>;
+
+    /// Drive the pin to the given level.
+    fn write(&mut self, level: Level) -> Result<()> {
+        match level {
+            Level::High => self.set_high(),
+            Level::Low => self.set_low(),
+        }
+    }
+}
+
+/// Interrupt-capable digital pin read by the testbed.
+pub trait InputPin: Debug + Send {
+    /// Returns the testbed-side number of the pin.
+    fn pin(&self) -> u8;
+
+    /// Returns the current level of the pin.
+    fn read(&self) -> Result<Level>;
+
+    /// Configure the pin to raise interrupts.
+    fn set_interrupt(&mut self, trigger: Trigger) -> Result<()>;
+
+    /// Stop the pin from raising interrupts.
+    fn clear_interrupt(&mut self) -> Result<()>;
+
+    /// Returns the pin as [`Any`] so a [`Backend`] can recover the concrete pin it created.
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Byte-oriented serial port.
+pub trait SerialPort: Debug + Send {
+    /// Set how long a read waits for data before returning with nothing.
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<()>;
+
+    /// Set how long a write waits for room to send before returning with nothing written.
+    fn set_write_timeout(&mut self, timeout: Duration) -> Result<()>;
+
+    /// Discard any received data that has not been read yet.
+    fn flush_input(&mut self) -> Result<()>;
+
+    /// Read available data into `buffer`, returning the number of bytes read.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize>;
+
+    /// Write data from `buffer`, returning the number of bytes written.
+    fn write(&mut self, buffer: &[u8]) -> Result<usize>;
+}
+
+/// I2C bus controller.
+pub trait I2cBus: Debug + Send {
+    /// Address subsequent transfers to the given peripheral.
+    fn set_slave_address(&mut self, address: u16) -> Result<()>;
+
+    /// Read data from the peripheral into `buffer`, returning the number of bytes read.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize>;
+
+    /// Write data to the peripheral, returning the number of bytes written.
+    fn write(&mut self, buffer: &[u8]) -> Result<usize>;
+
+    /// Write data to the peripheral and then read its reply without releasing the bus.
+    fn write_read(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()>;
+}
+
+/// SPI clock polarity and phase.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SpiMode {
+    /// Clock idles low, data sampled on the leading edge
+    Mode0,
+    /// Clock idles low, data sampled on the trailing edge
+    Mode1,
+    /// Clock idles high, data sampled on the leading edge
+    Mode2,
+    /// Clock idles high, data sampled on the trailing edge
+    Mode3,
+}
+
+/** SPI bus controller addressing one chip select.
+
+Buses are opened at 1MHz in [`SpiMode::Mode0`].
+ */
+pub trait SpiBus: Debug + Send {
+    /// Set the clock frequency in hertz.
+    fn set_clock_speed(&mut self, hz: u32) -> Result<()>;
+
+    /// Set the clock polarity and phase.
+    fn set_mode(&mut self, mode: SpiMode) -> Result<()>;
+
+    /// Read data from the peripheral into `buffer`, returning the number of bytes read.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize>;
+
+    /// Write data to the peripheral, returning the number of bytes written.
+    fn write(&mut self, buffer: &[u8]) -> Result<usize>;
+
+    /// Write `write_buffer` while reading the same number of bytes into `read_buffer`.
+    fn transfer(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()>;
+}
+
+/** Provider of the hardware the testbed uses.
+
+Pins handed out by a backend are only meaningful to the same backend;
+[`Backend::poll_interrupts`] may reject pins that another backend created.
+ */
+pub trait Backend: Debug + Send + Sync {
+    /// Acquire a pin the testbed drives.
+    fn output_pin(&self, pin_no: u8) -> Result<Box<dyn OutputPin>>;
+
+    /// Acquire a pin the testbed reads.
+    fn input_pin(&self, pin_no: u8) -> Result<Box<dyn InputPin>>;
+
+    /** Wait for an interrupt on any of the given pins.
+
+    Returns the number of the pin that triggered, its level, and when the edge occurred,
+    or `None` if `timeout` elapses first.
+    Backends that cannot timestamp edges report the time the interrupt was picked up.
+    A `timeout` of `None` waits indefinitely.
+     */
+    fn poll_interrupts(&self,
+                       pins: &[&dyn InputPin],
+                       timeout: Option<Duration>) -> Result<Option<(u8, Level, Instant)>>;
+
+    /// Open a serial port with the given line settings.
+    fn serial(&self, uart: &UART, config: &SerialConfig) -> Result<Box<dyn SerialPort>>;
+
+    /// Open the I2C bus.
+    fn i2c(&self) -> Result<Box<dyn I2cBus>>;
+
+    /// Open SPI bus `bus`, addressing the peripheral on `chip_select`.
+    fn spi(&self, bus: u8, chip_select: u8) -> Result<Box<dyn SpiBus>>;
+}
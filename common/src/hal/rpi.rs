@@ -0,0 +1,262 @@
+//! Raspberry Pi I/O through `rppal`.
+
+use std::any::Any;
+use std::time::{Duration, Instant};
+
+use rppal::gpio::{self, Gpio};
+use rppal::i2c::I2c;
+use rppal::spi::{self, Bus, SlaveSelect, Spi};
+use rppal::uart::{self, Uart, Parity as UARTParity};
+
+use crate::io::{FlowControl, IOError, Parity, Result, SerialConfig, UART};
+
+use super::{
+    Backend,
+    I2cBus,
+    InputPin,
+    Level,
+    OutputPin,
+    SerialPort,
+    SpiBus,
+    SpiMode,
+    Trigger,
+};
+
+fn from_rppal_level(level: gpio::Level) -> Level {
+    match level {
+        gpio::Level::High => Level::High,
+        gpio::Level::Low => Level::Low,
+    }
+}
+
+/// Pin driven through the Raspberry Pi's GPIO peripheral.
+#[derive(Debug)]
+pub struct RpiOutputPin(gpio::OutputPin);
+
+impl OutputPin for RpiOutputPin {
+    fn pin(&self) -> u8 {
+        self.0.pin()
+    }
+
+    fn set_high(&mut self) -> Result<()> {
+        self.0.set_high();
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<()> {
+        self.0.set_low();
+        Ok(())
+    }
+
+    fn level(&self) -> Result<Level> {
+        Ok(Level::from(self.0.is_set_high()))
+    }
+}
+
+/// Pin read through the Raspberry Pi's GPIO peripheral.
+#[derive(Debug)]
+pub struct RpiInputPin(gpio::InputPin);
+
+impl InputPin for RpiInputPin {
+    fn pin(&self) -> u8 {
+        self.0.pin()
+    }
+
+    fn read(&self) -> Result<Level> {
+        Ok(from_rppal_level(self.0.read()))
+    }
+
+    fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+        let trigger = match trigger {
+            Trigger::RisingEdge => gpio::Trigger::RisingEdge,
+            Trigger::FallingEdge => gpio::Trigger::FallingEdge,
+            Trigger::Both => gpio::Trigger::Both,
+        };
+        Ok(self.0.set_interrupt(trigger)?)
+    }
+
+    fn clear_interrupt(&mut self) -> Result<()> {
+        Ok(self.0.clear_interrupt()?)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// One of the Raspberry Pi's UARTs.
+#[derive(Debug)]
+pub struct RpiUart(pub(super) Uart);
+
+impl SerialPort for RpiUart {
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<()> {
+        Ok(self.0.set_read_mode(0, timeout)?)
+    }
+
+    fn set_write_timeout(&mut self, _timeout: Duration) -> Result<()> {
+        // The UART cannot time writes out, so they stop blocking instead and write what fits.
+        Ok(self.0.set_write_mode(false)?)
+    }
+
+    fn flush_input(&mut self) -> Result<()> {
+        Ok(self.0.flush(uart::Queue::Input)?)
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        Ok(self.0.read(buffer)?)
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        Ok(self.0.write(buffer)?)
+    }
+}
+
+/// The Raspberry Pi's primary I2C bus.
+#[derive(Debug)]
+pub struct RpiI2c(pub(super) I2c);
+
+impl I2cBus for RpiI2c {
+    fn set_slave_address(&mut self, address: u16) -> Result<()> {
+        Ok(self.0.set_slave_address(address)?)
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        Ok(self.0.read(buffer)?)
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        Ok(self.0.write(buffer)?)
+    }
+
+    fn write_read(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()> {
+        Ok(self.0.write_read(write_buffer, read_buffer)?)
+    }
+}
+
+fn to_rppal_mode(mode: SpiMode) -> spi::Mode {
+    match mode {
+        SpiMode::Mode0 => spi::Mode::Mode0,
+        SpiMode::Mode1 => spi::Mode::Mode1,
+        SpiMode::Mode2 => spi::Mode::Mode2,
+        SpiMode::Mode3 => spi::Mode::Mode3,
+    }
+}
+
+/// One of the Raspberry Pi's SPI buses.
+#[derive(Debug)]
+pub struct RpiSpi(pub(super) Spi);
+
+impl RpiSpi {
+    /// Open `/dev/spidev<bus>.<chip_select>` at 1MHz in mode 0.
+    pub(super) fn open(bus: u8, chip_select: u8) -> Result<RpiSpi> {
+        let bus = match bus {
+            0 => Bus::Spi0,
+            1 => Bus::Spi1,
+            2 => Bus::Spi2,
+            _ => return Err(IOError::Backend(format!("no SPI bus {}", bus))),
+        };
+        let slave_select = match chip_select {
+            0 => SlaveSelect::Ss0,
+            1 => SlaveSelect::Ss1,
+            2 => SlaveSelect::Ss2,
+            _ => return Err(IOError::Backend(format!("no SPI chip select {}", chip_select))),
+        };
+        Ok(RpiSpi(Spi::new(bus, slave_select, 1_000_000, spi::Mode::Mode0)?))
+    }
+}
+
+impl SpiBus for RpiSpi {
+    fn set_clock_speed(&mut self, hz: u32) -> Result<()> {
+        Ok(self.0.set_clock_speed(hz)?)
+    }
+
+    fn set_mode(&mut self, mode: SpiMode) -> Result<()> {
+        Ok(self.0.set_mode(to_rppal_mode(mode))?)
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        Ok(self.0.read(buffer)?)
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        Ok(self.0.write(buffer)?)
+    }
+
+    fn transfer(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()> {
+        self.0.transfer(read_buffer, write_buffer)?;
+        Ok(())
+    }
+}
+
+/** Raspberry Pi hardware.
+
+Peripherals are acquired on demand, so creating the backend succeeds on any host.
+ */
+#[derive(Debug, Default)]
+pub struct RaspberryPi;
+
+impl RaspberryPi {
+    /// Create a new Raspberry Pi backend.
+    pub fn new() -> RaspberryPi {
+        RaspberryPi
+    }
+}
+
+impl Backend for RaspberryPi {
+    fn output_pin(&self, pin_no: u8) -> Result<Box<dyn OutputPin>> {
+        let pin = Gpio::new()?.get(pin_no)?;
+        Ok(Box::new(RpiOutputPin(pin.into_output())))
+    }
+
+    fn input_pin(&self, pin_no: u8) -> Result<Box<dyn InputPin>> {
+        let pin = Gpio::new()?.get(pin_no)?;
+        Ok(Box::new(RpiInputPin(pin.into_input())))
+    }
+
+    fn poll_interrupts(&self,
+                       pins: &[&dyn InputPin],
+                       timeout: Option<Duration>) -> Result<Option<(u8, Level, Instant)>>
+    {
+        let mut rpi_pins = Vec::new();
+        for pin in pins {
+            let rpi_pin = pin.as_any()
+                .downcast_ref::<RpiInputPin>()
+                .ok_or_else(|| IOError::Backend(format!("pin {} was not acquired from the Raspberry Pi", pin.pin())))?;
+            rpi_pins.push(&rpi_pin.0);
+        }
+
+        let gpio = Gpio::new()?;
+        let poll = gpio.poll_interrupts(rpi_pins.as_slice(), false, timeout)?;
+
+        // rppal does not report when the edge happened.
+        Ok(poll.map(|(pin, level)| (pin.pin(), from_rppal_level(level), Instant::now())))
+    }
+
+    fn serial(&self, uart: &UART, config: &SerialConfig) -> Result<Box<dyn SerialPort>> {
+        let parity = match config.get_parity() {
+            Parity::None => UARTParity::None,
+            Parity::Even => UARTParity::Even,
+            Parity::Odd => UARTParity::Odd,
+        };
+        let mut uart = Uart::with_path(
+            uart.path(),
+            config.get_baud_rate(),
+            parity,
+            config.get_data_bits(),
+            config.get_stop_bits())?;
+        let flow_control = config.get_flow_control();
+        uart.set_hardware_flow_control(flow_control == FlowControl::Hardware)?;
+        uart.set_software_flow_control(flow_control == FlowControl::Software)?;
+        uart.set_read_mode(0, config.get_read_timeout())?;
+
+        Ok(Box::new(RpiUart(uart)))
+    }
+
+    fn i2c(&self) -> Result<Box<dyn I2cBus>> {
+        Ok(Box::new(RpiI2c(I2c::new()?)))
+    }
+
+    fn spi(&self, bus: u8, chip_select: u8) -> Result<Box<dyn SpiBus>> {
+        Ok(Box::new(RpiSpi::open(bus, chip_select)?))
+    }
+}
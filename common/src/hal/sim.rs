@@ -0,0 +1,516 @@
+/*! In-process stand-in for a device under test.
+
+A [`SimulatedDevice`] is a [`Backend`] whose pins and serial ports are wired to a scripted device instead of hardware.
+The script reacts to the pins the testbed drives ([`SimulatedDevice::when`])
+and the data it writes to the device's serial ports ([`SimulatedDevice::when_received`]),
+and performs effects at fixed points after the device starts ([`SimulatedDevice::at`]).
+
+Pins are identified by their testbed-side numbers, as they would be wired to the testbed.
+Serial ports are identified by the path of the [`UART`] they are opened with.
+
+# Examples
+```ignore
+let sim = SimulatedDevice::new()
+    .with_reset(17, Level::Low)
+    .when(20, Trigger::RisingEdge, Duration::from_millis(5), Effect::Toggle(21))
+    .at(Duration::from_millis(100), Effect::Transmit(UART::Custom("trace".to_string()), b"boot".to_vec()));
+let mapping = Mapping::new(device, &[(17, 23), (20, 13), (21, 14)], &[])?
+    .with_backend(Arc::new(sim));
+```
+ */
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::io::{IOError, Result, SerialConfig, UART};
+
+use super::{
+    Backend,
+    I2cBus,
+    InputPin,
+    Level,
+    OutputPin,
+    SerialPort,
+    SpiBus,
+    Trigger,
+};
+
+/// Something the simulated device does.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Effect {
+    /// Drive an output of the device to a level.
+    Set(u8, Level),
+    /// Invert the level of an output of the device.
+    Toggle(u8),
+    /// Send bytes from the device over a serial port.
+    Transmit(UART, Vec<u8>),
+}
+
+/// Reaction of the simulated device to a pin the testbed drives.
+#[derive(Clone, Debug)]
+struct Rule {
+    pin_no: u8,
+    trigger: Trigger,
+    delay: Duration,
+    effect: Effect,
+}
+
+/// Reaction of the simulated device to data the testbed writes to one of its serial ports.
+#[derive(Clone, Debug)]
+struct Command {
+    path: String,
+    data: Vec<u8>,
+    delay: Duration,
+    effect: Effect,
+}
+
+fn triggers(trigger: Trigger, level: Level) -> bool {
+    match trigger {
+        Trigger::RisingEdge => level == Level::High,
+        Trigger::FallingEdge => level == Level::Low,
+        Trigger::Both => true,
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    rules: Vec<Rule>,
+    commands: Vec<Command>,
+    timeline: Vec<(Duration, Effect)>,
+    reset: Option<(u8, Level)>,
+    in_reset: bool,
+    started_at: Instant,
+
+    levels: HashMap<u8, Level>,
+    interrupts: HashMap<u8, Trigger>,
+    pending_edges: VecDeque<(u8, Level, Instant)>,
+    scheduled: Vec<(Instant, Effect)>,
+    serial_rx: HashMap<String, VecDeque<u8>>,
+}
+
+impl State {
+    fn level(&self, pin_no: u8) -> Level {
+        self.levels.get(&pin_no).copied().unwrap_or(Level::Low)
+    }
+
+    /// Record a level driven by the testbed and react to it.
+    fn drive(&mut self, pin_no: u8, level: Level, now: Instant) {
+        self.advance(now);
+
+        let previous = self.levels.insert(pin_no, level).unwrap_or(Level::Low);
+
+        if let Some((reset_pin, active)) = self.reset {
+            if reset_pin == pin_no {
+                if level == active {
+                    self.in_reset = true;
+                    self.scheduled.clear();
+                    self.serial_rx.clear();
+                } else if self.in_reset {
+                    self.start(now);
+                }
+                return;
+            }
+        }
+
+        if previous != level && !self.in_reset {
+            let reactions: Vec<_> = self.rules.iter()
+                .filter(|rule| rule.pin_no == pin_no && triggers(rule.trigger, level))
+                .map(|rule| (now + rule.delay, rule.effect.clone()))
+                .collect();
+            self.scheduled.extend(reactions);
+        }
+    }
+
+    /// Record data written to a serial port of the device and react to it.
+    fn receive(&mut self, path: &str, data: &[u8], now: Instant) {
+        self.advance(now);
+        if self.in_reset {
+            return;
+        }
+
+        let reactions: Vec<_> = self.commands.iter()
+            .filter(|command| command.path == path)
+            .filter(|command| command.data.is_empty()
+                    || data.windows(command.data.len()).any(|window| window == command.data.as_slice()))
+            .map(|command| (now + command.delay, command.effect.clone()))
+            .collect();
+        self.scheduled.extend(reactions);
+    }
+
+    /// Bring the device out of reset and schedule its timeline.
+    fn start(&mut self, now: Instant) {
+        self.in_reset = false;
+        self.started_at = now;
+        let timeline: Vec<_> = self.timeline.iter()
+            .map(|(offset, effect)| (now + *offset, effect.clone()))
+            .collect();
+        self.scheduled.extend(timeline);
+    }
+
+    /// Perform all scheduled effects that are due.
+    fn advance(&mut self, now: Instant) {
+        while let Some(idx) = self.next_due(now) {
+            let (t, effect) = self.scheduled.remove(idx);
+            self.apply(effect, t);
+        }
+    }
+
+    fn next_due(&self, now: Instant) -> Option<usize> {
+        self.scheduled.iter()
+            .enumerate()
+            .filter(|(_idx, (t, _effect))| *t <= now)
+            .min_by_key(|(_idx, (t, _effect))| *t)
+            .map(|(idx, _)| idx)
+    }
+
+    /// Returns when the next scheduled effect is due.
+    fn next_wakeup(&self) -> Option<Instant> {
+        self.scheduled.iter()
+            .map(|(t, _effect)| *t)
+            .min()
+    }
+
+    /// Perform an effect that came due at `t`.
+    fn apply(&mut self, effect: Effect, t: Instant) {
+        match effect {
+            Effect::Set(pin_no, level) => self.set_output(pin_no, level, t),
+            Effect::Toggle(pin_no) => {
+                let level = if self.level(pin_no) == Level::High { Level::Low } else { Level::High };
+                self.set_output(pin_no, level, t);
+            },
+            Effect::Transmit(uart, data) => {
+                self.serial_rx.entry(uart.path().to_string())
+                    .or_default()
+                    .extend(data);
+            },
+        }
+    }
+
+    fn set_output(&mut self, pin_no: u8, level: Level, t: Instant) {
+        let previous = self.levels.insert(pin_no, level).unwrap_or(Level::Low);
+        if previous != level {
+            if let Some(trigger) = self.interrupts.get(&pin_no) {
+                if triggers(*trigger, level) {
+                    self.pending_edges.push_back((pin_no, level, t));
+                }
+            }
+        }
+    }
+}
+
+fn poisoned<T>(_e: T) -> IOError {
+    IOError::Backend("simulated device state is poisoned".to_string())
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> Result<MutexGuard<'_, State>> {
+        self.state.lock().map_err(poisoned)
+    }
+
+    /** Wait until `ready` produces a value or `deadline` passes.
+
+    Scheduled effects are performed as they come due while waiting.
+     */
+    fn wait_for<T, F>(&self, deadline: Option<Instant>, mut ready: F) -> Result<Option<T>>
+    where
+        F: FnMut(&mut State) -> Option<T>,
+    {
+        let mut state = self.lock()?;
+        loop {
+            let now = Instant::now();
+            state.advance(now);
+            if let Some(value) = ready(&mut state) {
+                return Ok(Some(value));
+            }
+
+            let wake_at = match (deadline, state.next_wakeup()) {
+                (Some(deadline), _) if now >= deadline => return Ok(None),
+                (Some(deadline), Some(next)) => deadline.min(next),
+                (Some(deadline), None) => deadline,
+                (None, Some(next)) => next,
+                (None, None) => {
+                    state = self.changed.wait(state).map_err(poisoned)?;
+                    continue;
+                },
+            };
+
+            let (guard, _timeout) = self.changed.wait_timeout(state, wake_at.saturating_duration_since(now))
+                .map_err(poisoned)?;
+            state = guard;
+        }
+    }
+}
+
+/// Pin of the simulated device the testbed drives.
+#[derive(Debug)]
+pub struct SimOutputPin {
+    pin_no: u8,
+    shared: Arc<Shared>,
+}
+
+impl OutputPin for SimOutputPin {
+    fn pin(&self) -> u8 {
+        self.pin_no
+    }
+
+    fn set_high(&mut self) -> Result<()> {
+        self.write(Level::High)
+    }
+
+    fn set_low(&mut self) -> Result<()> {
+        self.write(Level::Low)
+    }
+
+    fn write(&mut self, level: Level) -> Result<()> {
+        self.shared.lock()?.drive(self.pin_no, level, Instant::now());
+        self.shared.changed.notify_all();
+        Ok(())
+    }
+
+    fn level(&self) -> Result<Level> {
+        Ok(self.shared.lock()?.level(self.pin_no))
+    }
+}
+
+/// Pin of the simulated device the testbed reads.
+#[derive(Debug)]
+pub struct SimInputPin {
+    pin_no: u8,
+    shared: Arc<Shared>,
+}
+
+impl InputPin for SimInputPin {
+    fn pin(&self) -> u8 {
+        self.pin_no
+    }
+
+    fn read(&self) -> Result<Level> {
+        let mut state = self.shared.lock()?;
+        state.advance(Instant::now());
+        Ok(state.level(self.pin_no))
+    }
+
+    fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+        let mut state = self.shared.lock()?;
+        state.advance(Instant::now());
+        state.pending_edges.retain(|(pin_no, _level, _t)| *pin_no != self.pin_no);
+        state.interrupts.insert(self.pin_no, trigger);
+        Ok(())
+    }
+
+    fn clear_interrupt(&mut self) -> Result<()> {
+        let mut state = self.shared.lock()?;
+        state.pending_edges.retain(|(pin_no, _level, _t)| *pin_no != self.pin_no);
+        state.interrupts.remove(&self.pin_no);
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Serial port of the simulated device.
+#[derive(Debug)]
+pub struct SimSerial {
+    path: String,
+    timeout: Duration,
+    shared: Arc<Shared>,
+}
+
+impl SerialPort for SimSerial {
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn set_write_timeout(&mut self, _timeout: Duration) -> Result<()> {
+        // Writes are received as soon as they are made.
+        Ok(())
+    }
+
+    fn flush_input(&mut self) -> Result<()> {
+        let mut state = self.shared.lock()?;
+        state.advance(Instant::now());
+        state.serial_rx.remove(&self.path);
+        Ok(())
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let path = &self.path;
+        let read = self.shared.wait_for(Some(Instant::now() + self.timeout), |state| {
+            let rx = state.serial_rx.get_mut(path)?;
+            if rx.is_empty() {
+                None
+            } else {
+                let count = rx.len().min(buffer.len());
+                for (dst, src) in buffer.iter_mut().zip(rx.drain(..count)) {
+                    *dst = src;
+                }
+                Some(count)
+            }
+        })?;
+
+        Ok(read.unwrap_or(0))
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        self.shared.lock()?.receive(&self.path, buffer, Instant::now());
+        self.shared.changed.notify_all();
+        Ok(buffer.len())
+    }
+}
+
+/** Scripted device under test.
+
+The device starts when it is created.
+If a reset pin is defined with [`SimulatedDevice::with_reset`],
+driving that pin to its active level stops the device and cancels anything it had scheduled;
+driving it back starts the device again.
+ */
+#[derive(Debug)]
+pub struct SimulatedDevice {
+    shared: Arc<Shared>,
+}
+
+impl SimulatedDevice {
+    /// Create a new simulated device that does nothing.
+    pub fn new() -> SimulatedDevice {
+        let state = State {
+            rules: Vec::new(),
+            commands: Vec::new(),
+            timeline: Vec::new(),
+            reset: None,
+            in_reset: false,
+            started_at: Instant::now(),
+            levels: HashMap::new(),
+            interrupts: HashMap::new(),
+            pending_edges: VecDeque::new(),
+            scheduled: Vec::new(),
+            serial_rx: HashMap::new(),
+        };
+
+        SimulatedDevice {
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                changed: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Define the pin that holds the device in reset when driven to `active`.
+    pub fn with_reset(self, pin_no: u8, active: Level) -> Self {
+        self.state().reset = Some((pin_no, active));
+        self
+    }
+
+    /// Perform `effect` `delay` after the testbed drives `pin_no` in a way that matches `trigger`.
+    pub fn when(self, pin_no: u8, trigger: Trigger, delay: Duration, effect: Effect) -> Self {
+        self.state().rules.push(Rule {
+            pin_no,
+            trigger,
+            delay,
+            effect,
+        });
+        self
+    }
+
+    /// Perform `effect` `delay` after the testbed writes data containing `data` to `uart`.
+    pub fn when_received(self, uart: UART, data: &[u8], delay: Duration, effect: Effect) -> Self {
+        self.state().commands.push(Command {
+            path: uart.path().to_string(),
+            data: data.to_vec(),
+            delay,
+            effect,
+        });
+        self
+    }
+
+    /// Perform `effect` at `offset` after the device starts.
+    pub fn at(self, offset: Duration, effect: Effect) -> Self {
+        {
+            let mut state = self.state();
+            if !state.in_reset {
+                let t = state.started_at + offset;
+                state.scheduled.push((t, effect.clone()));
+            }
+            state.timeline.push((offset, effect));
+        }
+        self
+    }
+
+    /// Returns the current level of a pin.
+    pub fn level(&self, pin_no: u8) -> Level {
+        let mut state = self.state();
+        state.advance(Instant::now());
+        state.level(pin_no)
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock()
+            .expect("simulated device state is poisoned")
+    }
+}
+
+impl Default for SimulatedDevice {
+    fn default() -> SimulatedDevice {
+        SimulatedDevice::new()
+    }
+}
+
+impl Backend for SimulatedDevice {
+    fn output_pin(&self, pin_no: u8) -> Result<Box<dyn OutputPin>> {
+        Ok(Box::new(SimOutputPin {
+            pin_no,
+            shared: Arc::clone(&self.shared),
+        }))
+    }
+
+    fn input_pin(&self, pin_no: u8) -> Result<Box<dyn InputPin>> {
+        Ok(Box::new(SimInputPin {
+            pin_no,
+            shared: Arc::clone(&self.shared),
+        }))
+    }
+
+    fn poll_interrupts(&self,
+                       pins: &[&dyn InputPin],
+                       timeout: Option<Duration>) -> Result<Option<(u8, Level, Instant)>>
+    {
+        let pin_nos: Vec<u8> = pins.iter()
+            .map(|pin| pin.pin())
+            .collect();
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        self.shared.wait_for(deadline, |state| {
+            let idx = state.pending_edges.iter()
+                .position(|(pin_no, _level, _t)| pin_nos.contains(pin_no))?;
+            state.pending_edges.remove(idx)
+        })
+    }
+
+    fn serial(&self, uart: &UART, config: &SerialConfig) -> Result<Box<dyn SerialPort>> {
+        // Data arrives as whole transmissions, so only the read timeout matters.
+        Ok(Box::new(SimSerial {
+            path: uart.path().to_string(),
+            timeout: config.get_read_timeout(),
+            shared: Arc::clone(&self.shared),
+        }))
+    }
+
+    fn i2c(&self) -> Result<Box<dyn I2cBus>> {
+        Err(IOError::Backend("simulated device has no I2C bus".to_string()))
+    }
+
+    fn spi(&self, _bus: u8, _chip_select: u8) -> Result<Box<dyn SpiBus>> {
+        Err(IOError::Backend("simulated device has no SPI bus".to_string()))
+    }
+}
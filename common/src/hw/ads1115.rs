@@ -0,0 +1,117 @@
+//! Support for the ADS1115 analog-to-digital converter.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::facility::AnalogInput;
+use crate::hal::{I2cDevice, SharedI2cBus};
+
+/// ADS1115 register addresses.
+mod register {
+    pub const CONVERSION: u8 = 0x00;
+    pub const CONFIG: u8     = 0x01;
+}
+
+/// Start a single conversion when written; conversion finished when read.
+const CONFIG_OS: u16 = 1 << 15;
+/// Full-scale range of ±4.096V.
+const CONFIG_PGA_4V096: u16 = 0b001 << 9;
+/// Single-shot conversions.
+const CONFIG_MODE_SINGLE: u16 = 1 << 8;
+/// 860 samples per second.
+const CONFIG_DR_860: u16 = 0b111 << 5;
+/// Comparator disabled.
+const CONFIG_COMP_DISABLE: u16 = 0b11;
+
+/// Conversion factor for the ±4.096V range (0.125mV per value).
+const LSB_MV: f32 = 0.125;
+
+/// Status reads before giving up on a conversion, about 8 times as long as one takes.
+const CONVERSION_POLLS: u32 = 50;
+
+/// Driver for the TI ADS1115 16-bit ADC.
+#[derive(Debug)]
+pub struct ADS1115 {
+    address: u8,
+    i2c: Arc<Mutex<I2cDevice>>,
+}
+
+impl ADS1115 {
+    /// Create a new ADS1115 driver.
+    pub fn new(i2c: &SharedI2cBus, address: u8) -> Result<ADS1115, String> {
+        Ok(ADS1115 {
+            address,
+            i2c: Arc::new(Mutex::new(i2c.device(address as u16))),
+        })
+    }
+
+    /// Returns the I2C address of the ADC.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /** Returns one of the single-ended inputs (AIN0–AIN3) as an [`AnalogInput`].
+
+    Channels of the same ADC share it and convert one at a time.
+    Other peripherals can use the bus while a conversion is in progress.
+     */
+    pub fn channel(&self, channel: u8) -> Result<ADS1115Channel, String> {
+        if channel > 3 {
+            Err(format!("ADS1115 has no channel {}", channel))
+        } else {
+            Ok(ADS1115Channel {
+                channel,
+                i2c: Arc::clone(&self.i2c),
+            })
+        }
+    }
+}
+
+/// Single-ended input of an [`ADS1115`].
+#[derive(Debug)]
+pub struct ADS1115Channel {
+    channel: u8,
+    i2c: Arc<Mutex<I2cDevice>>,
+}
+
+impl ADS1115Channel {
+    /// Perform a single-shot conversion, returning the voltage in millivolts.
+    pub fn convert(&self) -> Result<f32, String> {
+        let i2c = self.i2c.lock()
+            .map_err(|_e| "ADS1115 is poisoned".to_string())?;
+
+        // MUX 1xx selects AINx against GND.
+        let mux = (0b100 | self.channel as u16) << 12;
+        let config = CONFIG_OS | mux | CONFIG_PGA_4V096 | CONFIG_MODE_SINGLE | CONFIG_DR_860 | CONFIG_COMP_DISABLE;
+        i2c.write(&[register::CONFIG, (config >> 8) as u8, config as u8])
+            .map_err(|e| format!("failed to start conversion: {}", e))?;
+
+        // A conversion at 860SPS takes about 1.2ms.
+        let mut status = [0u8; 2];
+        let mut polls = 0;
+        loop {
+            thread::sleep(Duration::from_micros(200));
+            i2c.write_read(&[register::CONFIG], &mut status)
+                .map_err(|e| format!("failed to read conversion status: {}", e))?;
+            if u16::from_be_bytes(status) & CONFIG_OS != 0 { break; }
+
+            polls += 1;
+            if polls == CONVERSION_POLLS {
+                return Err(format!("conversion did not finish after {} status reads", polls));
+            }
+        }
+
+        let mut raw = [0u8; 2];
+        i2c.write_read(&[register::CONVERSION], &mut raw)
+            .map_err(|e| format!("failed to read conversion: {}", e))?;
+
+        Ok(i16::from_be_bytes(raw) as f32 * LSB_MV)
+    }
+}
+
+impl AnalogInput for ADS1115Channel {
+    fn voltage(&self) -> Result<f32, String> {
+        self.convert()
+    }
+}
@@ -1,13 +1,10 @@
 //! Support for the INA219 sensor.
 
-use std::cell::{RefCell, RefMut};
-use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 
-use rppal::i2c::I2c;
-
 use crate::facility::EnergyMetering;
+use crate::hal::{I2cDevice, SharedI2cBus};
 
 /// INA219 register addresses.
 #[allow(unused)]
@@ -27,17 +24,17 @@ const BUS_VOLTAGE_LSB: f32 = 0.004;
 #[derive(Debug)]
 pub struct INA219 {
     address: u8,
-    i2c: Mutex<RefCell<I2c>>,
+    i2c: I2cDevice,
 }
 
 impl INA219 {
     const CURRENT_LSB: f32 = 0.0305;
 
     /// Create a new INA219 driver.
-    pub fn new(i2c: I2c, address: u8) -> Result<INA219, String> {
+    pub fn new(i2c: &SharedI2cBus, address: u8) -> Result<INA219, String> {
         let ina = INA219 {
             address,
-            i2c: Mutex::new(RefCell::new(i2c)),
+            i2c: i2c.device(address as u16),
         };
         ina.init()?;
 
@@ -70,11 +67,12 @@ impl INA219 {
         Ok(((raw >> 3) as f32) * BUS_VOLTAGE_LSB)
     }
 
+    /// Returns the I2C address of the sensor.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
     fn init(&self) -> Result<(), String> {
-        self.with_i2c(|mut i2c| {
-            i2c.set_slave_address(self.address as u16)
-                .map_err(|e| format!("failed to set peripheral address: {}", e))
-        })?;
         self.reset()?;
 
         /* Set configuration; see INA219 documentation for details.
@@ -99,11 +97,9 @@ impl INA219 {
 
     fn read(&self, reg_addr: u8) -> Result<u16, String> {
         let mut out = [0xff; 2];
-        self.with_i2c(|i2c| {
-            i2c.write_read(&[reg_addr], &mut out)
-                .map_err(|e| format!("failed to perform write-read: {}", e))?;
-            Ok(((out[0] as u16) << 8) | (out[1] as u16))
-        })
+        self.i2c.write_read(&[reg_addr], &mut out)
+            .map_err(|e| format!("failed to perform write-read: {}", e))?;
+        Ok(((out[0] as u16) << 8) | (out[1] as u16))
     }
 
     fn write(&self, reg_addr: u8, value: u16) -> Result<(), String> {
@@ -112,21 +108,9 @@ impl INA219 {
             (value >> 8) as u8,
             (value & 0xFF) as u8,
         ];
-        self.with_i2c(|mut i2c| {
-            i2c.write(&buf)
-                .map(|_bytes_written| ())
-                .map_err(|e| format!("failed to write {:X} register: {}", reg_addr, e))
-        })
-    }
-
-    fn with_i2c<F, T>(&self, op: F) -> Result<T, String>
-    where
-        F: FnOnce(RefMut<'_, I2c>) -> Result<T, String>
-    {
-        let i2c_cell = self.i2c.lock()
-            .map_err(|e| format!("failed to lock I2C interface: {}", e))?;
-
-        op(i2c_cell.borrow_mut())
+        self.i2c.write(&buf)
+            .map(|_bytes_written| ())
+            .map_err(|e| format!("failed to write {:X} register: {}", reg_addr, e))
     }
 }
 
@@ -0,0 +1,59 @@
+//! Support for the MCP4725 digital-to-analog converter.
+
+use crate::facility::AnalogOutput;
+use crate::hal::{I2cDevice, SharedI2cBus};
+
+/// Highest code the 12-bit DAC accepts.
+const MAX_CODE: u32 = 0x0FFF;
+
+/// Driver for the Microchip MCP4725 12-bit DAC.
+#[derive(Debug)]
+pub struct MCP4725 {
+    address: u8,
+    reference_mv: u32,
+    i2c: I2cDevice,
+}
+
+impl MCP4725 {
+    /** Create a new MCP4725 driver.
+
+    `reference_mv` is the DAC's supply voltage in millivolts, which sets its full-scale output.
+    The output is driven to 0V.
+     */
+    pub fn new(i2c: &SharedI2cBus, address: u8, reference_mv: u32) -> Result<MCP4725, String> {
+        let mut dac = MCP4725 {
+            address,
+            reference_mv,
+            i2c: i2c.device(address as u16),
+        };
+        dac.write_code(0)?;
+
+        Ok(dac)
+    }
+
+    /// Returns the I2C address of the DAC.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Write a code to the DAC register without touching its EEPROM.
+    fn write_code(&mut self, code: u16) -> Result<(), String> {
+        // Fast mode write: power-down bits cleared, then the 12-bit code.
+        let data = [((code >> 8) & 0x0F) as u8, (code & 0xFF) as u8];
+        self.i2c.write(&data)
+            .map(|_| ())
+            .map_err(|e| format!("failed to write DAC code: {}", e))
+    }
+}
+
+impl AnalogOutput for MCP4725 {
+    fn max_voltage(&self) -> u32 {
+        self.reference_mv
+    }
+
+    fn set_voltage(&mut self, millivolts: u32) -> Result<(), String> {
+        let millivolts = millivolts.min(self.reference_mv) as u64;
+        let code = (millivolts * MAX_CODE as u64 + self.reference_mv as u64 / 2) / self.reference_mv as u64;
+        self.write_code(code as u16)
+    }
+}
@@ -1,5 +1,11 @@
 //! Drivers for additional hardware for the testbed.
 
+mod ads1115;
 mod ina219;
+mod mcp4725;
+mod pwm;
 
+pub use ads1115::{ADS1115, ADS1115Channel};
 pub use ina219::INA219;
+pub use mcp4725::MCP4725;
+pub use pwm::PwmOutput;
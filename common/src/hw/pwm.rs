@@ -0,0 +1,50 @@
+//! Analog output from the Raspberry Pi's hardware PWM.
+
+use rppal::pwm::{Channel, Polarity, Pwm};
+
+use crate::facility::AnalogOutput;
+
+/** Voltage produced by filtering a hardware PWM channel.
+
+The average voltage of a PWM signal is proportional to its duty cycle,
+so a low-pass filter between the PWM pin and the device turns the duty cycle into a voltage.
+The PWM frequency should sit well above the filter's cutoff.
+ */
+#[derive(Debug)]
+pub struct PwmOutput {
+    pwm: Pwm,
+    high_mv: u32,
+}
+
+impl PwmOutput {
+    /** Create a new PWM-driven analog output.
+
+    `high_mv` is the voltage of the PWM signal when high (3300 for the Raspberry Pi) and must not be 0.
+    The channel starts enabled at a 0% duty cycle.
+     */
+    pub fn new(channel: Channel, frequency: f64, high_mv: u32) -> Result<PwmOutput, String> {
+        if high_mv == 0 {
+            return Err(format!("PWM channel {} needs a high voltage above 0mV", channel));
+        }
+
+        let pwm = Pwm::with_frequency(channel, frequency, 0.0, Polarity::Normal, true)
+            .map_err(|e| format!("failed to set up PWM channel {}: {}", channel, e))?;
+
+        Ok(PwmOutput {
+            pwm,
+            high_mv,
+        })
+    }
+}
+
+impl AnalogOutput for PwmOutput {
+    fn max_voltage(&self) -> u32 {
+        self.high_mv
+    }
+
+    fn set_voltage(&mut self, millivolts: u32) -> Result<(), String> {
+        let duty_cycle = millivolts.min(self.high_mv) as f64 / self.high_mv as f64;
+        self.pwm.set_duty_cycle(duty_cycle)
+            .map_err(|e| format!("failed to set PWM duty cycle: {}", e))
+    }
+}
@@ -1,6 +1,6 @@
 //! Flexible testbed and test providers.
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display};
 
 use crate::test::Test;
 use crate::testbed::Testbed;
@@ -16,3 +16,140 @@ pub trait TestProvider: Debug {
     /// Create a Test-producing iterator.
     fn tests<'a>(&'a self) -> Box<dyn Iterator<Item = Test> + 'a>;
 }
+
+/** Returns true if `text` matches the glob `pattern`.
+
+`*` matches any run of characters and `?` any single character.
+ */
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*` if the rest does not match.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            },
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    // Let the `*` absorb one more character.
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    t = matched + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Property of a test that selects it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Selector {
+    /// Test identifier matches a glob (`*` and `?`).
+    Id(String),
+    /// Test is tagged with the tag.
+    Tag(String),
+    /// Test loads the application onto a device under test.
+    App(String),
+    /// Test requires the trace point.
+    TracePoint(String),
+}
+
+impl Selector {
+    /// Returns true if the test has the property.
+    pub fn matches(&self, test: &Test) -> bool {
+        match self {
+            Selector::Id(pattern) => glob_matches(pattern, test.get_id()),
+            Selector::Tag(tag) => test.get_tags().contains(tag),
+            Selector::App(app_id) => test.get_all_app_ids().any(|id| id == app_id),
+            Selector::TracePoint(trace_point) => test.get_trace_points().contains(trace_point),
+        }
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Selector::Id(pattern) => write!(f, "id '{}'", pattern),
+            Selector::Tag(tag) => write!(f, "tag '{}'", tag),
+            Selector::App(app_id) => write!(f, "app '{}'", app_id),
+            Selector::TracePoint(trace_point) => write!(f, "trace point '{}'", trace_point),
+        }
+    }
+}
+
+/** Which of the tests a provider yields to run.
+
+A test is selected if it matches any of the included selectors (or none are given)
+and none of the excluded ones.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct TestSelection {
+    include: Vec<Selector>,
+    exclude: Vec<Selector>,
+}
+
+impl TestSelection {
+    /// Create a selection of every test.
+    pub fn new() -> TestSelection {
+        TestSelection::default()
+    }
+
+    /// Select tests matching `selector`, along with the others included.
+    pub fn with_include(mut self, selector: Selector) -> Self {
+        self.include.push(selector);
+        self
+    }
+
+    /// Leave out tests matching `selector`, even if they are included.
+    pub fn with_exclude(mut self, selector: Selector) -> Self {
+        self.exclude.push(selector);
+        self
+    }
+
+    /// Returns true if the test is selected.
+    pub fn selects(&self, test: &Test) -> bool {
+        let included = self.include.is_empty()
+            || self.include.iter().any(|selector| selector.matches(test));
+        included && !self.exclude.iter().any(|selector| selector.matches(test))
+    }
+
+    /// Returns the selected tests.
+    pub fn filter<'a, T>(&'a self, tests: T) -> impl Iterator<Item = Test> + 'a
+    where
+        T: IntoIterator<Item = Test>,
+        T::IntoIter: 'a,
+    {
+        tests.into_iter()
+            .filter(move |test| self.selects(test))
+    }
+}
+
+#[cfg(test)]
+#[allow(missing_docs)]
+pub mod tests {
+    use super::glob_matches;
+
+    #[test]
+    pub fn glob_wildcards() {
+        assert!(glob_matches("radio-*", "radio-tx"));
+        assert!(glob_matches("*-tx", "radio-tx"));
+        assert!(glob_matches("r?dio*tx", "radio-packet-tx"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("radio-*", "blink"));
+        assert!(!glob_matches("radio", "radio-tx"));
+        assert!(!glob_matches("?", ""));
+    }
+}
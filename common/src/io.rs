@@ -1,46 +1,76 @@
 //! Managing inputs to and outputs from the device under test.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::From;
 use std::fmt;
 use std::fmt::Display;
 use std::iter::{Iterator, IntoIterator};
-use std::rc::Rc;
+use std::process;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use rppal::gpio;
-use rppal::gpio::{Gpio, InputPin, OutputPin};
 use rppal::i2c;
-use rppal::i2c::I2c;
+use rppal::spi;
 use rppal::uart;
-use rppal::uart::{
-    Uart,
-    Parity as UARTParity,
-};
 
 use crate::comm::{
     Class as SignalClass,
     Direction,
 };
+use crate::facility::AnalogOutput;
+use crate::hal::endpoint::Pty;
+use crate::hal::{
+    Backend,
+    InputPin,
+    Level,
+    OutputPin,
+    PtyEndpoint,
+    RaspberryPi,
+    SerialPort,
+    SharedI2cBus,
+    SpiBus,
+    TcpEndpoint,
+};
 
 /// Testbed I/O result type.
 pub type Result<T> = std::result::Result<T, IOError>;
 
 /// Set of pins that provide input _to_ the device under test.
-pub type DeviceInputs = Pins<OutputPin>;
+pub type DeviceInputs = Pins<Box<dyn OutputPin>>;
 /// Set of pins that accept output _from_ the device under test.
-pub type DeviceOutputs = Pins<InputPin>;
+pub type DeviceOutputs = Pins<Box<dyn InputPin>>;
+/// Set of analog outputs that drive voltages _to_ the device under test.
+pub type DeviceAnalogInputs = Pins<Box<dyn AnalogOutput>>;
 
 /// Errors related to acquiring and configuring I/O.
 #[derive(Debug)]
 pub enum IOError {
+    /// Error reported by an I/O backend.
+    Backend(String),
+    /// Serial endpoint error.
+    Endpoint(std::io::Error),
     /// GPIO-specific error.
     Gpio(gpio::Error),
+    /// GPIO character device error.
+    GpioChip(gpio_cdev::Error),
     /// Mapping does not allow I2C.
     I2CUnavailable,
     /// I2C initialization error.
     I2C(i2c::Error),
+    /// Mapping is not valid for the device and testbed.
+    InvalidMapping(Vec<MappingConflict>),
     /// Reset functionality not defined.
     NoReset,
+    /// Reset command failed.
+    ResetCommand(String),
+    /// Reset command did not finish in time and was killed (program).
+    ResetTimeout(String),
+    /// Mapping does not allow the SPI bus and chip select (bus, chip select).
+    SPIUnavailable(u8, u8),
+    /// SPI initialization error.
+    SPI(spi::Error),
     /// Mapping does not allow UART.
     UARTUnavailable,
     /// UART initialization error.
@@ -52,7 +82,9 @@ pub enum IOError {
 impl std::error::Error for IOError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
+            IOError::Endpoint(ref io_error) => Some(io_error),
             IOError::Gpio(ref gpio_error) => Some(gpio_error),
+            IOError::GpioChip(ref cdev_error) => Some(cdev_error),
             _ => None,
         }
     }
@@ -62,10 +94,32 @@ impl Display for IOError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use IOError::*;
         match self {
+            Backend(ref msg) => write!(f, "I/O backend error: {}", msg),
+            Endpoint(ref e) => write!(f, "error with serial endpoint: {}", e),
             Gpio(ref e) => write!(f, "error with GPIO interface: {}", e),
+            GpioChip(ref e) => write!(f, "error with GPIO character device: {}", e),
             I2CUnavailable => write!(f, "I2C pins (2, 3) are mapped"),
             I2C(ref e) => write!(f, "could not obtain I2C interface: {}", e),
+            InvalidMapping(ref conflicts) => {
+                write!(f, "invalid I/O mapping: ")?;
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", conflict)?;
+                }
+                Ok(())
+            },
             NoReset => write!(f, "reset functionality is not defined for the device"),
+            ResetCommand(ref msg) => write!(f, "reset command failed: {}", msg),
+            ResetTimeout(ref program) => write!(f, "reset command '{}' did not finish in time", program),
+            SPIUnavailable(bus, chip_select) => {
+                let pins: Vec<String> = Peripheral::SPI { bus: *bus, chip_select: *chip_select }.pins().iter()
+                    .map(|p| p.to_string())
+                    .collect();
+                write!(f, "SPI{} pins ({}) are mapped", bus, pins.join(", "))
+            },
+            SPI(ref e) => write!(f, "could not obtain SPI interface: {}", e),
             UARTUnavailable => write!(f, "UART pins (14, 15) are mapped"),
             UART(ref e) => write!(f, "could not obtain UART interface: {}", e),
             UndefinedPin(pin_no) => write!(f, "undefined pin ({}) used", pin_no),
@@ -79,18 +133,122 @@ impl From<gpio::Error> for IOError {
     }
 }
 
+impl From<gpio_cdev::Error> for IOError {
+    fn from(e: gpio_cdev::Error) -> Self {
+        IOError::GpioChip(e)
+    }
+}
+
 impl From<i2c::Error> for IOError {
     fn from(e: i2c::Error) -> Self {
         IOError::I2C(e)
     }
 }
 
+impl From<spi::Error> for IOError {
+    fn from(e: spi::Error) -> Self {
+        IOError::SPI(e)
+    }
+}
+
 impl From<uart::Error> for IOError {
     fn from(e: uart::Error) -> Self {
         IOError::UART(e)
     }
 }
 
+/// Problem found while validating a [`Mapping`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MappingConflict {
+    /// Testbed pin appears more than once in the mapping.
+    DuplicateHostPin(u8),
+    /// Device pin is mapped from more than one testbed pin (device pin, testbed pins).
+    SharedDevicePin(u8, Vec<u8>),
+    /// Device pin is not defined by the device.
+    UndefinedPin(u8),
+    /// Reset pin is not an input to the device.
+    ResetDirection(u8),
+    /// Reset pin is not mapped to a testbed pin.
+    UnmappedReset(u8),
+    /// Load switch pin of a power cycle reset is also mapped to a device pin.
+    MappedPowerSwitch(u8),
+    /// Testbed pin is needed by a reserved peripheral (testbed pin, peripheral).
+    ReservedPin(u8, Peripheral),
+}
+
+impl Display for MappingConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use MappingConflict::*;
+        match self {
+            DuplicateHostPin(pin_no) => write!(f, "testbed pin {} is mapped more than once", pin_no),
+            SharedDevicePin(pin_no, ref host_pins) => {
+                let host_pins: Vec<String> = host_pins.iter().map(|p| p.to_string()).collect();
+                write!(f, "device pin {} is mapped from testbed pins {}", pin_no, host_pins.join(", "))
+            },
+            UndefinedPin(pin_no) => write!(f, "device pin {} is not defined", pin_no),
+            ResetDirection(pin_no) => write!(f, "reset pin {} is not an input to the device", pin_no),
+            UnmappedReset(pin_no) => write!(f, "reset pin {} is not mapped to a testbed pin", pin_no),
+            MappedPowerSwitch(pin_no) => write!(f, "power switch pin {} is also mapped to the device", pin_no),
+            ReservedPin(pin_no, ref peripheral) => write!(f, "testbed pin {} is needed by {}", pin_no, peripheral),
+        }
+    }
+}
+
+/** Testbed peripheral that uses fixed testbed pins.
+
+Peripherals reserved when creating a [`Mapping`] make mapping over their pins an error.
+ */
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Peripheral {
+    /// I2C bus on pins 2 (SDA) and 3 (SCL).
+    I2C,
+    /** SPI bus and the chip select line of one peripheral on it.
+
+    The Raspberry Pi's SPI0 uses pins 9 (MISO), 10 (MOSI), 11 (SCLK), and 8 or 7 (CE0, CE1);
+    SPI1 uses pins 19, 20, 21, and 18, 17, or 16 (CE0-CE2);
+    SPI2 uses pins 40, 41, 42, and 43, 44, or 45 (CE0-CE2).
+    Other buses and chip selects use no GPIO pins.
+     */
+    SPI {
+        /// Bus number
+        bus: u8,
+        /// Chip select line
+        chip_select: u8,
+    },
+    /// UART on pins 14 (TX) and 15 (RX) when it is [`UART::PL011`]; other UARTs use no GPIO pins.
+    UART(UART),
+}
+
+impl Peripheral {
+    /// Returns the testbed pins the peripheral uses.
+    pub fn pins(&self) -> &'static [u8] {
+        match self {
+            Peripheral::I2C => &[2, 3],
+            Peripheral::SPI { bus: 0, chip_select: 0 } => &[9, 10, 11, 8],
+            Peripheral::SPI { bus: 0, chip_select: 1 } => &[9, 10, 11, 7],
+            Peripheral::SPI { bus: 1, chip_select: 0 } => &[19, 20, 21, 18],
+            Peripheral::SPI { bus: 1, chip_select: 1 } => &[19, 20, 21, 17],
+            Peripheral::SPI { bus: 1, chip_select: 2 } => &[19, 20, 21, 16],
+            Peripheral::SPI { bus: 2, chip_select: 0 } => &[40, 41, 42, 43],
+            Peripheral::SPI { bus: 2, chip_select: 1 } => &[40, 41, 42, 44],
+            Peripheral::SPI { bus: 2, chip_select: 2 } => &[40, 41, 42, 45],
+            Peripheral::SPI { .. } => &[],
+            Peripheral::UART(UART::PL011) => &[14, 15],
+            Peripheral::UART(_) => &[],
+        }
+    }
+}
+
+impl Display for Peripheral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Peripheral::I2C => write!(f, "I2C"),
+            Peripheral::SPI { bus, chip_select } => write!(f, "SPI{} (CE{})", bus, chip_select),
+            Peripheral::UART(ref uart) => write!(f, "UART ({})", uart.path()),
+        }
+    }
+}
+
 /// Wrapper around a set of pins.
 #[derive(Debug)]
 pub struct Pins<T> {
@@ -99,7 +257,7 @@ pub struct Pins<T> {
 
 impl<T> Pins<T> {
     /// Create a new collection of pins.
-    fn new<U>(pins: U) -> Pins<T> where
+    pub(crate) fn new<U>(pins: U) -> Pins<T> where
         U: IntoIterator<Item = (u8, T)>
     {
         Pins {
@@ -122,6 +280,11 @@ impl<T> Pins<T> {
             .ok_or(IOError::UndefinedPin(pin_no))
     }
 
+    /// Returns the numbers of the configured pins.
+    pub fn pin_numbers(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pins.keys().copied()
+    }
+
     /// Returns all configured pins as plain references.
     pub fn get(&self) -> Result<Vec<&T>> {
         let pins = self.pins.iter()
@@ -176,11 +339,116 @@ impl<'a, T> IntoIterator for &'a mut Pins<T> {
     }
 }
 
+/// How often a running reset command is checked on.
+const RESET_COMMAND_POLL: Duration = Duration::from_millis(10);
+
+/// Function holding or releasing a device with [`ResetStrategy::Custom`].
+pub type ResetFn = Arc<dyn Fn(&mut DeviceInputs) -> Result<()> + Send + Sync>;
+
+/** How the testbed resets a device under test.
+
+The testbed holds the device in reset before a test starts and releases it as the test starts.
+ */
+#[derive(Clone)]
+pub enum ResetStrategy {
+    /// Drive a device pin to its active level for the hold time, then to the opposite level.
+    Pin {
+        /// Device-side pin number.
+        pin_no: u8,
+        /// Level that holds the device in reset.
+        active: Level,
+        /// How long to hold the device in reset before the test starts.
+        hold: Duration,
+    },
+    /// Cut power to the device through a load switch driven by a testbed pin, then restore it.
+    PowerCycle {
+        /// Testbed-side pin number of the load switch.
+        switch_pin: u8,
+        /// Level that switches power on.
+        on: Level,
+        /// How long to keep power off before the test starts.
+        off: Duration,
+    },
+    /// Run an external command that resets the device, such as `tockloader` or `openocd`.
+    ///
+    /// The command runs before the test starts, so the device does not start with the test.
+    /// A command still running when the test [times out](crate::test::Test::with_timeout) is killed.
+    Command {
+        /// Program to run.
+        program: String,
+        /// Arguments to the program.
+        args: Vec<String>,
+    },
+    /// Hold and release the device with custom functions.
+    Custom {
+        /// Places the device in reset.
+        hold: ResetFn,
+        /// Releases the device from reset.
+        release: ResetFn,
+    },
+}
+
+impl ResetStrategy {
+    /// Create a strategy running `program` with `args`.
+    pub fn command<'a, T>(program: &str, args: T) -> ResetStrategy
+    where
+        T: IntoIterator<Item = &'a str>,
+    {
+        ResetStrategy::Command {
+            program: program.to_string(),
+            args: args.into_iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+}
+
+impl fmt::Debug for ResetStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ResetStrategy::*;
+        match self {
+            Pin { pin_no, active, hold } => f.debug_struct("Pin")
+                .field("pin_no", pin_no)
+                .field("active", active)
+                .field("hold", hold)
+                .finish(),
+            PowerCycle { switch_pin, on, off } => f.debug_struct("PowerCycle")
+                .field("switch_pin", switch_pin)
+                .field("on", on)
+                .field("off", off)
+                .finish(),
+            Command { program, args } => f.debug_struct("Command")
+                .field("program", program)
+                .field("args", args)
+                .finish(),
+            Custom { .. } => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Display for ResetStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ResetStrategy::*;
+        match self {
+            Pin { pin_no, active, hold } =>
+                write!(f, "device pin {} held {:?} for {:?}", pin_no, active, hold),
+            PowerCycle { switch_pin, on, off } =>
+                write!(f, "power cycle through testbed pin {} (on {:?}, off for {:?})", switch_pin, on, off),
+            Command { program, args } => {
+                write!(f, "command '{}", program)?;
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+                write!(f, "'")
+            },
+            Custom { .. } => write!(f, "custom"),
+        }
+    }
+}
+
 /// Properties of a device under test.
+#[derive(Debug)]
 pub struct Device {
     io: HashMap<u8, (Direction, SignalClass)>,
-    hold_reset: Option<Rc<dyn Fn(&mut DeviceInputs) -> Result<()>>>,
-    release_reset: Option<Rc<dyn Fn(&mut DeviceInputs) -> Result<()>>>,
+    reset: Option<ResetStrategy>,
 }
 
 impl Device {
@@ -193,27 +461,23 @@ impl Device {
         T: IntoIterator<Item = &'b (u8, (Direction, SignalClass))> {
         Device {
             io: pin_map.into_iter().map(|x| *x).collect(),
-            hold_reset: None,
-            release_reset: None,
+            reset: None,
         }
     }
 
-    /// Define reset functionality for the device.
-    pub fn with_reset(self,
-                      hold_reset: Rc<dyn Fn(&mut DeviceInputs) -> Result<()>>,
-                      release_reset: Rc<dyn Fn(&mut DeviceInputs) -> Result<()>>)
-                      -> Self
-    {
-        let (hold_reset, release_reset) = (Some(hold_reset),
-                                           Some(release_reset));
-
+    /// Define how the device is reset.
+    pub fn with_reset(self, strategy: ResetStrategy) -> Self {
         Self {
-            hold_reset,
-            release_reset,
+            reset: Some(strategy),
             ..self
         }
     }
 
+    /// Returns how the device is reset.
+    pub fn get_reset(&self) -> Option<&ResetStrategy> {
+        self.reset.as_ref()
+    }
+
     /// Returns true if the device definition defines a pin.
     pub fn has_pin(&self, pin_no: u8) -> bool {
         self.io.contains_key(&pin_no)
@@ -250,42 +514,182 @@ impl Device {
             .map(|&(_dir, sig)| sig)
             .ok_or(IOError::UndefinedPin(pin))
     }
-
-    /// Place the device in reset.
-    pub fn hold_in_reset(&self, inputs: &mut DeviceInputs) -> Result<()> {
-        let hold_reset = &*self.hold_reset.as_ref().ok_or(IOError::NoReset)?;
-        hold_reset(inputs)
-    }
-
-    /// Release the device from reset.
-    pub fn release_from_reset(&self, inputs: &mut DeviceInputs) -> Result<()> {
-        let release_reset = &*self.release_reset.as_ref().ok_or(IOError::NoReset)?;
-        release_reset(inputs)
-    }
-}
-
-impl fmt::Debug for Device {
-    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
-        Ok(())
-    }
 }
 
 /// Defined UART interfaces.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum UART {
     /// Full UART built into the Raspberry Pi.
     PL011,
     /// Other UART connected to the Raspberry Pi.
     Custom(String),
+    /// Pseudo-terminal created by the testbed, linked at the given path for a local process to write to.
+    Pty(String),
+    /// Raw TCP connection to a serial bridge at the given `host:port`.
+    Tcp(String),
 }
 
 impl UART {
-    /// Path to the UART the variant refers to.
+    /// Path to the UART the variant refers to, or the address of a TCP bridge.
     pub fn path(&self) -> &str {
         use UART::*;
         match self {
             PL011 => "/dev/ttyAMA0",
             Custom(ref path) => path.as_ref(),
+            Pty(ref path) => path.as_ref(),
+            Tcp(ref address) => address.as_ref(),
+        }
+    }
+}
+
+/// Parity bit checking on a serial line.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Parity {
+    /// No parity bit
+    None,
+    /// Even parity
+    Even,
+    /// Odd parity
+    Odd,
+}
+
+/// Flow control on a serial line.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FlowControl {
+    /// No flow control
+    None,
+    /// RTS/CTS flow control
+    Hardware,
+    /// XON/XOFF flow control
+    Software,
+}
+
+/** Line settings used to open a UART.
+
+The default matches what the testbed has always used:
+115200 baud, even parity, 8 data bits, 1 stop bit, no flow control, and reads that give up after 100ms.
+Serial tracing reads ports without a configured read timeout with a 50ms timeout instead,
+so tests that trace end promptly.
+
+# Examples
+```
+use std::time::Duration;
+use clockwise_common::io::{Parity, SerialConfig};
+
+// 921600 8N1
+let config = SerialConfig::new(921_600)
+    .with_parity(Parity::None)
+    .with_read_timeout(Duration::from_millis(20));
+```
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SerialConfig {
+    baud_rate: u32,
+    parity: Parity,
+    data_bits: u8,
+    stop_bits: u8,
+    flow_control: FlowControl,
+    read_timeout: Option<Duration>,
+}
+
+impl SerialConfig {
+    /// Create a new configuration at the given baud rate with the default line settings.
+    pub fn new(baud_rate: u32) -> SerialConfig {
+        SerialConfig {
+            baud_rate,
+            ..SerialConfig::default()
+        }
+    }
+
+    /// Use the given parity.
+    pub fn with_parity(self, parity: Parity) -> Self {
+        Self {
+            parity,
+            ..self
+        }
+    }
+
+    /// Use the given number of data bits.
+    pub fn with_data_bits(self, data_bits: u8) -> Self {
+        Self {
+            data_bits,
+            ..self
+        }
+    }
+
+    /// Use the given number of stop bits.
+    pub fn with_stop_bits(self, stop_bits: u8) -> Self {
+        Self {
+            stop_bits,
+            ..self
+        }
+    }
+
+    /// Use the given flow control.
+    pub fn with_flow_control(self, flow_control: FlowControl) -> Self {
+        Self {
+            flow_control,
+            ..self
+        }
+    }
+
+    /** Wait at most `read_timeout` for data on each read.
+
+    Collectors only notice that a test has ended between reads,
+    so a long timeout delays the end of tests that use the UART.
+     */
+    pub fn with_read_timeout(self, read_timeout: Duration) -> Self {
+        Self {
+            read_timeout: Some(read_timeout),
+            ..self
+        }
+    }
+
+    /// Returns the baud rate.
+    pub fn get_baud_rate(&self) -> u32 {
+        self.baud_rate
+    }
+
+    /// Returns the parity.
+    pub fn get_parity(&self) -> Parity {
+        self.parity
+    }
+
+    /// Returns the number of data bits.
+    pub fn get_data_bits(&self) -> u8 {
+        self.data_bits
+    }
+
+    /// Returns the number of stop bits.
+    pub fn get_stop_bits(&self) -> u8 {
+        self.stop_bits
+    }
+
+    /// Returns the flow control.
+    pub fn get_flow_control(&self) -> FlowControl {
+        self.flow_control
+    }
+
+    /// Returns the read timeout.
+    pub fn get_read_timeout(&self) -> Duration {
+        self.read_timeout.unwrap_or(Duration::from_millis(100))
+    }
+
+    /// Returns true if the read timeout was configured rather than left at the default.
+    pub fn has_read_timeout(&self) -> bool {
+        self.read_timeout.is_some()
+    }
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        SerialConfig {
+            baud_rate: 115_200,
+            parity: Parity::Even,
+            data_bits: 8,
+            stop_bits: 1,
+            flow_control: FlowControl::None,
+            read_timeout: None,
         }
     }
 }
@@ -299,42 +703,149 @@ Creating a mapping with [`Mapping::new`] ensures a valid testbed-device configur
 pub struct Mapping {
     device: Device,
     numbering: HashMap<u8, u8>,
-    reset_pin: Option<u8>,
+    // Acquired on the first power cycle and kept so the switch holds its level.
+    power_switch: Mutex<Option<Box<dyn OutputPin>>>,
+    i2c: Mutex<Option<SharedI2cBus>>,
+    backend: Arc<dyn Backend>,
+    serial_configs: HashMap<UART, SerialConfig>,
+    // Pseudo-terminals handed out, so every opening of a link shares one.
+    ptys: Mutex<HashMap<String, Weak<Pty>>>,
 }
 
 impl Mapping {
     /** Create a new `Mapping`.
 
     Returns and Ok(Mapping) or an error with the reason for the failure.
+    The mapping uses the Raspberry Pi's I/O unless another backend is given with [`Mapping::with_backend`].
+    `peripherals` are the testbed peripherals the mapping must leave pins free for.
+    The device's [`ResetStrategy`] is checked against the mapping as well.
+
+    # Errors
+    If the mapping is not valid, this function returns `IOError::InvalidMapping` with every conflict found.
 
     # Examples
     ```ignore
-    let mapping = Mapping::new(&device, &[(17, 23), (2, 13)]);
+    let mapping = Mapping::new(&device, &[(17, 23), (20, 13)], &[Peripheral::I2C]);
     ```
      */
     pub fn new<'b, T>(device: Device,
                       host_target_map: T,
-                      reset_pin: Option<u8>) -> Result<Mapping>
+                      peripherals: &[Peripheral]) -> Result<Mapping>
     where
         T: IntoIterator<Item = &'b (u8, u8)>,
     {
-        let numbering: HashMap<u8, u8> = host_target_map.into_iter()
+        let pairs: Vec<(u8, u8)> = host_target_map.into_iter()
             .map(|(h_pin, t_pin)| (*h_pin, *t_pin))
             .collect();
-        let reset_pin_v = if let Some(pin) = reset_pin { vec![pin] } else { Vec::new() };
-
-        let used_device_pins = numbering.iter()
-            .map(|(_h, t)| *t)
-            .chain(reset_pin_v.into_iter());
-        device.has_pins(used_device_pins)?;
+        let conflicts = Self::validate(&device, &pairs, peripherals);
+        if !conflicts.is_empty() {
+            return Err(IOError::InvalidMapping(conflicts));
+        }
 
         Ok(Mapping {
             device,
-            numbering,
-            reset_pin,
+            numbering: pairs.into_iter().collect(),
+            power_switch: Mutex::new(None),
+            i2c: Mutex::new(None),
+            backend: Arc::new(RaspberryPi::new()),
+            serial_configs: HashMap::new(),
+            ptys: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Returns every problem with mapping `pairs` of testbed and device pins.
+    fn validate(device: &Device,
+                pairs: &[(u8, u8)],
+                peripherals: &[Peripheral]) -> Vec<MappingConflict>
+    {
+        let mut conflicts = Vec::new();
+
+        let mut host_pins: Vec<u8> = Vec::new();
+        let mut duplicates: Vec<u8> = Vec::new();
+        let mut device_pins: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+        for &(h_pin, t_pin) in pairs {
+            if host_pins.contains(&h_pin) {
+                if !duplicates.contains(&h_pin) {
+                    duplicates.push(h_pin);
+                }
+                continue;
+            }
+            host_pins.push(h_pin);
+            device_pins.entry(t_pin).or_default().push(h_pin);
+        }
+        duplicates.sort_unstable();
+        conflicts.extend(duplicates.into_iter().map(MappingConflict::DuplicateHostPin));
+
+        for (t_pin, h_pins) in &device_pins {
+            if !device.has_pin(*t_pin) {
+                conflicts.push(MappingConflict::UndefinedPin(*t_pin));
+            }
+            if h_pins.len() > 1 {
+                let mut h_pins = h_pins.clone();
+                h_pins.sort_unstable();
+                conflicts.push(MappingConflict::SharedDevicePin(*t_pin, h_pins));
+            }
+        }
+
+        match device.get_reset() {
+            Some(&ResetStrategy::Pin { pin_no: reset_pin, .. }) => {
+                match device.direction_of(reset_pin) {
+                    Ok(Direction::In) => (),
+                    Ok(Direction::Out) => conflicts.push(MappingConflict::ResetDirection(reset_pin)),
+                    Err(_) if !device_pins.contains_key(&reset_pin) =>
+                        conflicts.push(MappingConflict::UndefinedPin(reset_pin)),
+                    Err(_) => (),
+                }
+                if !device_pins.contains_key(&reset_pin) {
+                    conflicts.push(MappingConflict::UnmappedReset(reset_pin));
+                }
+            },
+            Some(&ResetStrategy::PowerCycle { switch_pin, .. }) if host_pins.contains(&switch_pin) =>
+                conflicts.push(MappingConflict::MappedPowerSwitch(switch_pin)),
+            _ => (),
+        }
+
+        let power_switch = match device.get_reset() {
+            Some(&ResetStrategy::PowerCycle { switch_pin, .. }) => Some(switch_pin),
+            _ => None,
+        };
+        for peripheral in peripherals {
+            for pin_no in peripheral.pins() {
+                if host_pins.contains(pin_no) || power_switch == Some(*pin_no) {
+                    conflicts.push(MappingConflict::ReservedPin(*pin_no, peripheral.clone()));
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// Use the given backend to acquire I/O.
+    pub fn with_backend(self, backend: Arc<dyn Backend>) -> Self {
+        Self {
+            backend,
+            ..self
+        }
+    }
+
+    /// Open `uart` with the given line settings instead of the default ones.
+    pub fn with_serial_config(mut self, uart: UART, config: SerialConfig) -> Self {
+        self.serial_configs.insert(uart, config);
+        self
+    }
+
+    /// Returns the line settings `uart` is opened with.
+    pub fn get_serial_config(&self, uart: &UART) -> SerialConfig {
+        self.serial_configs.get(uart)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the backend providing the mapping's I/O.
+    pub fn get_backend(&self) -> Arc<dyn Backend> {
+        Arc::clone(&self.backend)
+    }
+
     /// Returns the device definition.
     pub fn get_device(&self) -> &Device {
         &self.device
@@ -345,9 +856,80 @@ impl Mapping {
         &self.numbering
     }
 
-    /// Returns the reset pin number.
+    /// Returns the device-side number of the pin that resets the device, if the device is reset with one.
     pub fn get_reset_pin(&self) -> Option<u8> {
-        self.reset_pin
+        match self.device.get_reset() {
+            Some(&ResetStrategy::Pin { pin_no, .. }) => Some(pin_no),
+            _ => None,
+        }
+    }
+
+    /** Place the device in reset according to its [`ResetStrategy`].
+
+    This returns once the device has been in reset for the hold time of the strategy.
+    A reset command still running at `deadline` is killed.
+
+    # Errors
+    - If the device does not define a reset strategy, this function returns `IOError::NoReset`.
+    - If a reset command cannot be run or does not exit successfully, this function returns `IOError::ResetCommand`.
+    - If a reset command is killed at `deadline`, this function returns `IOError::ResetTimeout`.
+     */
+    pub fn hold_in_reset(&self, inputs: &mut DeviceInputs, deadline: Instant) -> Result<()> {
+        match self.device.get_reset().ok_or(IOError::NoReset)? {
+            ResetStrategy::Pin { pin_no, active, hold } => {
+                inputs.get_pin_mut(*pin_no)?.write(*active)?;
+                thread::sleep(*hold);
+                Ok(())
+            },
+            ResetStrategy::PowerCycle { switch_pin, on, off } => {
+                self.write_power_switch(*switch_pin, !*on)?;
+                thread::sleep(*off);
+                Ok(())
+            },
+            ResetStrategy::Command { program, args } => {
+                let mut child = process::Command::new(program)
+                    .args(args)
+                    .spawn()
+                    .map_err(|e| IOError::ResetCommand(format!("could not run '{}': {}", program, e)))?;
+                loop {
+                    let status = child.try_wait()
+                        .map_err(|e| IOError::ResetCommand(format!("could not wait for '{}': {}", program, e)))?;
+                    match status {
+                        Some(status) if status.success() => return Ok(()),
+                        Some(status) =>
+                            return Err(IOError::ResetCommand(format!("'{}' exited with {}", program, status))),
+                        None if Instant::now() >= deadline => {
+                            // Reap the killed command so it does not linger.
+                            let _ = child.kill();
+                            let _ = child.wait();
+                            return Err(IOError::ResetTimeout(program.clone()));
+                        },
+                        None => thread::sleep(RESET_COMMAND_POLL.min(deadline.saturating_duration_since(Instant::now()))),
+                    }
+                }
+            },
+            ResetStrategy::Custom { hold, .. } => hold(inputs),
+        }
+    }
+
+    /// Release the device from reset according to its [`ResetStrategy`].
+    pub fn release_from_reset(&self, inputs: &mut DeviceInputs) -> Result<()> {
+        match self.device.get_reset().ok_or(IOError::NoReset)? {
+            ResetStrategy::Pin { pin_no, active, .. } => inputs.get_pin_mut(*pin_no)?.write(!*active),
+            ResetStrategy::PowerCycle { switch_pin, on, .. } => self.write_power_switch(*switch_pin, *on),
+            // The command already reset the device.
+            ResetStrategy::Command { .. } => Ok(()),
+            ResetStrategy::Custom { release, .. } => release(inputs),
+        }
+    }
+
+    fn write_power_switch(&self, switch_pin: u8, level: Level) -> Result<()> {
+        let mut power_switch = self.power_switch.lock()
+            .map_err(|_e| IOError::Backend("power switch pin is poisoned".to_string()))?;
+        if power_switch.is_none() {
+            *power_switch = Some(self.backend.output_pin(switch_pin)?);
+        }
+        power_switch.as_mut().unwrap().write(level)
     }
 
     /** Returns GPIO pins that are inputs _to the device_ (i.e., outputs from the testbed).
@@ -360,11 +942,9 @@ impl Mapping {
             .map(|(h, t)| (*h, *t))
             .filter(|(_h, t)| self.device.direction_of(*t).unwrap() == Direction::In);
         let mut inputs = Vec::new();
-        let gpio = Gpio::new()?;
 
         for (h_pin, t_pin) in input_numbering {
-            let pin = gpio.get(h_pin)?;
-            inputs.push((t_pin, pin.into_output()));
+            inputs.push((t_pin, self.backend.output_pin(h_pin)?));
         }
 
         Ok(DeviceInputs::new(inputs))
@@ -380,11 +960,9 @@ impl Mapping {
             .map(|(h, t)| (*h, *t))
             .filter(|(_h, t)| self.device.direction_of(*t).unwrap() == Direction::Out);
         let mut outputs = Vec::new();
-        let gpio = Gpio::new()?;
 
         for (h_pin, t_pin) in output_numbering {
-            let pin = gpio.get(h_pin)?;
-            outputs.push((t_pin, pin.into_input()));
+            outputs.push((t_pin, self.backend.input_pin(h_pin)?));
         }
 
         Ok(DeviceOutputs::new(outputs))
@@ -392,25 +970,52 @@ impl Mapping {
 
     /** Configures and returns the I2C interface.
 
+    The bus is opened on the first call; later calls return handles to the same bus,
+    so any number of peripherals can share it.
+
     # Errors
     - If the I/O mapping has mapped the pins used for the I2C bus, this function returns `IOError::I2CUnavailable`.
     - If the underlying implementation encounters an error initializing I2C, this function returns `IOError::I2C`.
      */
-    pub fn get_i2c(&self) -> Result<I2c> {
+    pub fn get_i2c(&self) -> Result<SharedI2cBus> {
         let i2c_pins_mapped =
             self.numbering.contains_key(&2)
             || self.numbering.contains_key(&3);
         if i2c_pins_mapped {
-            Err(IOError::I2CUnavailable)
+            return Err(IOError::I2CUnavailable);
+        }
+
+        let mut i2c = self.i2c.lock()
+            .map_err(|_e| IOError::Backend("I2C bus is poisoned".to_string()))?;
+        if i2c.is_none() {
+            *i2c = Some(SharedI2cBus::new(self.backend.i2c()?));
+        }
+        Ok(i2c.as_ref().unwrap().clone())
+    }
+
+    /** Configures and returns SPI bus `bus`, addressing the peripheral on `chip_select`.
+
+    # Errors
+    - If the I/O mapping has mapped any pin the bus or chip select line uses, this function returns `IOError::SPIUnavailable`.
+    - If the underlying implementation encounters an error initializing SPI, this function returns `IOError::SPI`.
+     */
+    pub fn get_spi(&self, bus: u8, chip_select: u8) -> Result<Box<dyn SpiBus>> {
+        let spi_pins_mapped = Peripheral::SPI { bus, chip_select }.pins().iter()
+            .any(|pin_no| self.numbering.contains_key(pin_no));
+        if spi_pins_mapped {
+            Err(IOError::SPIUnavailable(bus, chip_select))
         } else {
-            Ok(I2c::new()?)
+            self.backend.spi(bus, chip_select)
         }
     }
 
     /// Retrieves the UART interface.
     ///
     /// If using the UART built into the Raspberry Pi, `which_uart` must be `UART::PL011` to do pin mapping checking.
-    pub fn get_uart(&self, which_uart: &UART) -> Result<Uart>
+    /// The UART is opened with the settings given to [`Mapping::with_serial_config`], if any.
+    /// Pseudo-terminals and TCP bridges are opened by the testbed itself rather than the backend.
+    /// Opening a pseudo-terminal again while an earlier endpoint on it is in use shares that pseudo-terminal.
+    pub fn get_uart(&self, which_uart: &UART) -> Result<Box<dyn SerialPort>>
     {
         // Must check the pins that this UART uses.
         if *which_uart == UART::PL011
@@ -418,12 +1023,25 @@ impl Mapping {
         {
             Err(IOError::UARTUnavailable)
         } else {
-            // Use hard-coded values here to avoid complexity
-            // in code wanting to use the UART.
             println!("Opening UART: {}", which_uart.path());
-            let mut uart = Uart::with_path(which_uart.path(), 115_200, UARTParity::Even, 8, 1)?;
-            uart.set_hardware_flow_control(false)?;
-            Ok(uart)
+            let config = self.get_serial_config(which_uart);
+            match which_uart {
+                UART::Pty(ref link) => {
+                    let mut ptys = self.ptys.lock()
+                        .map_err(|_e| IOError::Backend("pseudo-terminals are poisoned".to_string()))?;
+                    let endpoint = match ptys.get(link).and_then(|pty| PtyEndpoint::upgrade(pty, &config)) {
+                        Some(endpoint) => endpoint,
+                        None => {
+                            let endpoint = PtyEndpoint::open(link, &config)?;
+                            ptys.insert(link.clone(), endpoint.downgrade());
+                            endpoint
+                        },
+                    };
+                    Ok(Box::new(endpoint))
+                },
+                UART::Tcp(ref address) => Ok(Box::new(TcpEndpoint::connect(address, &config)?)),
+                _ => self.backend.serial(which_uart, &config),
+            }
         }
     }
 }
@@ -5,12 +5,15 @@
 pub mod comm;
 pub mod criteria;
 pub mod evaluation;
+pub mod events;
 pub mod facility;
+pub mod hal;
 pub mod hw;
 pub mod input;
 pub mod io;
 pub mod mem;
 pub mod output;
+pub mod param;
 pub mod parsing_support;
 pub mod sw;
 pub mod test;
@@ -159,6 +159,7 @@ pub fn parse_counter(input: &[u8], time: Instant) -> BitsResult<MemoryTrace> {
 }
 
 #[cfg(test)]
+#[allow(missing_docs)]
 pub mod tests {
     use super::*;
     use super::StreamOperation;
@@ -190,10 +191,10 @@ pub mod tests {
 
         assert_eq!(r.is_ok(), true);
         assert_eq!(r.map(|(_i, c)| c).unwrap(),
-                   CounterId::PCB((0b0001_0000 as u32) << (1)
-                                  | (0b0000_1000 as u32) << (1 + 8)
-                                  | (0b0000_0010 as u32) << (1 + 16)
-                                  | (0b0000_0001 as u32) << (1 + 24)));
+                   CounterId::PCB(0b0001_0000_u32 << (1)
+                                  | 0b0000_1000_u32 << (1 + 8)
+                                  | 0b0000_0010_u32 << (1 + 16)
+                                  | 0b0000_0001_u32 << (1 + 24)));
     }
 
     #[test]
@@ -207,10 +208,10 @@ pub mod tests {
 
         assert_eq!(r.is_ok(), true);
         assert_eq!(r.map(|(_i, c)| c).unwrap(),
-                   CounterId::UpcallQueue((0b0001_0000 as u32) << (1)
-                                          | (0b0000_1000 as u32) << (1 + 8)
-                                          | (0b0000_0010 as u32) << (1 + 16)
-                                          | (0b0000_0001 as u32) << (1 + 24)));
+                   CounterId::UpcallQueue(0b0001_0000_u32 << (1)
+                                          | 0b0000_1000_u32 << (1 + 8)
+                                          | 0b0000_0010_u32 << (1 + 16)
+                                          | 0b0000_0001_u32 << (1 + 24)));
     }
 
     #[test]
@@ -224,10 +225,10 @@ pub mod tests {
 
         assert_eq!(r.is_ok(), true);
         assert_eq!(r.map(|(_i, c)| c).unwrap(),
-                   CounterId::GrantPointerTable((0b0001_0000 as u32) << (1)
-                                                | (0b0000_1000 as u32) << (1 + 8)
-                                                | (0b0000_0010 as u32) << (1 + 16)
-                                                | (0b0000_0001 as u32) << (1 + 24)));
+                   CounterId::GrantPointerTable(0b0001_0000_u32 << (1)
+                                                | 0b0000_1000_u32 << (1 + 8)
+                                                | 0b0000_0010_u32 << (1 + 16)
+                                                | 0b0000_0001_u32 << (1 + 24)));
     }
 
     #[test]
@@ -244,14 +245,14 @@ pub mod tests {
         let r = grant((&input, 0));
         assert_eq!(r.map(|(_i, c)| c).unwrap(),
                    CounterId::Grant(
-                       (0b0001_0000 as u32) << (1)
-                           | (0b0000_1000 as u32) << (1 + 8)
-                           | (0b0000_0010 as u32) << (1 + 16)
-                           | (0b0000_0001 as u32) << (1 + 24),
-                       (0b0001_0000 as u32) << (2)
-                           | (0b0000_1000 as u32) << (2 + 8)
-                           | (0b0000_0010 as u32) << (2 + 16)
-                           | (0b0000_0001 as u32) << (2 + 24)));
+                       0b0001_0000_u32 << (1)
+                           | 0b0000_1000_u32 << (1 + 8)
+                           | 0b0000_0010_u32 << (1 + 16)
+                           | 0b0000_0001_u32 << (1 + 24),
+                       0b0001_0000_u32 << (2)
+                           | 0b0000_1000_u32 << (2 + 8)
+                           | 0b0000_0010_u32 << (2 + 16)
+                           | 0b0000_0001_u32 << (2 + 24)));
     }
 
     #[test]
@@ -265,10 +266,10 @@ pub mod tests {
 
         assert_eq!(r.is_ok(), true);
         assert_eq!(r.map(|(_i, c)| c).unwrap(),
-                   CounterId::CustomGrant((0b0001_0000 as u32) << (1)
-                                          | (0b0000_1000 as u32) << (1 + 8)
-                                          | (0b0000_0010 as u32) << (1 + 16)
-                                          | (0b0000_0001 as u32) << (1 + 24)));
+                   CounterId::CustomGrant(0b0001_0000_u32 << (1)
+                                          | 0b0000_1000_u32 << (1 + 8)
+                                          | 0b0000_0010_u32 << (1 + 16)
+                                          | 0b0000_0001_u32 << (1 + 24)));
     }
 
     #[test]
@@ -278,6 +279,11 @@ pub mod tests {
                      0b0000_0110,
                      0b0000_0000,
                      0b0000_0000,
+                     0b0000_0000,
+
+                     0b0000_0011,
+                     0b0000_0000,
+                     0b0000_0000,
                      0b0000_0000];
         let now = Instant::now();
         let r = streamed_counter((&input, 0), now);
@@ -288,7 +294,8 @@ pub mod tests {
             MemoryTrace {
                 time: now,
                 op: StreamOperation::Set,
-                counter: CounterId::PCB(6)
+                counter: CounterId::PCB(6),
+                value: 3,
             });
     }
 
@@ -303,6 +310,6 @@ pub mod tests {
         let r = streamed_counter((&input, 0), now);
         println!("counter: {:?}", r);
 
-        assert!(r.is_ok());
+        assert!(matches!(r, Err(nom::Err::Incomplete(_))));
     }
 }
@@ -0,0 +1,259 @@
+/*! Parameterized test definitions.
+
+A [`ParameterizedTest`] builds near-identical tests from a template
+instead of repeating a [`Test::new`] call for every app set, trace point set or input timing.
+Every combination of the parameter values in its matrix expands into a concrete [`Test`],
+named by substituting the values into the identifier template,
+and each test keeps its [`Parameters`] so results can be reported and grouped by them.
+
+# Examples
+```ignore
+let blink = ParameterizedTest::new("blink-{apps}-{period}", |params| {
+    let apps = params.get("apps").and_then(ParamValue::as_set).unwrap();
+    let period = params.get("period").and_then(ParamValue::as_duration).unwrap();
+    Test::new(
+        "blink",
+        apps.iter().map(String::as_str),
+        Vec::new(),
+        &[Operation::at(0).square_wave(13, 1.0 / period.as_secs_f64(), 50.0, 10).unwrap()],
+        Vec::new(),
+        true)
+})
+    .with_parameter("apps", [&["blink"][..], &["blink", "radio"][..]])
+    .with_parameter("period", [Duration::from_millis(10), Duration::from_millis(50)]);
+// blink-blink-10ms, blink-blink-50ms, blink-blink+radio-10ms, blink-blink+radio-50ms
+let tests = blink.expand();
+```
+ */
+
+use std::fmt::{self, Debug, Display};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::test::Test;
+
+/// Value a test parameter takes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParamValue {
+    /// Whole number
+    Int(i64),
+    /// Length of time, such as an input timing
+    Duration(Duration),
+    /// Free-form text
+    Text(String),
+    /// Set of names, such as applications or trace points
+    Set(Vec<String>),
+}
+
+impl ParamValue {
+    /// Returns the value if it is a whole number.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            ParamValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a length of time.
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            ParamValue::Duration(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is text.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            ParamValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a set of names.
+    pub fn as_set(&self) -> Option<&[String]> {
+        match self {
+            ParamValue::Set(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamValue::Int(value) => write!(f, "{}", value),
+            ParamValue::Duration(value) => write!(f, "{:?}", value),
+            ParamValue::Text(value) => write!(f, "{}", value),
+            ParamValue::Set(values) => write!(f, "{}", values.join("+")),
+        }
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(value: i64) -> ParamValue {
+        ParamValue::Int(value)
+    }
+}
+
+impl From<u32> for ParamValue {
+    fn from(value: u32) -> ParamValue {
+        ParamValue::Int(value.into())
+    }
+}
+
+impl From<Duration> for ParamValue {
+    fn from(value: Duration) -> ParamValue {
+        ParamValue::Duration(value)
+    }
+}
+
+impl From<&str> for ParamValue {
+    fn from(value: &str) -> ParamValue {
+        ParamValue::Text(value.to_string())
+    }
+}
+
+impl From<&[&str]> for ParamValue {
+    fn from(values: &[&str]) -> ParamValue {
+        ParamValue::Set(values.iter().map(|value| value.to_string()).collect())
+    }
+}
+
+/// Named parameter values a test was built from, in the order the parameters were defined.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Parameters {
+    values: Vec<(String, ParamValue)>,
+}
+
+impl Parameters {
+    /// Create a new set of parameter values.
+    pub fn new<'a, T>(values: T) -> Parameters
+    where
+        T: IntoIterator<Item = (&'a str, ParamValue)>,
+    {
+        Parameters {
+            values: values.into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+
+    /// Returns the value of the named parameter.
+    pub fn get(&self, name: &str) -> Option<&ParamValue> {
+        self.values.iter()
+            .find(|(param, _value)| param == name)
+            .map(|(_param, value)| value)
+    }
+
+    /// Returns the parameters and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ParamValue)> {
+        self.values.iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Returns true if there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Substitute `{name}` in `template` with the value of each parameter.
+    fn fill(&self, template: &str) -> String {
+        self.values.iter()
+            .fold(template.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), &value.to_string())
+            })
+    }
+}
+
+impl Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (name, value)) in self.values.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Function building a test from one combination of parameter values.
+pub type TestTemplate = Arc<dyn Fn(&Parameters) -> Test + Send + Sync>;
+
+/// Test definition expanded over a matrix of parameter values.
+#[derive(Clone)]
+pub struct ParameterizedTest {
+    id: String,
+    matrix: Vec<(String, Vec<ParamValue>)>,
+    template: TestTemplate,
+}
+
+impl ParameterizedTest {
+    /** Define a new parameterized test.
+
+    `id` is a template in which `{name}` stands for the value of parameter `name`.
+    `template` builds the test for one combination of values;
+    the identifier it gives the test is replaced by the filled-in `id`.
+     */
+    pub fn new<F>(id: &str, template: F) -> ParameterizedTest
+    where
+        F: Fn(&Parameters) -> Test + Send + Sync + 'static,
+    {
+        ParameterizedTest {
+            id: id.to_string(),
+            matrix: Vec::new(),
+            template: Arc::new(template),
+        }
+    }
+
+    /// Add a parameter taking each of `values` in turn.
+    pub fn with_parameter<T, V>(mut self, name: &str, values: T) -> Self
+    where
+        T: IntoIterator<Item = V>,
+        V: Into<ParamValue>,
+    {
+        self.matrix.push((name.to_string(), values.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Returns the identifier template.
+    pub fn get_id_template(&self) -> &str {
+        &self.id
+    }
+
+    /** Build a test for every combination of parameter values.
+
+    Tests are ordered with the first parameter varying slowest.
+    A parameter without values produces no tests.
+     */
+    pub fn expand(&self) -> Vec<Test> {
+        let mut combinations: Vec<Vec<(&str, ParamValue)>> = vec![Vec::new()];
+        for (name, values) in &self.matrix {
+            combinations = combinations.into_iter()
+                .flat_map(|combination| values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push((name.as_str(), value.clone()));
+                    combination
+                }))
+                .collect();
+        }
+
+        combinations.into_iter()
+            .map(|combination| {
+                let parameters = Parameters::new(combination);
+                (self.template)(&parameters)
+                    .with_parameters(&parameters.fill(&self.id), parameters)
+            })
+            .collect()
+    }
+}
+
+impl Debug for ParameterizedTest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParameterizedTest")
+            .field("id", &self.id)
+            .field("matrix", &self.matrix)
+            .finish_non_exhaustive()
+    }
+}
@@ -6,54 +6,62 @@ use std::error;
 use std::fmt;
 use std::fmt::Display;
 use std::iter::IntoIterator;
+use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
-use rppal::gpio::{
-    self,
-    Gpio,
-    InputPin,
-    Level,
-    Trigger,
-};
-use rppal::uart::{self, Uart};
-
 use crate::comm::Signal;
-use crate::criteria::{
-    Criterion,
-    GPIOCriterion,
-};
-use crate::facility::EnergyMetering;
-use crate::io::{DeviceInputs, DeviceOutputs, IOError};
+use crate::criteria::Criterion;
+use crate::events::{DeviceEvents, WaitCondition};
+use crate::facility::{AnalogInput, EnergyMetering};
+use crate::hal::{Backend, InputPin, Level, OutputPin, SerialPort, Trigger};
+use crate::io::{DeviceAnalogInputs, DeviceInputs, DeviceOutputs, IOError, UART};
 use crate::mem::MemoryTrace;
+use crate::param::Parameters;
+use crate::testbed::DEFAULT_DEVICE;
 
 type Result<T> = std::result::Result<T, TestingError>;
 
+/// Time a test keeps running after its last operation or criterion window, unless it sets its own tail.
+pub const DEFAULT_TAIL: Duration = Duration::from_millis(5);
+
+/// Time a test may take beyond its [`Test::max_runtime`] before the testbed gives up on it, unless it sets its own timeout.
+pub const DEFAULT_TIMEOUT_MARGIN: Duration = Duration::from_secs(10);
+
 /// Testing error.
 #[derive(Debug)]
 pub enum TestingError {
-    /// GPIO-related error.
-    GPIO(gpio::Error),
+    /// Analog output failed to drive a pin.
+    Analog(u8, String),
+    /// Analog input failed to sample a pin.
+    Sampling(u8, String),
     /// Testbed to device I/O error.
     IO(IOError),
     /// Energy meter does not exist.
     NoSuchMeter(String),
+    /// No analog input is wired to the device pin.
+    NoSuchAnalogInput(u8),
+    /// Device under test does not exist.
+    NoSuchDevice(String),
+    /// Serial port was not opened for the test.
+    NoSuchSerialPort(UART),
     /// Invalid test protocol data received.
     Protocol,
     /// Reset requested when [`io::Mapping`] does not specify one.
     Reset(IOError),
-    /// Error configuring UART hardware.
-    UART(uart::Error),
+    /// Device event a wait step waited for did not happen in time.
+    WaitTimeout(WaitCondition),
+    /// The test ran into the deadline it was given.
+    Deadline,
 }
 
 impl error::Error for TestingError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         use TestingError::*;
         match self {
-            GPIO(ref e) => Some(e),
             IO(ref e) => Some(e),
             Protocol => None,
             Reset(ref e) => Some(e),
-            UART(ref e) => Some(e),
             _ => None,
         }
     }
@@ -65,39 +73,174 @@ impl From<IOError> for TestingError {
     }
 }
 
-impl From<gpio::Error> for TestingError {
-    fn from(e: gpio::Error) -> Self {
-        TestingError::GPIO(e)
-    }
-}
-
-impl From<uart::Error> for TestingError {
-    fn from(e: uart::Error) -> Self {
-        TestingError::UART(e)
-    }
-}
-
 impl Display for TestingError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use TestingError::*;
         match self {
-            GPIO(ref e) => write!(f, "GPIO error while testing: {}", e),
+            Analog(pin_no, ref msg) => write!(f, "failed to drive analog pin {}: {}", pin_no, msg),
+            Sampling(pin_no, ref msg) => write!(f, "failed to sample analog pin {}: {}", pin_no, msg),
             IO(ref e) => write!(f, "I/O error: {}", e),
             NoSuchMeter(ref id) => write!(f, "the meter '{}' does not exist", id),
+            NoSuchAnalogInput(pin_no) => write!(f, "no analog input is wired to device pin {}", pin_no),
+            NoSuchDevice(ref name) => write!(f, "the device '{}' does not exist", name),
+            NoSuchSerialPort(ref uart) => write!(f, "the serial port '{}' is not open", uart.path()),
             Protocol => write!(f, "testbed/DUT test protocol mismatch"),
             Reset(ref e) => write!(f, "failed to reset device: {}", e),
-            UART(ref e) => write!(f, "UART configuration error: {}", e),
+            WaitTimeout(ref condition) => write!(f, "timed out waiting for {}", condition),
+            Deadline => write!(f, "test ran past its deadline"),
         }
     }
 }
 
 /// An action that occurs as part of an operation.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Action {
     /// No-op
     Idle(Duration),
     /// Apply an input signal to a particular pin.
     Input(Signal, u8),
+    /// Drive a pin to a level for a while, then to the opposite level.
+    Pulse {
+        /// Device pin
+        pin: u8,
+        /// Level during the pulse
+        level: Level,
+        /// Length of the pulse
+        width: Duration,
+    },
+    /// Drive a pin to the opposite of its current level.
+    Toggle(u8),
+    /// Drive a pin high at the start of every period and low once `high` has passed.
+    SquareWave {
+        /// Device pin
+        pin: u8,
+        /// Length of one cycle
+        period: Duration,
+        /// Length of the high part of a cycle
+        high: Duration,
+        /// Number of cycles
+        count: u32,
+    },
+    /// Drive a pin through a sequence of levels (true for high), one per bit period.
+    Bits {
+        /// Device pin
+        pin: u8,
+        /// Levels to drive, in order
+        bits: Vec<bool>,
+        /// Length of each bit
+        bit_period: Duration,
+    },
+    /// Write bytes to the device over a serial port.
+    SerialWrite {
+        /// Serial port to write to
+        uart: UART,
+        /// Data to write
+        bytes: Vec<u8>,
+    },
+    /// Emulate a bouncing contact: alternate between `level` and its opposite every `interval`, then settle at `level`.
+    Bounce {
+        /// Device pin
+        pin: u8,
+        /// Level the contact settles at
+        level: Level,
+        /// Number of times the contact bounces back before settling
+        bounces: u32,
+        /// Time between level changes while bouncing
+        interval: Duration,
+    },
+    /// Wait until the device does something, then carry on with the rest of the timeline from then.
+    WaitFor {
+        /// Device event to wait for
+        condition: WaitCondition,
+        /// Longest time to wait before failing the test
+        timeout: Duration,
+    },
+}
+
+/// Single change of a device input that actions expand into.
+#[derive(Copy, Clone, Debug)]
+enum Change<'a> {
+    Idle(Duration),
+    Drive(u8, Level),
+    Toggle(u8),
+    Analog(u8, u32),
+    Serial(&'a UART, &'a [u8]),
+    Wait(&'a WaitCondition, Duration),
+}
+
+impl Action {
+    /// Returns how long the action takes to perform.
+    ///
+    /// Waits take no time of their own; they push the rest of the timeline back instead.
+    pub fn duration(&self) -> Duration {
+        use Action::*;
+        match self {
+            Idle(d) => *d,
+            Input(..) | Toggle(_) | SerialWrite { .. } | WaitFor { .. } => Duration::from_secs(0),
+            Pulse { width, .. } => *width,
+            SquareWave { period, count, .. } => *period * *count,
+            Bits { bits, bit_period, .. } => *bit_period * bits.len() as u32,
+            Bounce { bounces, interval, .. } => *interval * (2 * *bounces),
+        }
+    }
+
+    /// Returns the device pin the action drives, if any.
+    pub fn get_pin(&self) -> Option<u8> {
+        use Action::*;
+        match *self {
+            Input(_, pin) | Toggle(pin) => Some(pin),
+            Pulse { pin, .. } | SquareWave { pin, .. } | Bits { pin, .. } | Bounce { pin, .. } => Some(pin),
+            Idle(_) | SerialWrite { .. } | WaitFor { .. } => None,
+        }
+    }
+
+    /// Returns the changes the action consists of and when each happens relative to the start of the action.
+    fn expand(&self) -> Vec<(Duration, Change<'_>)> {
+        use Action::*;
+        let start = Duration::from_secs(0);
+        match *self {
+            Idle(d) => vec![(start, Change::Idle(d))],
+            Input(Signal::Digital(level), pin) => vec![(start, Change::Drive(pin, level.into()))],
+            Input(Signal::Analog(millivolts), pin) => vec![(start, Change::Analog(pin, millivolts))],
+            Pulse { pin, level, width } => vec![
+                (start, Change::Drive(pin, level)),
+                (width, Change::Drive(pin, !level)),
+            ],
+            Toggle(pin) => vec![(start, Change::Toggle(pin))],
+            SerialWrite { ref uart, ref bytes } => vec![(start, Change::Serial(uart, bytes))],
+            WaitFor { ref condition, timeout } => vec![(start, Change::Wait(condition, timeout))],
+            // A wave that never goes low or never goes high is a steady level, without glitches between cycles.
+            SquareWave { pin, period, high, count } if high >= period => vec![
+                (start, Change::Drive(pin, Level::High)),
+                (period * count, Change::Drive(pin, Level::Low)),
+            ],
+            SquareWave { pin, high, .. } if high.is_zero() => vec![(start, Change::Drive(pin, Level::Low))],
+            SquareWave { pin, period, high, count } => (0..count)
+                .flat_map(|cycle| vec![
+                    (period * cycle, Change::Drive(pin, Level::High)),
+                    (period * cycle + high, Change::Drive(pin, Level::Low)),
+                ])
+                .collect(),
+            Bits { pin, ref bits, bit_period } => {
+                // Only level changes need to be driven.
+                let mut changes = Vec::new();
+                let mut previous = None;
+                for (idx, bit) in bits.iter().enumerate() {
+                    if previous != Some(*bit) {
+                        changes.push((bit_period * idx as u32, Change::Drive(pin, (*bit).into())));
+                    }
+                    previous = Some(*bit);
+                }
+                changes
+            },
+            Bounce { pin, level, bounces, interval } => (0..=2 * bounces)
+                .map(|idx| {
+                    let level = if idx % 2 == 0 { level } else { !level };
+                    (interval * idx, Change::Drive(pin, level))
+                })
+                .collect(),
+        }
+    }
 }
 
 impl Display for Action {
@@ -106,20 +249,47 @@ impl Display for Action {
         match self {
             Idle(d) => write!(f, "idle for {:?}", d),
             Input(signal, pin) => write!(f, "input {}, pin {}", signal, pin),
+            Pulse { pin, level, width } => write!(f, "{:?} pulse for {:?}, pin {}", level, width, pin),
+            Toggle(pin) => write!(f, "toggle, pin {}", pin),
+            SerialWrite { uart, bytes } => write!(f, "write \"{}\" to {}", bytes.escape_ascii(), uart.path()),
+            SquareWave { pin, period, high, count } => write!(
+                f, "{} cycles at {:.1}Hz ({:.0}% duty), pin {}",
+                count,
+                1.0 / period.as_secs_f64(),
+                100.0 * high.as_secs_f64() / period.as_secs_f64(),
+                pin),
+            Bits { pin, bits, bit_period } => {
+                let bits: String = bits.iter().map(|bit| if *bit { '1' } else { '0' }).collect();
+                write!(f, "bits {} at {:?} per bit, pin {}", bits, bit_period, pin)
+            },
+            Bounce { pin, level, bounces, interval } =>
+                write!(f, "bounce {} times every {:?} to {:?}, pin {}", bounces, interval, level, pin),
+            WaitFor { condition, timeout } => write!(f, "wait up to {:?} for {}", timeout, condition),
         }
     }
 }
 
 /// An input to perform at a specific time.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Operation {
-    time: u64,
+    time: Duration,
     action: Option<Action>,
+    device: Option<String>,
 }
 
 impl Operation {
-    /// Specify the time for the Operation to occur.
+    /// Specify the time for the Operation to occur, in milliseconds.
     pub fn at(time: u64) -> Operation {
+        Operation::at_time(Duration::from_millis(time))
+    }
+
+    /// Specify the time for the Operation to occur, in microseconds.
+    pub fn at_micros(time: u64) -> Operation {
+        Operation::at_time(Duration::from_micros(time))
+    }
+
+    /// Specify the time into the test for the Operation to occur.
+    pub fn at_time(time: Duration) -> Operation {
         Operation {
             time,
             // Create with no action initially.
@@ -127,9 +297,33 @@ impl Operation {
             // since there is a function to create idle time.
             // Empty Operations should be ignored by testing.
             action: None,
+            device: None,
+        }
+    }
+
+    /// Perform the operation on the named device under test instead of the default one.
+    pub fn on(self, device: &str) -> Self {
+        Self {
+            device: Some(device.to_string()),
+            ..self
         }
     }
 
+    /// Returns the time into the test the operation occurs at.
+    pub fn get_time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns the action the operation performs, if any.
+    pub fn get_action(&self) -> Option<&Action> {
+        self.action.as_ref()
+    }
+
+    /// Returns the name of the device under test the operation is performed on.
+    pub fn get_device(&self) -> &str {
+        self.device.as_deref().unwrap_or(DEFAULT_DEVICE)
+    }
+
     /// Create an input.
     pub fn input(self, signal: Signal, pin: u8) -> Self {
         Self {
@@ -145,17 +339,104 @@ impl Operation {
             ..self
         }
     }
+
+    /// Create a pulse of `width` at `level`, after which the pin is driven to the opposite level.
+    pub fn pulse(self, pin: u8, level: Level, width: Duration) -> Self {
+        Self {
+            action: Some(Action::Pulse { pin, level, width }),
+            ..self
+        }
+    }
+
+    /// Drive the pin to the opposite of its current level.
+    pub fn toggle(self, pin: u8) -> Self {
+        Self {
+            action: Some(Action::Toggle(pin)),
+            ..self
+        }
+    }
+
+    /** Create `count` cycles of a square wave at `frequency` hertz.
+
+    `frequency` must be positive and finite, with a period of at least a nanosecond.
+    `duty` is the percentage of each cycle the pin is high, from 0 to 100 as for
+    [`Waveform::with_duty_cycle`](crate::criteria::Waveform::with_duty_cycle).
+    Each cycle starts with the rising edge, and the pin is left low.
+     */
+    pub fn square_wave(self, pin: u8, frequency: f64, duty: f64, count: u32) -> std::result::Result<Self, String> {
+        let period = Duration::try_from_secs_f64(1.0 / frequency).ok()
+            .filter(|period| !period.is_zero())
+            .ok_or_else(|| format!("square wave frequency must be positive and finite, got {} Hz", frequency))?;
+        if !(0.0..=100.0).contains(&duty) {
+            return Err(format!("square wave duty cycle must be between 0 and 100%, got {}%", duty));
+        }
+        Ok(Self {
+            action: Some(Action::SquareWave {
+                pin,
+                period,
+                high: period.mul_f64(duty / 100.0),
+                count,
+            }),
+            ..self
+        })
+    }
+
+    /// Drive the pin through `bits` (true for high), holding each for `bit_period`.
+    pub fn bits(self, pin: u8, bits: &[bool], bit_period: Duration) -> Self {
+        Self {
+            action: Some(Action::Bits { pin, bits: bits.to_vec(), bit_period }),
+            ..self
+        }
+    }
+
+    /// Write `bytes` to `uart` of the device.
+    pub fn serial_write(self, uart: UART, bytes: &[u8]) -> Self {
+        Self {
+            action: Some(Action::SerialWrite { uart, bytes: bytes.to_vec() }),
+            ..self
+        }
+    }
+
+    /** Emulate a contact that bounces `bounces` times before settling at `level`.
+
+    The pin is driven to `level` and then alternates every `interval`,
+    ending at `level` after `2 * bounces` changes.
+     */
+    pub fn bounce(self, pin: u8, level: Level, bounces: u32, interval: Duration) -> Self {
+        Self {
+            action: Some(Action::Bounce { pin, level, bounces, interval }),
+            ..self
+        }
+    }
+
+    /** Wait up to `timeout` for the device to meet `condition`.
+
+    Everything after the wait in the timeline is delayed by however long the device took,
+    so later operations happen relative to when the condition was met.
+    The test fails if the condition is not met in time.
+     */
+    pub fn wait_for(self, condition: WaitCondition, timeout: Duration) -> Self {
+        Self {
+            action: Some(Action::WaitFor { condition, timeout }),
+            ..self
+        }
+    }
 }
 
 impl Display for Operation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let action_text = if let Some(action) = self.action {
+        let action_text = if let Some(ref action) = self.action {
             format!("{}", action)
         } else {
             "None".to_string()
         };
 
-        write!(f, "@{}ms\taction: {}", self.time, action_text)
+        write!(f, "@{:?}\taction: {}", self.time, action_text)?;
+        if let Some(ref device) = self.device {
+            write!(f, " on '{}'", device)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -211,6 +492,14 @@ impl Response {
         self.pin_no
     }
 
+    /// Returns the level the pin changed to, if the response is digital.
+    pub fn get_level(&self) -> Option<Level> {
+        match self.output {
+            Signal::Digital(high) => Some(high.into()),
+            Signal::Analog(_) => None,
+        }
+    }
+
     /// Returns the output signal of the response.
     pub fn get_output(&self) -> Signal {
         self.output
@@ -234,20 +523,99 @@ impl Display for Response {
     }
 }
 
+/** Operation as it was performed during a test run.
+
+Operations whose action expands into several pin changes are recorded once per change.
+ */
+#[derive(Clone, Debug)]
+pub struct AppliedOperation {
+    operation: Operation,
+    scheduled_at: Instant,
+    applied_at: Instant,
+    level: Option<Level>,
+    condition_met: Option<Instant>,
+}
+
+impl AppliedOperation {
+    /// Returns the operation that was performed.
+    pub fn get_operation(&self) -> &Operation {
+        &self.operation
+    }
+
+    /// Returns the Instant the operation was scheduled for.
+    pub fn get_scheduled(&self) -> Instant {
+        self.scheduled_at
+    }
+
+    /** Returns the Instant the operation took effect.
+
+    Inputs take effect once the pin or analog output has been driven;
+    serial writes and idle periods take effect when they start.
+     */
+    pub fn get_applied(&self) -> Instant {
+        self.applied_at
+    }
+
+    /** Returns the level the operation drove its pin to.
+
+    Only digital inputs and toggles drive a pin to a level; other operations return `None`.
+     */
+    pub fn get_level(&self) -> Option<Level> {
+        self.level
+    }
+
+    /// Returns when the condition a wait step waited for was met.
+    pub fn get_condition_met(&self) -> Option<Instant> {
+        self.condition_met
+    }
+
+    /// Returns how long after its scheduled time the operation took effect.
+    pub fn lateness(&self) -> Duration {
+        self.applied_at.saturating_duration_since(self.scheduled_at)
+    }
+}
+
 /// Test execution information
 #[derive(Clone, Debug)]
 pub struct Execution {
     started_at: Instant,
     finished_at: Instant,
+    applied: Vec<AppliedOperation>,
 }
 
 impl Execution {
     /// Create a new Execution.
-    fn new(started_at: Instant, finished_at: Instant) -> Execution {
+    fn new(started_at: Instant, finished_at: Instant, applied: Vec<AppliedOperation>) -> Execution {
         Execution {
             started_at,
             finished_at,
+            applied,
+        }
+    }
+
+    /// Returns the operations performed during the test, in the order they were performed.
+    pub fn get_applied_operations(&self) -> &[AppliedOperation] {
+        &self.applied
+    }
+
+    /// Returns the longest any operation took effect after its scheduled time.
+    pub fn max_lateness(&self) -> Duration {
+        self.applied.iter()
+            .map(|applied| applied.lateness())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the average time operations took effect after their scheduled time.
+    pub fn mean_lateness(&self) -> Duration {
+        if self.applied.is_empty() {
+            return Duration::from_secs(0);
         }
+
+        let total: Duration = self.applied.iter()
+            .map(|applied| applied.lateness())
+            .sum();
+        total / self.applied.len() as u32
     }
 
     /// Return the point in time the test execution started.
@@ -261,6 +629,34 @@ impl Execution {
     }
 }
 
+/** Block until `t`.
+
+Sleeps until shortly before `t` and spins for the rest,
+so operations are applied within microseconds of their scheduled time without
+spinning through long gaps in the timeline.
+ */
+fn wait_until(t: Instant) {
+    // Longest the scheduler is expected to oversleep by.
+    const SPIN_MARGIN: Duration = Duration::from_millis(1);
+
+    let now = Instant::now();
+    if t > now + SPIN_MARGIN {
+        thread::sleep(t - now - SPIN_MARGIN);
+    }
+    while Instant::now() < t { std::hint::spin_loop(); }
+}
+
+/// Returns the input `pin_no` of the device the operation is performed on.
+fn device_pin<'a>(pins: &'a mut HashMap<String, DeviceInputs>,
+                  op: &Operation,
+                  pin_no: u8) -> Result<&'a mut Box<dyn OutputPin>>
+{
+    let pin = pins.get_mut(op.get_device())
+        .ok_or_else(|| TestingError::NoSuchDevice(op.get_device().to_string()))?
+        .get_pin_mut(pin_no)?;
+    Ok(pin)
+}
+
 /** Test definition.
 
 A test mainly consists of a timeline of [`Operation`]s to perform (inputs to the device under test)
@@ -273,15 +669,23 @@ Executing a test (via [`Test::execute`]) produces an [`Execution`] that contains
 pub struct Test {
     id: String,
     app_ids: HashSet<String>,
+    device_app_ids: HashMap<String, HashSet<String>>,
     trace_points: HashSet<String>,
     actions: BinaryHeap<Reverse<Operation>>,
     criteria: Vec<Criterion>,
-    tail_duration: Option<Duration>,
+    tail: Duration,
+    runtime: Option<Duration>,
     reset_device: bool,
+    repetitions: u32,
+    parameters: Parameters,
+    tags: HashSet<String>,
+    timeout: Option<Duration>,
 }
 
 impl Test {
     /// Define a new test.
+    ///
+    /// `app_id` names the applications to load onto the default device under test.
     pub fn new<'a, T, U, V, W>(id: &str,
                                app_id: T,
                                trace_points: U,
@@ -297,14 +701,87 @@ impl Test {
         Test {
             id: id.to_string(),
             app_ids: app_id.into_iter().map(|id| id.to_string()).collect(),
+            device_app_ids: HashMap::new(),
             trace_points: trace_points.into_iter().map(|tp| tp.to_string()).collect(),
-            actions: ops.into_iter().map(|x| Reverse(*x)).collect(),
+            actions: ops.into_iter().map(|x| Reverse(x.clone())).collect(),
             criteria: criteria.into_iter().cloned().collect(),
-            tail_duration: Some(Duration::from_millis(5)),
+            tail: DEFAULT_TAIL,
+            runtime: None,
             reset_device,
+            repetitions: 1,
+            parameters: Parameters::default(),
+            tags: HashSet::new(),
+            timeout: None,
         }
     }
 
+    /// Keep the test running for `tail` after its last operation or criterion window.
+    pub fn with_tail(mut self, tail: Duration) -> Self {
+        self.tail = tail;
+        self
+    }
+
+    /** Run the test for exactly `runtime`, instead of deriving it from the operations and criteria.
+
+    Responses are only collected during the runtime.
+     */
+    pub fn with_runtime(mut self, runtime: Duration) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /** Give up on the test if a run takes longer than `timeout` of wall-clock time.
+
+    The limit covers a run from when the testbed starts preparing for it until its data is collected,
+    including resetting the device.
+     */
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Tag the test, for selecting groups of tests to run.
+    pub fn with_tags<'a, T>(mut self, tags: T) -> Self
+    where
+        T: IntoIterator<Item = &'a str>,
+    {
+        self.tags.extend(tags.into_iter().map(|tag| tag.to_string()));
+        self
+    }
+
+    /** Run the test `count` times in a row.
+
+    Every run produces its own [`Observation`](crate::testbed::Observation);
+    [`evaluation::aggregate`](crate::evaluation::aggregate) summarizes them.
+    A count of 0 is treated as 1.
+     */
+    pub fn with_repetitions(mut self, count: u32) -> Self {
+        self.repetitions = count.max(1);
+        self
+    }
+
+    /// Load applications onto the named device under test for the test.
+    pub fn with_device_apps<'a, T>(mut self, device: &str, app_ids: T) -> Self
+    where
+        T: IntoIterator<Item = &'a str>,
+    {
+        if device == DEFAULT_DEVICE {
+            self.app_ids = app_ids.into_iter().map(|id| id.to_string()).collect();
+        } else {
+            self.device_app_ids.insert(
+                device.to_string(),
+                app_ids.into_iter().map(|id| id.to_string()).collect());
+        }
+        self
+    }
+
+    /// Name the test after the parameter values it was built from.
+    pub(crate) fn with_parameters(mut self, id: &str, parameters: Parameters) -> Self {
+        self.id = id.to_string();
+        self.parameters = parameters;
+        self
+    }
+
     /// Returns the identifier of the test definition.
     pub fn get_id(&self) -> &str {
         &self.id
@@ -315,6 +792,31 @@ impl Test {
         &self.app_ids
     }
 
+    /// Returns the identifiers of the applications the test exercises on the named device under test.
+    pub fn get_device_app_ids(&self, device: &str) -> Option<&HashSet<String>> {
+        if device == DEFAULT_DEVICE {
+            Some(&self.app_ids)
+        } else {
+            self.device_app_ids.get(device)
+        }
+    }
+
+    /// Returns the identifiers of the applications the test loads onto any device under test.
+    pub fn get_all_app_ids(&self) -> impl Iterator<Item = &String> {
+        self.app_ids.iter()
+            .chain(self.device_app_ids.values().flatten())
+    }
+
+    /// Returns the tags of the test.
+    pub fn get_tags(&self) -> &HashSet<String> {
+        &self.tags
+    }
+
+    /// Returns the parameter values the test was built from, if it is one of a [`ParameterizedTest`](crate::param::ParameterizedTest)'s.
+    pub fn get_parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+
     /// Returns the trace points the test requires.
     pub fn get_trace_points(&self) -> &HashSet<String> {
         &self.trace_points
@@ -325,46 +827,177 @@ impl Test {
         &self.criteria
     }
 
+    /// Returns the operations on the test's timeline, in no particular order.
+    pub fn get_operations(&self) -> impl Iterator<Item = &Operation> {
+        self.actions.iter()
+            .map(|Reverse(op)| op)
+    }
+
+    /// Returns how long a run of the test may take before the testbed gives up on it.
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+            .unwrap_or_else(|| self.max_runtime() + DEFAULT_TIMEOUT_MARGIN)
+    }
+
+    /// Returns how many times the test runs.
+    pub fn get_repetitions(&self) -> u32 {
+        self.repetitions
+    }
+
     /// Returns true if the device under test should reset for the test.
     pub fn get_reset_on_start(&self) -> bool {
         self.reset_device
     }
 
-    /// Drive test outputs (inputs to the device).
-    pub fn execute(&self, t0: Instant, pins: &mut DeviceInputs) -> Result<Execution> {
-        let timeline = self.actions.iter()
-            .map(|Reverse(op)| (t0 + Duration::from_millis(op.time), op));
-        for (t, op) in timeline {
-            while Instant::now() < t {  } // spin wait?
-
-            if let Some(action) = op.action {
-                match action {
-                    Action::Idle(wait_length) => {
-                        let t_until = t + wait_length;
-                        while Instant::now() < t_until {  } // spin wait?
-                    },
-
-                    Action::Input(signal, pin_no) => match signal {
-                        Signal::Digital(true) => (*pins.get_pin_mut(pin_no)?).set_high(),
-                        Signal::Digital(false) => (*pins.get_pin_mut(pin_no)?).set_low(),
-                        input => panic!("Unhandled input type: {:?}", input),
-                    },
-                };
+    /// Returns the serial ports the test writes to on the named device under test.
+    pub fn get_serial_outputs(&self, device: &str) -> HashSet<UART> {
+        self.actions.iter()
+            .filter(|Reverse(op)| op.get_device() == device)
+            .filter_map(|Reverse(op)| match op.action {
+                Some(Action::SerialWrite { ref uart, .. }) => Some(uart.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Drive test outputs (inputs to the devices).
+    ///
+    /// `pins` holds the inputs of each device under test by name,
+    /// `serial_ports` the ports opened for [`Test::get_serial_outputs`] of each device,
+    /// and `events` what each device has done so far, for wait steps.
+    /// Analog inputs belong to the default device.
+    /// Changes due after `deadline`, and waits, idles and serial writes still busy at it,
+    /// fail with [`TestingError::Deadline`].
+    pub fn execute(&self,
+                   t0: Instant,
+                   deadline: Instant,
+                   pins: &mut HashMap<String, DeviceInputs>,
+                   analog_pins: &mut DeviceAnalogInputs,
+                   serial_ports: &mut HashMap<String, HashMap<UART, Box<dyn SerialPort>>>,
+                   events: &HashMap<String, Arc<DeviceEvents>>) -> Result<Execution>
+    {
+        // Expand every action into the pin changes it consists of, merged into one timeline.
+        // Heap iteration order is arbitrary, so walk the operations sorted from the earliest.
+        let operations = self.actions.clone().into_sorted_vec();
+        let mut timeline = Vec::new();
+        for Reverse(op) in operations.iter().rev() {
+            if let Some(ref action) = op.action {
+                for (offset, change) in action.expand() {
+                    timeline.push((t0 + op.time + offset, op, change));
+                }
             }
         }
+        // Stable, so changes at the same time keep the order of their operations, and of the changes within one.
+        timeline.sort_by_key(|(t, _op, _change)| *t);
+
+        let mut applied = Vec::new();
+        // How much later than planned wait steps have pushed the rest of the timeline.
+        let mut delay = Duration::from_secs(0);
+        for (t, op, change) in timeline {
+            let t = t + delay;
+            if t > deadline {
+                return Err(TestingError::Deadline);
+            }
+            wait_until(t);
+
+            let (applied_at, level) = match change {
+                Change::Idle(wait_length) => {
+                    applied.push(AppliedOperation {
+                        operation: op.clone(),
+                        scheduled_at: t,
+                        applied_at: Instant::now(),
+                        level: None,
+                        condition_met: None,
+                    });
+                    if t + wait_length > deadline {
+                        wait_until(deadline);
+                        return Err(TestingError::Deadline);
+                    }
+                    wait_until(t + wait_length);
+                    continue;
+                },
+                Change::Wait(condition, timeout) => {
+                    let started_at = Instant::now();
+                    let met = events.get(op.get_device())
+                        .ok_or_else(|| TestingError::NoSuchDevice(op.get_device().to_string()))?
+                        .wait_for(condition, (t + timeout).min(deadline))
+                        .ok_or_else(|| if t + timeout > deadline {
+                            TestingError::Deadline
+                        } else {
+                            TestingError::WaitTimeout(condition.clone())
+                        })?;
+                    // A condition met before the wait started releases it right away.
+                    let met = met.max(t);
+                    delay += met - t;
+                    applied.push(AppliedOperation {
+                        operation: op.clone(),
+                        scheduled_at: t,
+                        applied_at: started_at,
+                        level: None,
+                        condition_met: Some(met),
+                    });
+                    continue;
+                },
+                Change::Drive(pin_no, level) => {
+                    device_pin(pins, op, pin_no)?.write(level)?;
+                    (Instant::now(), Some(level))
+                },
+                Change::Toggle(pin_no) => {
+                    let pin = device_pin(pins, op, pin_no)?;
+                    let level = pin.level()?;
+                    pin.write(!level)?;
+                    (Instant::now(), Some(!level))
+                },
+                Change::Analog(pin_no, _) if op.get_device() != DEFAULT_DEVICE =>
+                    return Err(TestingError::NoSuchAnalogInput(pin_no)),
+                Change::Analog(pin_no, millivolts) => {
+                    analog_pins.get_pin_mut(pin_no)?
+                        .set_voltage(millivolts)
+                        .map_err(|e| TestingError::Analog(pin_no, e))?;
+                    (Instant::now(), None)
+                },
+                Change::Serial(uart, bytes) => {
+                    let port = serial_ports.get_mut(op.get_device())
+                        .and_then(|ports| ports.get_mut(uart))
+                        .ok_or_else(|| TestingError::NoSuchSerialPort(uart.clone()))?;
+                    // Transmission starts with the first write.
+                    let sent_at = Instant::now();
+                    let mut written = 0;
+                    while written < bytes.len() {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero() {
+                            return Err(TestingError::Deadline);
+                        }
+                        port.set_write_timeout(remaining)?;
+                        // A port with no room returns without writing; try again until the deadline.
+                        written += port.write(&bytes[written..])?;
+                    }
+                    (sent_at, None)
+                },
+            };
+            applied.push(AppliedOperation {
+                operation: op.clone(),
+                scheduled_at: t,
+                applied_at,
+                level,
+                condition_met: None,
+            });
+        }
 
-        Ok(Execution::new(t0, Instant::now()))
+        Ok(Execution::new(t0, Instant::now(), applied))
     }
 
-    /// Set up to record test inputs.
+    /// Set up to record test inputs from the named device under test.
     pub fn prep_observe(&self,
+                        device: &str,
                         pins: &mut DeviceOutputs) -> Result<Vec<u8>>
     {
         let mut interrupt_pins: Vec<u8> = Vec::new();
 
         let gpio_criteria = self.criteria.iter()
+            .filter(|criterion| criterion.get_device() == device)
             .filter_map(|criterion| {
-                if let Criterion::GPIO(gpio_crit) = criterion {
+                if let Criterion::GPIO(gpio_crit) = criterion.get_target() {
                     Some(gpio_crit)
                 } else {
                     None
@@ -372,13 +1005,29 @@ impl Test {
             });
         for criterion in gpio_criteria {
             println!("observer: watching for {}", criterion);
-            match criterion {
-                GPIOCriterion::Any(pin_no) => {
-                    pins.get_pin_mut(*pin_no)?
+            // Every criterion needs both edges to know the pin's level.
+            for pin_no in criterion.get_pins() {
+                if !interrupt_pins.contains(&pin_no) {
+                    pins.get_pin_mut(pin_no)?
                         .set_interrupt(Trigger::Both)?;
-                    interrupt_pins.push(*pin_no);
-                },
-            };
+                    interrupt_pins.push(pin_no);
+                }
+            }
+        }
+
+        // Wait steps need to see the pins they wait on change.
+        let wait_pins = self.actions.iter()
+            .filter(|Reverse(op)| op.get_device() == device)
+            .filter_map(|Reverse(op)| match op.action {
+                Some(Action::WaitFor { condition: WaitCondition::PinLevel(pin_no, _), .. }) => Some(pin_no),
+                _ => None,
+            });
+        for pin_no in wait_pins {
+            if !interrupt_pins.contains(&pin_no) {
+                pins.get_pin_mut(pin_no)?
+                    .set_interrupt(Trigger::Both)?;
+                interrupt_pins.push(pin_no);
+            }
         }
 
         Ok(interrupt_pins)
@@ -388,29 +1037,33 @@ impl Test {
     ///
     /// Watches for responses from the device under test for a slightly longer duration than the duration of the test.
     /// This is done to catch any straggling responses from the device.
+    /// The level each pin starts at goes into `initial`;
+    /// pin levels are also recorded into `events` as they change.
     pub fn observe(&self,
                    t0: Instant,
-                   pins: &Vec<&InputPin>,
-                   out: &mut Vec<Response>) -> Result<()>
+                   backend: &dyn Backend,
+                   pins: &[&dyn InputPin],
+                   initial: &mut Vec<Response>,
+                   out: &mut Vec<Response>,
+                   events: &DeviceEvents) -> Result<()>
     {
-        let gpio = Gpio::new()?;
         let t_end = t0 + self.max_runtime();
+        for pin in pins {
+            let level = pin.read()?;
+            events.record_level(pin.pin(), level, t0);
+            initial.push(Response::new(t0, pin.pin(), Signal::Digital(level.into())));
+        }
         let mut t = Instant::now();
 
         while t < t_end {
-            let poll = gpio.poll_interrupts(
-                pins.as_slice(),
-                false,
-                Some(t_end - t))?;
+            let poll = backend.poll_interrupts(pins, Some(t_end - t))?;
 
-            if let Some((pin, level)) = poll {
+            if let Some((pin_no, level, time)) = poll {
+                events.record_level(pin_no, level, time);
                 let response = Response::new(
-                    Instant::now(),
-                    pin.pin(),
-                    match level {
-                        Level::High => Signal::Digital(true),
-                        Level::Low => Signal::Digital(false),
-                    });
+                    time,
+                    pin_no,
+                    Signal::Digital(level.into()));
                 out.push(response);
             }
 
@@ -439,7 +1092,7 @@ impl Test {
         let mut has_energy_criteria = false;
         // pre-allocate space in sample output vectors
         for criterion in &self.criteria {
-            if let Criterion::Energy(ref energy_criterion) = criterion {
+            if let Criterion::Energy(ref energy_criterion) = criterion.get_target() {
                 has_energy_criteria = true;
                 let meter_id = energy_criterion.get_meter();
                 if !meters.contains_key(meter_id) {
@@ -483,15 +1136,67 @@ impl Test {
         }
     }
 
+    /// Prepare structures for analog sampling.
+    ///
+    /// # Returns
+    /// Returns true if there are analog criteria in this test.
+    /// [`Test::sample`] should be called when running the test.
+    pub fn prep_sample(&self,
+                       inputs: &HashMap<u8, Box<dyn AnalogInput>>,
+                       out: &mut HashMap<u8, Vec<(Instant, f32)>>,
+    ) -> Result<bool> {
+        // only care about pins defined in the criteria
+        out.clear();
+
+        let mut has_analog_criteria = false;
+        for criterion in &self.criteria {
+            if let Criterion::Analog(ref analog_criterion) = criterion.get_target() {
+                has_analog_criteria = true;
+                let pin_no = analog_criterion.get_pin();
+                // Analog inputs are wired to the default device.
+                if !inputs.contains_key(&pin_no) || criterion.get_device() != DEFAULT_DEVICE {
+                    return Err(TestingError::NoSuchAnalogInput(pin_no));
+                } else {
+                    out.entry(pin_no)
+                        .or_default();
+                }
+            }
+        }
+
+        Ok(has_analog_criteria)
+    }
+
+    /// Perform analog sampling.
+    ///
+    /// The `out` parameter should be the same `out` passed to [`Test::prep_sample`].
+    /// Sampling stops at the first reading that fails.
+    pub fn sample(&self,
+                  inputs: &HashMap<u8, Box<dyn AnalogInput>>,
+                  out: &mut HashMap<u8, Vec<(Instant, f32)>>) -> Result<()>
+    {
+        let start = Instant::now();
+        let runtime = self.max_runtime();
+
+        loop {
+            if Instant::now() - start >= runtime { break; }
+
+            for (pin_no, buf) in &mut *out {
+                let input = inputs.get(pin_no).unwrap();
+                // Conversions take a while; stamp the sample when its value is known.
+                let voltage = input.voltage()
+                    .map_err(|e| TestingError::Sampling(*pin_no, e))?;
+                buf.push((Instant::now(), voltage));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Prepare structures for tracing.
     pub fn prep_tracing<'a>(&self,
-                            uart: &mut Uart,
+                            uart: &mut dyn SerialPort,
                             data_buffer: &'a mut Vec<u8>,
                             schedule: &'a mut Vec<(Instant, usize)>) -> Result<()> {
-        // Timeout is a bit arbitrary here.
-        // Don't want the thread hanging the test unnecessarily.
-        uart.set_read_mode(0, Duration::from_millis(50))?;
-
         schedule.clear();
 
         let buffer_alloc: usize = 1 * 1024 * 1024;
@@ -499,16 +1204,17 @@ impl Test {
         schedule.reserve_exact(buffer_alloc);
         while data_buffer.len() < buffer_alloc { data_buffer.push(0); }
         // Clear out any early data that arrives before the reset.
-        uart.flush(rppal::uart::Queue::Input)?;
+        uart.flush_input()?;
 
         Ok(())
     }
 
-    /// Perform the tracing specified by the test.
+    /// Perform the tracing specified by the test, recording the data received into `events` as it arrives.
     pub fn trace(&self,
-                 uart: &mut Uart,
+                 uart: &mut dyn SerialPort,
                  buffer: &mut Vec<u8>,
-                 schedule: &mut Vec<(Instant, usize)>) -> Result<usize> {
+                 schedule: &mut Vec<(Instant, usize)>,
+                 events: &DeviceEvents) -> Result<usize> {
         let buffer: &mut [u8] = buffer.as_mut_slice();
         let mut bytes_read: usize = 0;
 
@@ -521,8 +1227,12 @@ impl Test {
 
             let read = uart.read(&mut buffer[bytes_read..])?;
             if read > 0 {
+                // The read blocks until data arrives or times out,
+                // so the data arrived when the read returned, not when the iteration started.
+                let received_at = Instant::now();
+                events.record_serial(&buffer[bytes_read..bytes_read + read], received_at);
                 bytes_read += read;
-                schedule.push((now, read));
+                schedule.push((received_at, read));
             }
         }
 
@@ -531,13 +1241,10 @@ impl Test {
 
     /// Prepare structures for memory tracking.
     pub fn prep_memtrack(&self,
-                         uart: &mut Uart,
+                         _uart: &mut dyn SerialPort,
                          buffer: &mut Vec<u8>,
                          schedule: &mut Vec<MemoryTrace>) -> Result<()>
     {
-        // Again, timeout is arbitrary.
-        uart.set_read_mode(0, Duration::from_millis(100))?;
-
         schedule.clear();
 
         let buffer_alloc = 1 * 1024 * 1024;
@@ -549,11 +1256,12 @@ impl Test {
         Ok(())
     }
 
-    /// Perform memory tracking.
+    /// Perform memory tracking, recording counter updates into `events` as they are parsed.
     pub fn memtrack(&self,
-                    uart: &mut Uart,
+                    uart: &mut dyn SerialPort,
                     buffer: &mut Vec<u8>,
-                    schedule: &mut Vec<MemoryTrace>) -> Result<usize>
+                    schedule: &mut Vec<MemoryTrace>,
+                    events: &DeviceEvents) -> Result<usize>
     {
         let buffer: &mut [u8] = buffer.as_mut_slice();
         let mut bytes_read = 0;
@@ -609,6 +1317,7 @@ impl Test {
                         // Parser successfully read a stream operation.
                         // We advance our bytes_parsed marker forward by the number of bytes we parsed.
                         Ok(((unparsed, _bit_offset), op)) => {
+                            events.record_counter(&op);
                             schedule.push(op);
                             bytes_parsed += to_parse.len() - unparsed.len();
                         },
@@ -639,39 +1348,64 @@ impl Test {
         Ok(bytes_read - bytes_parsed)
     }
 
-    /// Return the maximum length of time the test can run.
-    ///
-    /// TODO: make this dependent on actions' timing, criteria timing, and another tail duration(?).
+    /** Return the maximum length of time the test can run.
+
+    Unless set [explicitly](Test::with_runtime), the test runs until its last operation finishes
+    or the [window](Criterion::get_window) of its last timed criterion closes, whichever is later,
+    followed by the [tail](Test::with_tail).
+     */
     pub fn max_runtime(&self) -> Duration {
-        let duration_ms = self.actions.iter()
+        if let Some(runtime) = self.runtime {
+            return runtime;
+        }
+
+        let operations = self.actions.iter()
             // Only Operations with actions.
-            .filter(|Reverse(op)| op.action.is_some())
-            .map(|Reverse(op)| match op.action.unwrap() {
-                Action::Idle(idle_duration) => op.time + (idle_duration.as_millis() as u64),
-                _ => op.time,
+            .filter_map(|Reverse(op)| op.action.as_ref().map(|action| op.time + action.duration()))
+            .max()
+            .unwrap_or_default();
+        // Each wait can push the rest of the timeline back by up to its timeout.
+        let waits: Duration = self.actions.iter()
+            .filter_map(|Reverse(op)| match op.action {
+                Some(Action::WaitFor { timeout, .. }) => Some(timeout),
+                _ => None,
             })
-            .last()
-            .unwrap_or(0);
-        let tail_ms = self.tail_duration
-            .unwrap_or(Duration::from_millis(0))
-            .as_millis();
+            .sum();
+        let criteria = self.criteria.iter()
+            .filter_map(Criterion::get_window)
+            .max()
+            .unwrap_or_default();
 
-        Duration::from_millis(duration_ms + tail_ms as u64)
+        (operations + waits).max(criteria) + self.tail
     }
 }
 
 impl Display for Test {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Test: {}\n", self.id)?;
+        if self.repetitions > 1 {
+            writeln!(f, "Repetitions: {}", self.repetitions)?;
+        }
+        if !self.parameters.is_empty() {
+            writeln!(f, "Parameters: {}", self.parameters)?;
+        }
+        if !self.tags.is_empty() {
+            let mut tags: Vec<&str> = self.tags.iter().map(String::as_str).collect();
+            tags.sort_unstable();
+            writeln!(f, "Tags: {}", tags.join(", "))?;
+        }
         write!(f, "=== Operation timeline\n")?;
-        write!(f, "|{:>10}|{:^20}|\n", "time (ms)", "operation")?;
+        write!(f, "|{:>10}|{:^20}|\n", "time", "operation")?;
         write!(f, "|----------+--------------------|\n")?;
         for Reverse(ref action) in &self.actions {
-            if let Some(act) = action.action {
-                let act_text = format!("{}", act);
-                write!(f, "|{:>10}|{:^20}|\n", action.time, act_text)?;
+            if let Some(ref act) = action.action {
+                let act_text = match action.device {
+                    Some(ref device) => format!("{} ({})", act, device),
+                    None => format!("{}", act),
+                };
+                write!(f, "|{:>10}|{:^20}|\n", format!("{:?}", action.time), act_text)?;
             } else {
-                write!(f, "|{:>10}|{:^20}|\n", action.time, "-")?;
+                write!(f, "|{:>10}|{:^20}|\n", format!("{:?}", action.time), "-")?;
             }
         }
         write!(f, "\n")?;
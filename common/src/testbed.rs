@@ -1,26 +1,34 @@
 //! Configure and execute tests.
 
 use std::collections::HashMap;
+use std::env;
 use std::error;
 use std::fmt;
 use std::fmt::Display;
+use std::path::Path;
 use std::sync::mpsc;
-use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::{Receiver, SyncSender};
 use std::sync::{Arc,
-                Barrier,
+                Condvar,
                 Mutex,
+                MutexGuard,
                 RwLock};
 use std::thread;
 use std::thread::JoinHandle;
-use std::time::Instant;
-
-use crate::facility::EnergyMetering;
-use crate::io::{IOError, Mapping, UART};
+use std::time::{Duration, Instant};
+
+use crate::comm::{Direction, Signal};
+use crate::criteria::{Criterion, GPIOCriterion};
+use crate::events::{DeviceEvents, WaitCondition};
+use crate::facility::{AnalogInput, AnalogOutput, EnergyMetering};
+use crate::hal::Level;
+use crate::io::{DeviceAnalogInputs, DeviceInputs, IOError, Mapping, ResetStrategy, UART};
 use crate::mem::MemoryTrace;
 use crate::output::DataWriter;
+use crate::param::Parameters;
 use crate::sw::{self, PlatformSupport};
 use crate::sw::instrument::Spec;
-use crate::test::{Execution, Response, Test, TestingError};
+use crate::test::{Action, Execution, Response, Test, TestingError};
 use crate::trace;
 use crate::trace::{TraceData, TraceKind, SerialTrace};
 
@@ -32,10 +40,18 @@ type Result<T> = std::result::Result<T, TestbedError>;
 pub enum TestbedError {
     /// A problem occured while executing a test.
     Execution(TestingError),
+    /// A problem occured while acquiring I/O for a test.
+    IO(IOError),
     /// A problem occured while performing a reset operation on the device.
     Reset(IOError),
     /// A problem occured while interacting with software ([`sw::error::Error`]).
     Software(sw::error::SoftwareError),
+    /// The test did not finish within its timeout; the named threads were still busy.
+    Timeout(Duration, Vec<String>),
+    /// A thread collecting data during the test failed (thread, error).
+    Collection(String, TestingError),
+    /// The named threads collecting data exited or stopped responding, so the test could not be run.
+    Stopped(Vec<String>),
 }
 
 impl error::Error for TestbedError {
@@ -43,8 +59,12 @@ impl error::Error for TestbedError {
         use TestbedError::*;
         match self {
             Execution(ref e) => Some(e),
+            IO(ref e) => Some(e),
             Reset(ref e) => Some(e),
             Software(ref e) => Some(e),
+            Timeout(..) => None,
+            Collection(_, ref e) => Some(e),
+            Stopped(_) => None,
         }
     }
 }
@@ -66,26 +86,453 @@ impl Display for TestbedError {
         use TestbedError::*;
         match self {
             Execution(ref e) => write!(f, "test execution error: {}", e),
+            IO(ref e) => write!(f, "failed to acquire I/O: {}", e),
             Reset(ref e) => write!(f, "failed to reset device: {}", e),
             Software(ref e) => write!(f, "software interaction error: {}", e),
+            Timeout(limit, ref busy) => write!(f, "test did not finish within {:?} (waiting for {})", limit, busy.join(", ")),
+            Collection(ref thread, ref e) => write!(f, "data collection by {} failed: {}", thread, e),
+            Stopped(ref threads) => write!(f, "data collection stopped: {} exited or stopped responding", threads.join(", ")),
         }
     }
 }
 
-/// Test suite executor
+/// Problem found while [validating](Testbed::validate) a [`Test`] against the testbed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestConflict {
+    /// Operation or criterion names a device that is not on the testbed.
+    NoSuchDevice(String),
+    /// Device pin is not defined by the device (device, pin).
+    UndefinedPin(String, u8),
+    /// Device pin is not mapped to a testbed pin (device, pin).
+    UnmappedPin(String, u8),
+    /// Device pin does not go the way the test uses it (device, pin, direction the test needs).
+    PinDirection(String, u8, Direction),
+    /// No analog output is wired to the device pin (device, pin).
+    NoAnalogOutput(String, u8),
+    /// No analog input is wired to the device pin (device, pin).
+    NoAnalogInput(String, u8),
+    /// Energy meter is not configured.
+    NoSuchMeter(String),
+    /// Device has no serial tracing UART.
+    NoTracingUart(String),
+    /// Device has no memory tracking UART.
+    NoMemoryUart(String),
+    /// Trace points are enabled but no device has a serial tracing UART.
+    UntracedTracePoints,
+    /// Test resets on start but no device on the testbed can be reset.
+    NoReset,
+    /// Program of a reset command cannot be found (device, program).
+    NoResetProgram(String, String),
+}
+
+impl Display for TestConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TestConflict::*;
+        match self {
+            NoSuchDevice(ref device) => write!(f, "device '{}' is not on the testbed", device),
+            UndefinedPin(ref device, pin_no) => write!(f, "device pin {} is not defined by '{}'", pin_no, device),
+            UnmappedPin(ref device, pin_no) =>
+                write!(f, "device pin {} of '{}' is not mapped to a testbed pin", pin_no, device),
+            PinDirection(ref device, pin_no, Direction::In) =>
+                write!(f, "device pin {} of '{}' is not an input", pin_no, device),
+            PinDirection(ref device, pin_no, Direction::Out) =>
+                write!(f, "device pin {} of '{}' is not an output", pin_no, device),
+            NoAnalogOutput(ref device, pin_no) =>
+                write!(f, "no analog output is wired to device pin {} of '{}'", pin_no, device),
+            NoAnalogInput(ref device, pin_no) =>
+                write!(f, "no analog input is wired to device pin {} of '{}'", pin_no, device),
+            NoSuchMeter(ref meter) => write!(f, "energy meter '{}' is not configured", meter),
+            NoTracingUart(ref device) => write!(f, "device '{}' has no serial tracing UART", device),
+            NoMemoryUart(ref device) => write!(f, "device '{}' has no memory tracking UART", device),
+            UntracedTracePoints => write!(f, "trace points are enabled but no device has a serial tracing UART"),
+            NoReset => write!(f, "test resets on start but no device can be reset"),
+            NoResetProgram(ref device, ref program) =>
+                write!(f, "reset program '{}' of '{}' cannot be found", program, device),
+        }
+    }
+}
+
+/// Returns the problem with a test using `pin_no` of `dut` as an input or output, if any.
+fn pin_conflict(dut: &DeviceUnderTest, pin_no: u8, direction: Direction) -> Option<TestConflict> {
+    let mapped = dut.pin_mapping.get_mapping().values().any(|t_pin| *t_pin == pin_no);
+    match dut.pin_mapping.get_device().direction_of(pin_no) {
+        Err(_) => Some(TestConflict::UndefinedPin(dut.name.clone(), pin_no)),
+        Ok(pin_direction) if pin_direction != direction =>
+            Some(TestConflict::PinDirection(dut.name.clone(), pin_no, direction)),
+        Ok(_) if !mapped => Some(TestConflict::UnmappedPin(dut.name.clone(), pin_no)),
+        Ok(_) => None,
+    }
+}
+
+/// Returns whether `program` names an existing file, looking it up on `PATH` if it is a bare name.
+fn program_exists(program: &str) -> bool {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file();
+    }
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+/// Name the main testbed thread goes by when meeting the data-collecting threads.
+const EXECUTOR: &str = "executor";
+
+/// How long the executor waits for the other threads to stop once all tests have run.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Name of the energy metering thread.
+const METERING: &str = "test-metering";
+
+/// Name of the analog sampling thread.
+const SAMPLING: &str = "test-sampling";
+
+/// Returns the name of the thread collecting `data` from the device under test `dut`.
+fn collector(data: &str, dut: &str) -> String {
+    format!("test-{}-{}", data, dut)
+}
+
+/// Read timeout of serial tracing UARTs without a configured one.
+const TRACING_READ_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// What a data-collecting thread sends the executor after a test run.
 #[derive(Debug)]
-pub struct Testbed {
+enum Collected<T> {
+    /// One piece of the data collected.
+    Data(T),
+    /// All data was sent.
+    Done,
+    /// Collection failed; no data follows.
+    Failed(TestingError),
+}
+
+/// Reason a thread did not meet the others at a [`Rendezvous`] step.
+#[derive(Debug)]
+enum Missed {
+    /// The executor gave up on the step before the thread arrived.
+    Abandoned,
+    /// The deadline passed before the named threads arrived, so the step was given up on.
+    TimedOut(Vec<String>),
+}
+
+impl Missed {
+    /// Returns the error of a run given up on at the missed step, with `limit` the run's time limit.
+    fn into_error(self, limit: Duration) -> TestbedError {
+        match self {
+            Missed::TimedOut(busy) => TestbedError::Timeout(limit, busy),
+            Missed::Abandoned => TestbedError::Timeout(limit, Vec::new()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RendezvousState {
+    /// Threads still meeting at each step.
+    parties: Vec<String>,
+    /// Step the threads are meeting at; counts up as steps complete or are given up on.
+    step: u64,
+    arrived: Vec<String>,
+    abandoned: Vec<u64>,
+    /// Threads that exited.
+    left: Vec<String>,
+    /// Threads that missed a deadline and have not arrived at a step since.
+    missing: Vec<String>,
+    /// Whether any thread ever missed a deadline.
+    missed: bool,
+}
+
+impl RendezvousState {
+    /// Give up on the current step and move on to the next.
+    fn give_up(&mut self) {
+        self.arrived.clear();
+        self.abandoned.push(self.step);
+        self.step += 1;
+    }
+}
+
+/** Barrier the executor and the data-collecting threads meet at between the stages of a test run.
+
+Unlike [`std::sync::Barrier`], the executor can give up on a step when a thread does not arrive in time.
+Threads waiting at that step are released with an error,
+and a thread that arrives after it was given up on gets an error straight away,
+so every thread can drop the run and wait for the next one.
+A thread that exits [leaves](Rendezvous::leave), and the others stop waiting for it.
+Threads that miss a deadline are not waited for either, until they arrive at a later step.
+ */
+#[derive(Debug)]
+struct Rendezvous {
+    state: Mutex<RendezvousState>,
+    changed: Condvar,
+}
+
+impl Rendezvous {
+    /// Create a rendezvous for the named threads.
+    fn new(parties: Vec<String>) -> Rendezvous {
+        Rendezvous {
+            state: Mutex::new(RendezvousState { parties, ..RendezvousState::default() }),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RendezvousState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /** Wait for every thread to arrive at step `*step`, giving up on the step once `deadline` passes.
+
+    `*step` is updated to the step to meet at next, whether or not this one was met.
+     */
+    fn wait(&self, name: &str, step: &mut u64, deadline: Option<Instant>) -> std::result::Result<(), Missed> {
+        let mut state = self.lock();
+        if let Some(idx) = state.missing.iter().position(|party| party == name) {
+            // Back from missing a deadline; the step it was at has been given up on.
+            state.missing.remove(idx);
+            state.parties.push(name.to_string());
+        }
+        if state.step != *step {
+            // The step was given up on while this thread was busy.
+            *step = state.step;
+            return Err(Missed::Abandoned);
+        }
+
+        state.arrived.push(name.to_string());
+        if state.arrived.len() == state.parties.len() {
+            state.arrived.clear();
+            state.step += 1;
+            *step = state.step;
+            self.changed.notify_all();
+            return Ok(());
+        }
+
+        let waiting_at = state.step;
+        loop {
+            if state.step != waiting_at {
+                *step = state.step;
+                return if state.abandoned.contains(&waiting_at) {
+                    Err(Missed::Abandoned)
+                } else {
+                    Ok(())
+                };
+            }
+
+            match deadline {
+                Some(deadline) if Instant::now() >= deadline => {
+                    let mut busy = state.parties.clone();
+                    for arrived in &state.arrived {
+                        if let Some(idx) = busy.iter().position(|party| party == arrived) {
+                            busy.remove(idx);
+                        }
+                    }
+                    // Later steps go on without the threads that are still busy.
+                    state.parties.retain(|party| !busy.contains(party));
+                    state.missing.extend(busy.iter().cloned());
+                    state.missed = true;
+                    state.give_up();
+                    *step = state.step;
+                    self.changed.notify_all();
+                    return Err(Missed::TimedOut(busy));
+                },
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    state = self.changed.wait_timeout(state, timeout)
+                        .map(|(state, _timeout)| state)
+                        .unwrap_or_else(|e| e.into_inner().0);
+                },
+                None => {
+                    state = self.changed.wait(state)
+                        .unwrap_or_else(|e| e.into_inner());
+                },
+            }
+        }
+    }
+
+    /// Give up on step `*step` without waiting, releasing the threads waiting at it with an error.
+    fn give_up(&self, step: &mut u64) {
+        let mut state = self.lock();
+        if state.step == *step {
+            state.give_up();
+            self.changed.notify_all();
+        }
+        *step = state.step;
+    }
+
+    /// Stop waiting for `name` at any step, as its thread exited.
+    fn leave(&self, name: &str) {
+        let mut state = self.lock();
+        state.parties.retain(|party| party != name);
+        state.arrived.retain(|party| party != name);
+        state.missing.retain(|party| party != name);
+        state.left.push(name.to_string());
+        if !state.arrived.is_empty() && state.arrived.len() == state.parties.len() {
+            // The others were only waiting for the thread that left.
+            state.arrived.clear();
+            state.step += 1;
+        }
+        self.changed.notify_all();
+    }
+
+    /// Returns the threads that [left](Rendezvous::leave) or are missing since a deadline passed.
+    fn gone(&self) -> Vec<String> {
+        let state = self.lock();
+        state.left.iter()
+            .chain(state.missing.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns true if any thread missed a deadline, even if it has come back since.
+    fn missed(&self) -> bool {
+        self.lock().missed
+    }
+
+    /// Returns a guard that makes `name` leave when dropped, including when its thread panics.
+    fn member<'a>(&'a self, name: &'a str) -> Member<'a> {
+        Member { barrier: self, name }
+    }
+}
+
+/// Thread meeting the others at a [`Rendezvous`] until it exits.
+struct Member<'a> {
+    barrier: &'a Rendezvous,
+    name: &'a str,
+}
+
+impl Drop for Member<'_> {
+    fn drop(&mut self) {
+        self.barrier.leave(self.name);
+    }
+}
+
+/** Receive what the thread `name` collected during a test run, passing each piece to `data`.
+
+Returns the error of a thread that failed to collect or exited.
+ */
+fn receive<T, F>(name: &str, channel: &Receiver<Collected<T>>, mut data: F) -> Option<TestbedError>
+where
+    F: FnMut(T),
+{
+    loop {
+        match channel.recv() {
+            Ok(Collected::Data(piece)) => data(piece),
+            Ok(Collected::Done) => return None,
+            Ok(Collected::Failed(e)) => return Some(TestbedError::Collection(name.to_string(), e)),
+            // The thread exited without finishing.
+            Err(_) => return Some(TestbedError::Stopped(vec![name.to_string()])),
+        }
+    }
+}
+
+/** Name of the device under test given to [`Testbed::new`].
+
+Operations and criteria that do not name a device apply to this one.
+ */
+pub const DEFAULT_DEVICE: &str = "dut";
+
+/** Device under test wired to the testbed.
+
+Each device has its own pin mapping (and with it, its own reset functionality), platform support, and serial tracing.
+Devices beyond the one given to [`Testbed::new`] are added with [`Testbed::with_device`].
+
+# Examples
+```ignore
+let radio = DeviceUnderTest::new("radio", mapping, Box::new(platform))
+    .with_tracing_uart(UART::Custom("/dev/ttyUSB0".to_string()));
+let testbed = Testbed::new(...).with_device(radio);
+```
+ */
+#[derive(Debug)]
+pub struct DeviceUnderTest {
+    name: String,
     pin_mapping: Mapping,
     platform_support: Box<dyn PlatformSupport>,
-    energy_meters: Arc<Mutex<HashMap<String, Box<dyn EnergyMetering>>>>,
     tracing_uart: Option<UART>,
     memory_uart: Option<UART>,
     tracing: Vec<(TraceKind, UART)>,
+}
+
+impl DeviceUnderTest {
+    /// Define a new device under test.
+    pub fn new(name: &str,
+               pin_mapping: Mapping,
+               platform_support: Box<dyn PlatformSupport>) -> DeviceUnderTest
+    {
+        DeviceUnderTest {
+            name: name.to_string(),
+            pin_mapping,
+            platform_support,
+            tracing_uart: None,
+            memory_uart: None,
+            tracing: Vec::new(),
+        }
+    }
+
+    /// Receive serial traces from the device over `uart`.
+    pub fn with_tracing_uart(self, uart: UART) -> Self {
+        Self {
+            tracing_uart: Some(uart),
+            ..self
+        }
+    }
+
+    /// Receive memory tracking data from the device over `uart`.
+    pub fn with_memory_uart(self, uart: UART) -> Self {
+        Self {
+            memory_uart: Some(uart),
+            ..self
+        }
+    }
+
+    /// Collect trace data of the given kind from the device over `uart`.
+    pub fn with_tracing(mut self, kind: TraceKind, uart: UART) -> Self {
+        self.tracing.push((kind, uart));
+        self
+    }
+
+    /// Returns the name of the device.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the I/O mapping of the device.
+    pub fn get_mapping(&self) -> &Mapping {
+        &self.pin_mapping
+    }
+}
+
+/// What the observer thread saw on a device's pins.
+enum Observed {
+    /// Level a pin was at when the test started.
+    Initial(Response),
+    /// Pin changed level.
+    Change(Response),
+}
+
+/// Receiving ends of the threads collecting data from a device under test.
+struct DeviceChannels {
+    observer: (JoinHandle<()>, Receiver<Collected<Observed>>),
+    tracing: (JoinHandle<()>, Receiver<Collected<SerialTrace>>),
+    memory: (JoinHandle<()>, Receiver<Collected<MemoryTrace>>),
+    // The ordering must be consistent with the device's tracing definitions.
+    // Collection failures the threads survive arrive as no data.
+    trace_kinds: Vec<Receiver<Collected<Option<TraceData>>>>,
+    // What the threads have seen the device do, for wait steps.
+    events: Arc<DeviceEvents>,
+}
+
+/// Test suite executor
+#[derive(Debug)]
+pub struct Testbed {
+    devices: Vec<DeviceUnderTest>,
+    energy_meters: Arc<Mutex<HashMap<String, Box<dyn EnergyMetering>>>>,
+    analog_outputs: Mutex<DeviceAnalogInputs>,
+    analog_inputs: Arc<Mutex<HashMap<u8, Box<dyn AnalogInput>>>>,
     data_writer: Option<Box<dyn DataWriter>>,
 }
 
 impl Testbed {
     /// Create a new `Testbed`.
+    ///
+    /// The mapping, platform support, and UARTs define the [default device](DEFAULT_DEVICE) under test.
     pub fn new(
         pin_mapping: Mapping,
         platform_support: Box<dyn PlatformSupport>,
@@ -95,18 +542,183 @@ impl Testbed {
         tracing: Vec<(TraceKind, UART)>,
     ) -> Testbed
     {
-        Testbed {
+        let device = DeviceUnderTest {
+            name: DEFAULT_DEVICE.to_string(),
             pin_mapping,
             platform_support,
-            energy_meters: Arc::new(Mutex::new(energy_meters)),
             tracing_uart,
             memory_uart,
             tracing,
+        };
+
+        Testbed {
+            devices: vec![device],
+            energy_meters: Arc::new(Mutex::new(energy_meters)),
+            analog_outputs: Mutex::new(DeviceAnalogInputs::new(Vec::new())),
+            analog_inputs: Arc::new(Mutex::new(HashMap::new())),
             data_writer: None,
         }
     }
 
+    /** Add another device under test.
+
+    Tests address the device by its name in [`Operation::on`](crate::test::Operation::on)
+    and [`Criterion::on`](crate::criteria::Criterion::on).
+    A device with the same name as one already on the testbed replaces it.
+     */
+    pub fn with_device(mut self, device: DeviceUnderTest) -> Self {
+        match self.devices.iter().position(|dut| dut.name == device.name) {
+            Some(idx) => self.devices[idx] = device,
+            None => self.devices.push(device),
+        }
+        self
+    }
+
+    /** Use analog outputs to drive the voltages of device pins.
+
+    `analog_outputs` is keyed by the device-side pin number each output is wired to.
+    Operations applying [`Signal::Analog`](crate::comm::Signal::Analog) to a pin use its output.
+    Analog outputs are wired to the [default device](DEFAULT_DEVICE).
+     */
+    pub fn with_analog_outputs<T>(self, analog_outputs: T) -> Self
+    where
+        T: IntoIterator<Item = (u8, Box<dyn AnalogOutput>)>,
+    {
+        Self {
+            analog_outputs: Mutex::new(DeviceAnalogInputs::new(analog_outputs)),
+            ..self
+        }
+    }
+
+    /** Use analog inputs to sample the voltages of device pins.
+
+    `analog_inputs` is keyed by the device-side pin number each input is wired to.
+    Pins named by [`Criterion::Analog`](crate::criteria::Criterion::Analog) criteria are sampled while a test runs.
+    Analog inputs are wired to the [default device](DEFAULT_DEVICE).
+     */
+    pub fn with_analog_inputs<T>(self, analog_inputs: T) -> Self
+    where
+        T: IntoIterator<Item = (u8, Box<dyn AnalogInput>)>,
+    {
+        Self {
+            analog_inputs: Arc::new(Mutex::new(analog_inputs.into_iter().collect())),
+            ..self
+        }
+    }
+
+    /// Returns the devices under test.
+    pub fn get_devices(&self) -> &[DeviceUnderTest] {
+        &self.devices
+    }
+
+    /** Check that a test fits the testbed, without running it.
+
+    Returns every problem found, such as inputs applied to device outputs, criteria on unmapped pins,
+    energy meters that are not configured, trace points without a tracing UART,
+    or resets on start that no device supports or whose reset program is missing.
+    A test without problems returns an empty list.
+    Validate tests before [executing](Testbed::execute) them, while the energy meters and analog I/O are free.
+     */
+    pub fn validate(&self, test: &Test) -> Vec<TestConflict> {
+        let meters = self.energy_meters.lock().unwrap();
+        let analog_outputs: Vec<u8> = self.analog_outputs.lock().unwrap().pin_numbers().collect();
+        let analog_inputs = self.analog_inputs.lock().unwrap();
+
+        let mut conflicts = Vec::new();
+        let device = |name: &str| self.devices.iter().find(|dut| dut.name == name);
+
+        for op in test.get_operations() {
+            let dut = match device(op.get_device()) {
+                Some(dut) => dut,
+                None => {
+                    conflicts.push(TestConflict::NoSuchDevice(op.get_device().to_string()));
+                    continue;
+                },
+            };
+            match op.get_action() {
+                // Analog outputs are wired to the default device.
+                Some(&Action::Input(Signal::Analog(_), pin_no))
+                    if dut.name != DEFAULT_DEVICE || !analog_outputs.contains(&pin_no) =>
+                    conflicts.push(TestConflict::NoAnalogOutput(dut.name.clone(), pin_no)),
+                Some(Action::Input(Signal::Analog(_), _)) => (),
+                Some(Action::WaitFor { condition, .. }) => match *condition {
+                    WaitCondition::PinLevel(pin_no, _) =>
+                        conflicts.extend(pin_conflict(dut, pin_no, Direction::Out)),
+                    WaitCondition::SerialTrace(_) if dut.tracing_uart.is_none() =>
+                        conflicts.push(TestConflict::NoTracingUart(dut.name.clone())),
+                    WaitCondition::MemoryCounter(..) if dut.memory_uart.is_none() =>
+                        conflicts.push(TestConflict::NoMemoryUart(dut.name.clone())),
+                    _ => (),
+                },
+                Some(action) => {
+                    if let Some(pin_no) = action.get_pin() {
+                        conflicts.extend(pin_conflict(dut, pin_no, Direction::In));
+                    }
+                },
+                None => (),
+            }
+        }
+
+        for criterion in test.get_criteria() {
+            let dut = match device(criterion.get_device()) {
+                Some(dut) => dut,
+                None => {
+                    conflicts.push(TestConflict::NoSuchDevice(criterion.get_device().to_string()));
+                    continue;
+                },
+            };
+            match criterion.get_target() {
+                Criterion::GPIO(gpio) => {
+                    conflicts.extend(gpio.get_pins().into_iter()
+                        .filter_map(|pin_no| pin_conflict(dut, pin_no, Direction::Out)));
+                    if let GPIOCriterion::Latency(latency) = gpio {
+                        conflicts.extend(pin_conflict(dut, latency.get_stimulus_pin(), Direction::In));
+                    }
+                },
+                Criterion::Energy(energy) if !meters.contains_key(energy.get_meter()) =>
+                    conflicts.push(TestConflict::NoSuchMeter(energy.get_meter().to_string())),
+                Criterion::SerialTrace(_) if dut.tracing_uart.is_none() =>
+                    conflicts.push(TestConflict::NoTracingUart(dut.name.clone())),
+                // Analog inputs are wired to the default device.
+                Criterion::Analog(analog) if dut.name != DEFAULT_DEVICE || !analog_inputs.contains_key(&analog.get_pin()) =>
+                    conflicts.push(TestConflict::NoAnalogInput(dut.name.clone(), analog.get_pin())),
+                _ => (),
+            }
+        }
+
+        if test.get_reset_on_start() {
+            if self.devices.iter().all(|dut| dut.pin_mapping.get_device().get_reset().is_none()) {
+                conflicts.push(TestConflict::NoReset);
+            }
+            // `Mapping::new` already keeps power switches off the mapped pins.
+            for dut in &self.devices {
+                match dut.pin_mapping.get_device().get_reset() {
+                    Some(&ResetStrategy::Pin { pin_no, .. }) =>
+                        conflicts.extend(pin_conflict(dut, pin_no, Direction::In)),
+                    Some(ResetStrategy::Command { program, .. }) if !program_exists(program) =>
+                        conflicts.push(TestConflict::NoResetProgram(dut.name.clone(), program.clone())),
+                    _ => (),
+                }
+            }
+        }
+
+        if !test.get_trace_points().is_empty() && self.devices.iter().all(|dut| dut.tracing_uart.is_none()) {
+            conflicts.push(TestConflict::UntracedTracePoints);
+        }
+
+        // Report each problem once, however often the test runs into it.
+        let mut unique = Vec::new();
+        for conflict in conflicts {
+            if !unique.contains(&conflict) {
+                unique.push(conflict);
+            }
+        }
+        unique
+    }
+
     /// Define a write for testing data.
+    ///
+    /// Data is saved for the [default device](DEFAULT_DEVICE).
     pub fn save_results_with(&mut self, formatter: Box<dyn DataWriter>) {
         self.data_writer = Some(formatter);
     }
@@ -116,7 +728,7 @@ impl Testbed {
     Execute the given tests one after the other.
 
     # Examples
-    ```
+    ```ignore
     let mut results = Vec::new();
     testbed.execute(&[test], &mut results);
     ```
@@ -128,289 +740,533 @@ impl Testbed {
         let mut test_results = Vec::new();
 
         let barrier = {
-            let barrier_count =
             // One for each staticly allocated thread we have:
             // - Main testbed thread
-            // - Observer thread
             // - Energy metering thread
+            // - Analog sampling thread
+            let mut parties = vec![
+                EXECUTOR.to_string(),
+                METERING.to_string(),
+                SAMPLING.to_string(),
+            ];
+            // One for each thread watching a device under test:
+            // - Observer thread
             // - Serial tracing thread
             // - Memory tracing thread
-                5
-            // One for each user-defined tracing thread
-                + self.tracing.len();
+            // - One for each user-defined tracing thread
+            for dut in &self.devices {
+                parties.push(collector("observer", &dut.name));
+                parties.push(collector("stracing", &dut.name));
+                parties.push(collector("memtrack", &dut.name));
+                for (kind, _uart) in &dut.tracing {
+                    parties.push(collector(&kind.to_string(), &dut.name));
+                }
+            }
 
-            Arc::new(Barrier::new(barrier_count))
+            Arc::new(Rendezvous::new(parties))
         };
+        let mut step = 0;
 
         let current_test: Arc<RwLock<Option<Test>>> = Arc::new(RwLock::new(None));
 
-        let (observer_schannel, observer_rchannel) = mpsc::sync_channel(0);
-        let watch_thread = self.launch_observer(Arc::clone(&current_test),
-                                                Arc::clone(&barrier),
-                                                observer_schannel);
-
         let (energy_schannel, energy_rchannel) = mpsc::sync_channel(0);
         let energy_thread = self.launch_metering(Arc::clone(&current_test),
                                                  Arc::clone(&barrier),
                                                  energy_schannel);
 
-        let (trace_schannel, trace_rchannel) = mpsc::sync_channel(0);
-        let trace_thread = self.launch_tracing(Arc::clone(&current_test),
-                                               Arc::clone(&barrier),
-                                               trace_schannel,
-                                               self.tracing_uart.as_ref());
+        let (analog_schannel, analog_rchannel) = mpsc::sync_channel(0);
+        let analog_thread = self.launch_sampling(Arc::clone(&current_test),
+                                                 Arc::clone(&barrier),
+                                                 analog_schannel);
+
+        // Create threads for each device under test.
+        // The ordering must be consistent with the devices.
+        let device_channels: Vec<DeviceChannels> = self.devices.iter()
+            .map(|dut| self.launch_device(dut, &current_test, &barrier))
+            .collect();
+        let events: HashMap<String, Arc<DeviceEvents>> = self.devices.iter()
+            .zip(device_channels.iter())
+            .map(|(dut, channels)| (dut.name.clone(), Arc::clone(&channels.events)))
+            .collect();
+
+        let mut analog_inputs = self.analog_outputs.lock().unwrap();
+
+        // Repeated tests run back to back.
+        let runs = tests.flat_map(|test| {
+            let repetitions = test.get_repetitions();
+            (0..repetitions).map(move |run| (run, test.clone()))
+        });
+        for (run, test) in runs {
+            println!("executor: running '{}' ({}/{})", test.get_id(), run + 1, test.get_repetitions());
+            println!("{}", test);
+            let deadline = Instant::now() + test.get_timeout();
 
-        let (mem_schannel, mem_rchannel) = mpsc::sync_channel(0);
-        let mem_thread = self.launch_memstat(Arc::clone(&current_test),
-                                             Arc::clone(&barrier),
-                                             mem_schannel,
-                                             self.memory_uart.as_ref());
+            let mut devices: Vec<DeviceObservation> = self.devices.iter()
+                .map(|dut| DeviceObservation::new(&dut.name))
+                .collect();
 
-        // Create threads for the defined tracing purposes.
-        // Keep track of the receiving ends of their channels.
-        // The ordering must be consistent between the two vectors.
-        let tracing_rchannels = {
-            let mut rchannels = Vec::new();
-            for (kind, uart) in &self.tracing {
-                let (schannel, rchannel) = mpsc::sync_channel(0);
-                self.launch_tracing_kind(
-                    kind.clone(),
-                    uart,
-                    Arc::clone(&current_test),
-                    Arc::clone(&barrier),
-                    schannel);
-                rchannels.push(rchannel);
+            // Without all of its threads, the testbed cannot collect the data of a run.
+            let stopped = barrier.gone();
+            if !stopped.is_empty() {
+                println!("executor: not running '{}'; {} exited or stopped responding", test.get_id(), stopped.join(", "));
+                test_results.push(Observation::failed(test, run, devices, TestbedError::Stopped(stopped)));
+                continue;
             }
 
-            rchannels
-        };
-
-        for test in tests {
-            println!("executor: running '{}'", test.get_id());
-            println!("{}", test);
-
-            // Reconfigure target if necessary.
+            // Reconfigure targets if necessary.
             // Just always configuring when there are trace points
             // instead of doing anything idempotent.
             let trace_points: Vec<String> = test.get_trace_points().iter()
                 .cloned()
                 .collect();
-            let res = self.platform_support.reconfigure(&trace_points);
-            if let Err(reconfig_err) = res {
-                let observation = Observation::failed(
-                    test.clone(),
-                    None,
-                    TestbedError::Software(reconfig_err));
-                test_results.push(observation);
-                continue;
+            let mut setup_error = None;
+            let mut serial_ports = HashMap::new();
+            for (dut, observation) in self.devices.iter().zip(devices.iter_mut()) {
+                match dut.platform_support.reconfigure(&trace_points) {
+                    Ok(platform_spec) => observation.software_spec = Some(platform_spec),
+                    Err(reconfig_err) => {
+                        setup_error = Some(TestbedError::Software(reconfig_err));
+                        break;
+                    },
+                }
+
+                // Load application(s) if necessary.
+                if let Err(load_err) = self.load_apps(dut, &test) {
+                    println!("executor: error loading/removing application(s) on '{}'", dut.name);
+                    setup_error = Some(load_err);
+                    break;
+                }
+
+                // Open the serial ports the test writes to.
+                let mut ports = HashMap::new();
+                for uart in test.get_serial_outputs(&dut.name) {
+                    match dut.pin_mapping.get_uart(&uart) {
+                        Ok(port) => { ports.insert(uart, port); },
+                        Err(e) => {
+                            setup_error = Some(TestbedError::IO(e));
+                            break;
+                        },
+                    }
+                }
+                serial_ports.insert(dut.name.clone(), ports);
+                if setup_error.is_some() {
+                    break;
+                }
             }
-            let platform_spec = res.unwrap();
-
-            // Load application(s) if necessary.
-            if let Err(load_err) = self.load_apps(&test) {
-                println!("executor: error loading/removing application(s)");
-                let observation = Observation::failed(
-                    test.clone(),
-                    Some(platform_spec.clone()),
-                    load_err);
+            if let Some(error) = setup_error {
+                let observation = Observation::failed(test.clone(), run, devices, error);
                 test_results.push(observation);
                 continue;
             }
 
             *current_test.write().unwrap() = Some(test.clone());
+            for device_events in events.values() {
+                device_events.clear();
+            }
 
-            let mut inputs = self.pin_mapping.get_gpio_inputs()
-                .expect("Could not obtain GPIO inputs from executor thread.");
+            let mut inputs: HashMap<String, DeviceInputs> = self.devices.iter()
+                .map(|dut| {
+                    let device_inputs = dut.pin_mapping.get_gpio_inputs()
+                        .expect("Could not obtain GPIO inputs from executor thread.");
+                    (dut.name.clone(), device_inputs)
+                })
+                .collect();
 
             // wait for observer, metering thread to be ready
-            barrier.wait();
+            if let Err(missed) = barrier.wait(EXECUTOR, &mut step, Some(deadline)) {
+                let error = missed.into_error(test.get_timeout());
+                test_results.push(self.abandon(test, run, devices, error, &mut inputs));
+                continue;
+            }
 
             let use_reset = test.get_reset_on_start();
+            let mut reset_devices = Vec::new();
+            let mut reset_res = Ok(());
             if use_reset {
-                println!("Placing device in reset.");
-                let reset_res = self.pin_mapping.get_device().hold_in_reset(&mut inputs);
-                if let Err(e) = reset_res {
-                    let observation = Observation::failed(
-                        test.clone(),
-                        Some(platform_spec.clone()),
-                        TestbedError::Reset(e));
-                    test_results.push(observation);
-                    continue;
+                println!("Placing device(s) in reset.");
+                // Devices without reset functionality keep running,
+                // but at least one device must be reset.
+                reset_res = Err(IOError::NoReset);
+                for (dut, observation) in self.devices.iter().zip(devices.iter_mut()) {
+                    let device_inputs = inputs.get_mut(&dut.name).unwrap();
+                    match dut.pin_mapping.hold_in_reset(device_inputs, deadline) {
+                        Ok(()) => {
+                            reset_devices.push(dut);
+                            observation.reset_strategy = dut.pin_mapping.get_device().get_reset().cloned();
+                            reset_res = Ok(());
+                        },
+                        Err(IOError::NoReset) => (),
+                        Err(e) => {
+                            reset_res = Err(e);
+                            break;
+                        },
+                    }
                 }
             }
+            // A reset command that hung used up the run's time, so the run is given up on.
+            if let Err(error @ IOError::ResetTimeout(_)) = reset_res {
+                barrier.give_up(&mut step);
+                test_results.push(self.abandon(test, run, devices, TestbedError::Reset(error), &mut inputs));
+                continue;
+            }
 
             // wait for test to begin
-            barrier.wait();
+            if let Err(missed) = barrier.wait(EXECUTOR, &mut step, Some(deadline)) {
+                let error = missed.into_error(test.get_timeout());
+                test_results.push(self.abandon(test, run, devices, error, &mut inputs));
+                continue;
+            }
             println!("executor: starting test '{}'", test.get_id());
 
             // make sure testing has _just_ started before releasing reset
-            if use_reset {
-                self.pin_mapping.get_device().release_from_reset(&mut inputs)
-                    // failed to release reset, no point in continuing
-                    .expect("failed to release device from reset");
+            for dut in &reset_devices {
+                if let Err(e) = dut.pin_mapping.release_from_reset(inputs.get_mut(&dut.name).unwrap()) {
+                    // failed to release reset, no point in driving the test
+                    println!("executor: failed to release '{}' from reset: {}", dut.name, e);
+                    reset_res = reset_res.and(Err(e));
+                }
             }
-            let exec_result = test.execute(Instant::now(), &mut inputs)
-                .map_err(|e| TestbedError::Execution(e));
+            // The other threads run the test regardless,
+            // so a failed reset only skips driving the device.
+            let exec_result = match reset_res {
+                Ok(()) => match test.execute(Instant::now(), deadline, &mut inputs, &mut analog_inputs, &mut serial_ports, &events) {
+                    Err(TestingError::Deadline) => Err(TestbedError::Timeout(test.get_timeout(), vec![EXECUTOR.to_string()])),
+                    result => result.map_err(TestbedError::Execution),
+                },
+                Err(e) => Err(TestbedError::Reset(e)),
+            };
 
             // release observer thread
             println!("executor: test execution complete");
-            barrier.wait();
-
-            // get GPIO responses
-            let mut gpio_activity = Vec::new();
-            while let Some(response) = observer_rchannel.recv().unwrap() {
-                let response = response.remapped(self.pin_mapping.get_mapping());
-                gpio_activity.push(response);
+            if let Err(missed) = barrier.wait(EXECUTOR, &mut step, Some(deadline)) {
+                let error = missed.into_error(test.get_timeout());
+                test_results.push(self.abandon(test, run, devices, error, &mut inputs));
+                continue;
             }
 
             // get energy data
+            // (the first thread to fail fails the run, but every thread's data is received)
+            let mut collection_error = None;
             let mut energy_data = HashMap::new();
-            while let Some((meter_id, (t, sample))) = energy_rchannel.recv().unwrap() {
+            let error = receive(METERING, &energy_rchannel, |(meter_id, (t, sample))| {
                 energy_data.entry(meter_id)
                     .or_insert(Vec::new())
                     .push((t, sample));
-            }
-
-            // get tracing data
-            println!("executor: receiving trace data");
-            let mut serial_traces: Vec<SerialTrace> = Vec::new();
-            while let Some(trace) = trace_rchannel.recv().unwrap() {
-                serial_traces.push(trace);
-            }
-
-            let start = exec_result.as_ref().map(|exec| exec.get_start()).unwrap();
-            for trace in &serial_traces {
-                println!("{} @ {:?}", trace, trace.get_offset(start));
-            }
+            });
+            collection_error = collection_error.or(error);
+
+            // get analog samples
+            let mut analog_samples: HashMap<u8, Vec<(Instant, f32)>> = HashMap::new();
+            let error = receive(SAMPLING, &analog_rchannel, |(pin_no, (t, sample))| {
+                analog_samples.entry(pin_no)
+                    .or_default()
+                    .push((t, sample));
+            });
+            collection_error = collection_error.or(error);
+
+            let iter = self.devices.iter()
+                .zip(device_channels.iter())
+                .zip(devices.iter_mut());
+            for ((dut, channels), observation) in iter {
+                println!("executor: receiving data from '{}'", dut.name);
+
+                // get GPIO responses
+                let error = receive(&collector("observer", &dut.name), &channels.observer.1, |observed| {
+                    match observed {
+                        Observed::Initial(level) =>
+                            observation.initial_levels.push(level.remapped(dut.pin_mapping.get_mapping())),
+                        Observed::Change(response) =>
+                            observation.gpio_responses.push(response.remapped(dut.pin_mapping.get_mapping())),
+                    }
+                });
+                collection_error = collection_error.or(error);
+
+                // get tracing data
+                println!("executor: receiving trace data");
+                let error = receive(&collector("stracing", &dut.name), &channels.tracing.1, |trace| {
+                    observation.traces.push(trace);
+                });
+                collection_error = collection_error.or(error);
+
+                if let Ok(execution) = exec_result.as_ref() {
+                    for trace in &observation.traces {
+                        println!("{} @ {:?}", trace, trace.get_offset(execution.get_start()));
+                    }
+                }
 
-            // get memory data
-            println!("executor: receiving memory data");
-            let mut mem_traces: Vec<MemoryTrace> = Vec::new();
-            println!("| {:^15} | op. | {:^35} | {:^6} |", "offset", "counter", "value");
-            while let Some(mem_event) = mem_rchannel.recv().unwrap() {
-                let offset = format!("@{:?}", mem_event.time() - exec_result.as_ref().unwrap().get_start());
-                let counter = format!("{}", mem_event.counter());
-                println!("| {:>15} | {:^5?} | {:^35} | {:>6} |",
-                         offset,
-                         mem_event.operation(),
-                         counter,
-                         mem_event.value());
-                mem_traces.push(mem_event);
+                // get memory data
+                println!("executor: receiving memory data");
+                println!("| {:^15} | op. | {:^35} | {:^6} |", "offset", "counter", "value");
+                let error = receive(&collector("memtrack", &dut.name), &channels.memory.1, |mem_event| {
+                    let offset = match exec_result.as_ref() {
+                        Ok(execution) => format!("@{:?}", mem_event.time().saturating_duration_since(execution.get_start())),
+                        Err(_e) => " - ".to_string(),
+                    };
+                    let counter = format!("{}", mem_event.counter());
+                    println!("| {:>15} | {:^5?} | {:^35} | {:>6} |",
+                             offset,
+                             mem_event.operation(),
+                             counter,
+                             mem_event.value());
+                });
+                collection_error = collection_error.or(error);
+
+                // Receive tracing data.
+                let iter = channels.trace_kinds.iter()
+                    .zip(dut.tracing.iter());
+                for (rchannel, (trace_kind, _uart)) in iter {
+                    println!("executor: receiving data from {} thread", trace_kind);
+                    let mut data = None;
+                    let error = receive(&collector(&trace_kind.to_string(), &dut.name), rchannel, |trace_data| {
+                        data = trace_data;
+                    });
+                    collection_error = collection_error.or(error);
+                    observation.trace_info.push(trace_kind);
+                    observation.trace_data.push(data);
+                }
             }
 
-            // Receive tracing data.
-            let mut trace_data = Vec::new();
-            let iter = tracing_rchannels.iter()
-                .zip(self.tracing.iter());
-            for (rchannel, (trace_kind, _uart)) in iter {
-                println!("executor: receiving data from {} thread", trace_kind);
-                let data = rchannel.recv()
-                    .expect("Failed to receive data from tracing channel.");
-                trace_data.push(data);
-            }
+            // A run missing data it was meant to collect failed.
+            let exec_result = match collection_error {
+                Some(error) => Err(error),
+                None => exec_result,
+            };
 
             // save data
             if let (Some(writer), Ok(execution)) = (self.data_writer.as_ref(), exec_result.as_ref()) {
                 println!("executor: sending test data to writer");
+                let default_device = devices.iter()
+                    .find(|observation| observation.name == DEFAULT_DEVICE)
+                    .expect("default device is not on the testbed");
                 writer.save_output(
                     &test,
                     execution,
-                    &gpio_activity,
-                    &serial_traces,
+                    &default_device.gpio_responses,
+                    &default_device.traces,
                     &energy_data)
                     .expect("failed to save test data");
             }
 
             let observation = Observation::completed(
                 test.clone(),
-                Some(platform_spec.clone()),
+                run,
                 exec_result,
-                gpio_activity,
-                serial_traces,
-                self.tracing.iter()
-                    .map(|(kind, _uart)| kind)
-                    .collect(),
-                trace_data,
-                energy_data);
+                devices,
+                energy_data,
+                analog_samples);
             test_results.push(observation);
             println!("executor: test finished.");
         }
 
         *current_test.write().unwrap() = None;
         println!("executor: final wait");
-        barrier.wait();
+        if let Err(missed) = barrier.wait(EXECUTOR, &mut step, Some(Instant::now() + SHUTDOWN_TIMEOUT)) {
+            // Threads stuck on a device would block joining them forever.
+            println!("executor: not waiting for threads to exit: {:?}", missed);
+            return test_results;
+        }
+        if barrier.missed() {
+            // A thread that missed a deadline may never get back to its exit.
+            println!("executor: not waiting for threads that stopped responding to exit");
+            return test_results;
+        }
 
         // Not too concerned with joining these without error
         // since testing is complete at this point. It shouldn't
         // result in a crash either.
-        watch_thread.join().unwrap_or_else(|_e| {
-            println!("executor: failed to join with observer thread");
-        });
         energy_thread.join().unwrap_or_else(|_e| {
             println!("executor: failed to join with metering thread");
         });
-        trace_thread.join().unwrap_or_else(|_e| {
-            println!("executor: failed to join with tracing thread");
-        });
-        mem_thread.join().unwrap_or_else(|_e| {
-            println!("executor: failed to join with memory thread");
+        analog_thread.join().unwrap_or_else(|_e| {
+            println!("executor: failed to join with sampling thread");
         });
+        for channels in device_channels {
+            channels.observer.0.join().unwrap_or_else(|_e| {
+                println!("executor: failed to join with observer thread");
+            });
+            channels.tracing.0.join().unwrap_or_else(|_e| {
+                println!("executor: failed to join with tracing thread");
+            });
+            channels.memory.0.join().unwrap_or_else(|_e| {
+                println!("executor: failed to join with memory thread");
+            });
+        }
 
         test_results
     }
 
+    /** Give up on a test run that did not finish in time, failing it with `error`.
+
+    Devices that can be reset are reset, so the next run starts from a known state
+    even if the hung run left them mid-operation or held in reset.
+    Reset commands get as long as the run had before they are killed.
+     */
+    fn abandon<'a>(
+        &'a self,
+        test: Test,
+        run: u32,
+        devices: Vec<DeviceObservation<'a>>,
+        error: TestbedError,
+        inputs: &mut HashMap<String, DeviceInputs>,
+    ) -> Observation<'a> {
+        println!("executor: giving up on '{}': {}", test.get_id(), error);
+
+        let deadline = Instant::now() + test.get_timeout();
+        for dut in &self.devices {
+            let device_inputs = inputs.get_mut(&dut.name).unwrap();
+            let reset = dut.pin_mapping.hold_in_reset(device_inputs, deadline)
+                .and_then(|()| dut.pin_mapping.release_from_reset(device_inputs));
+            match reset {
+                Ok(()) | Err(IOError::NoReset) => (),
+                Err(e) => println!("executor: failed to reset '{}': {}", dut.name, e),
+            }
+        }
+
+        Observation::failed(test, run, devices, error)
+    }
+
+    /// Launch the threads that collect data from a device under test.
+    fn launch_device(
+        &self,
+        dut: &DeviceUnderTest,
+        test_container: &Arc<RwLock<Option<Test>>>,
+        barrier: &Arc<Rendezvous>,
+    ) -> DeviceChannels {
+        let events = Arc::new(DeviceEvents::new(dut.pin_mapping.get_mapping()));
+
+        let (observer_schannel, observer_rchannel) = mpsc::sync_channel(0);
+        let watch_thread = self.launch_observer(dut,
+                                                Arc::clone(test_container),
+                                                Arc::clone(barrier),
+                                                observer_schannel,
+                                                Arc::clone(&events));
+
+        let (trace_schannel, trace_rchannel) = mpsc::sync_channel(0);
+        let trace_thread = self.launch_tracing(dut,
+                                               Arc::clone(test_container),
+                                               Arc::clone(barrier),
+                                               trace_schannel,
+                                               dut.tracing_uart.as_ref(),
+                                               Arc::clone(&events));
+
+        let (mem_schannel, mem_rchannel) = mpsc::sync_channel(0);
+        let mem_thread = self.launch_memstat(dut,
+                                             Arc::clone(test_container),
+                                             Arc::clone(barrier),
+                                             mem_schannel,
+                                             dut.memory_uart.as_ref(),
+                                             Arc::clone(&events));
+
+        // Create threads for the defined tracing purposes.
+        // Keep track of the receiving ends of their channels.
+        let mut trace_kinds = Vec::new();
+        for (kind, uart) in &dut.tracing {
+            let (schannel, rchannel) = mpsc::sync_channel(0);
+            self.launch_tracing_kind(
+                dut,
+                kind.clone(),
+                uart,
+                Arc::clone(test_container),
+                Arc::clone(barrier),
+                schannel);
+            trace_kinds.push(rchannel);
+        }
+
+        DeviceChannels {
+            observer: (watch_thread, observer_rchannel),
+            tracing: (trace_thread, trace_rchannel),
+            memory: (mem_thread, mem_rchannel),
+            trace_kinds,
+            events,
+        }
+    }
+
     fn launch_observer(
         &self,
+        dut: &DeviceUnderTest,
         test_container: Arc<RwLock<Option<Test>>>,
-        barrier: Arc<Barrier>,
-        response_schannel: SyncSender<Option<Response>>,
+        barrier: Arc<Rendezvous>,
+        response_schannel: SyncSender<Collected<Observed>>,
+        events: Arc<DeviceEvents>,
     ) -> JoinHandle<()> {
-        let mut outputs = self.pin_mapping.get_gpio_outputs()
+        let mut outputs = dut.pin_mapping.get_gpio_outputs()
             .expect("Could not obtain GPIO outputs from observer thread.");
+        let backend = dut.pin_mapping.get_backend();
+        let device = dut.name.clone();
+        let name = collector("observer", &device);
 
         thread::Builder::new()
-            .name("test-observer".to_string())
+            .name(name.clone())
             .spawn(move || {
+                let _member = barrier.member(&name);
                 println!("observer: started.");
 
+                let mut initial_levels = Vec::new();
                 let mut responses = Vec::new();
                 responses.reserve(1000);
+                let mut step = 0;
                 loop {
                     // wait for next test
-                    barrier.wait();
+                    if barrier.wait(&name, &mut step, None).is_err() {
+                        continue;
+                    }
 
                     // set up to watch for responses according to criteria
-                    if let Some(ref test) = *test_container.read().unwrap() {
-                        let interrupt_pin_nos = test.prep_observe(&mut outputs)
-                            .unwrap(); // <-- communicate back?
-                        let interrupt_pins = interrupt_pin_nos.into_iter()
-                            .map(|pin_no| outputs.get_pin(pin_no).unwrap())
-                            .collect();
-
-                        // wait for test to begin
-                        println!("observer: ready to begin test");
-                        barrier.wait();
+                    // (cloned so a hung test does not hold the lock)
+                    let test = match test_container.read().unwrap().clone() {
+                        Some(test) => test,
+                        // no more tests to run
+                        None => break,
+                    };
+                    // Failures are reported with the data, once the test is over.
+                    let mut failure = None;
+                    let interrupt_pin_nos = test.prep_observe(&device, &mut outputs)
+                        .unwrap_or_else(|e| {
+                            failure = Some(e);
+                            Vec::new()
+                        });
+                    let interrupt_pins = interrupt_pin_nos.into_iter()
+                        .map(|pin_no| outputs.get_pin(pin_no).unwrap().as_ref())
+                        .collect::<Vec<_>>();
+
+                    // wait for test to begin
+                    println!("observer: ready to begin test");
+                    let started = barrier.wait(&name, &mut step, None).is_ok();
+                    if started && failure.is_none() {
                         println!("observer: starting watch");
 
                         let t0 = Instant::now();
-                        test.observe(t0, &interrupt_pins, &mut responses)
-                            .unwrap();
-
-                        barrier.wait();
+                        failure = test.observe(t0, backend.as_ref(), &interrupt_pins, &mut initial_levels, &mut responses, &events)
+                            .err();
+                    }
+                    let finished = started && barrier.wait(&name, &mut step, None).is_ok();
 
-                        println!("observer: cleaning up interrupts");
-                        for pin in &mut outputs {
-                            pin.clear_interrupt().unwrap();
+                    println!("observer: cleaning up interrupts");
+                    for pin in &mut outputs {
+                        if let Err(e) = pin.clear_interrupt() {
+                            failure.get_or_insert(e.into());
                         }
+                    }
 
-                        for r in responses.drain(..) {
-                            response_schannel.send(Some(r)).unwrap();
-                        }
-                        response_schannel.send(None).unwrap();
-                    } else {
-                        // no more tests to run
-                        break;
+                    if !finished || failure.is_some() {
+                        initial_levels.clear();
+                        responses.clear();
+                    }
+                    if !finished {
+                        // the executor gave up on the test
+                        continue;
                     }
+                    if let Some(e) = failure {
+                        response_schannel.send(Collected::Failed(e)).unwrap();
+                        continue;
+                    }
+                    for level in initial_levels.drain(..) {
+                        response_schannel.send(Collected::Data(Observed::Initial(level))).unwrap();
+                    }
+                    for r in responses.drain(..) {
+                        response_schannel.send(Collected::Data(Observed::Change(r))).unwrap();
+                    }
+                    response_schannel.send(Collected::Done).unwrap();
                 }
 
                 println!("observer: exiting");
@@ -421,16 +1277,18 @@ impl Testbed {
     fn launch_metering(
         &self,
         test_container: Arc<RwLock<Option<Test>>>,
-        barrier: Arc<Barrier>,
-        energy_schannel: SyncSender<Option<(String, (Instant, f32))>>,
+        barrier: Arc<Rendezvous>,
+        energy_schannel: SyncSender<Collected<(String, (Instant, f32))>>,
     ) -> JoinHandle<()> {
         println!("Starting energy metering thread.");
 
+        const NAME: &str = METERING;
         let meters = Arc::clone(&self.energy_meters);
 
         thread::Builder::new()
-            .name("test-metering".to_string())
+            .name(NAME.to_string())
             .spawn(move || {
+                let _member = barrier.member(NAME);
                 println!("metering: started.");
 
                 let meters = meters.lock().unwrap();
@@ -438,96 +1296,212 @@ impl Testbed {
                     .map(|meter_id| { (meter_id.clone(), Vec::new()) })
                     .collect();
 
+                let mut step = 0;
                 loop {
                     // wait for next test
-                    barrier.wait();
-
-                    if let Some(ref test) = *test_container.read().unwrap() {
-                        // here, better error management across threads would be nice!
-                        let need_metering = test.prep_meter(&meters, &mut samples).unwrap();
-                        if !need_metering {
-                            println!("metering: idling; not needed for this test");
-                            barrier.wait();
-                        } else {
-                            // wait for test to begin
-                            println!("metering: ready to begin test");
-                            barrier.wait();
-
-                            test.meter(&meters, &mut samples);
+                    if barrier.wait(NAME, &mut step, None).is_err() {
+                        continue;
+                    }
+
+                    let test = match test_container.read().unwrap().clone() {
+                        Some(test) => test,
+                        // no more tests to run
+                        None => break,
+                    };
+                    // Failures are reported with the data, once the test is over.
+                    let mut failure = None;
+                    let need_metering = test.prep_meter(&meters, &mut samples)
+                        .unwrap_or_else(|e| {
+                            failure = Some(e);
+                            false
+                        });
+                    if !need_metering {
+                        println!("metering: idling; not needed for this test");
+                        if barrier.wait(NAME, &mut step, None).is_err() {
+                            continue;
                         }
                     } else {
-                        // no more tests to run
-                        break;
+                        // wait for test to begin
+                        println!("metering: ready to begin test");
+                        if barrier.wait(NAME, &mut step, None).is_err() {
+                            continue;
+                        }
+
+                        test.meter(&meters, &mut samples);
                     }
 
-                    barrier.wait();
+                    if barrier.wait(NAME, &mut step, None).is_err() {
+                        continue;
+                    }
 
                     // communicate results back
+                    if let Some(e) = failure {
+                        energy_schannel.send(Collected::Failed(e)).unwrap();
+                        continue;
+                    }
                     for (meter_id, samples) in &samples {
                         for sample in samples {
                             // .to_string()... kinda wasteful, but it works;
                             // perhaps better comm. types wanted?
-                            let message = Some((meter_id.to_string(), *sample));
+                            let message = Collected::Data((meter_id.to_string(), *sample));
                             energy_schannel.send(message).unwrap();
                         }
                     }
-                    energy_schannel.send(None).unwrap(); // done communicating results
+                    energy_schannel.send(Collected::Done).unwrap(); // done communicating results
                 }
             })
             .expect("Could not spawn metering thread.")
     }
 
+    fn launch_sampling(
+        &self,
+        test_container: Arc<RwLock<Option<Test>>>,
+        barrier: Arc<Rendezvous>,
+        analog_schannel: SyncSender<Collected<(u8, (Instant, f32))>>,
+    ) -> JoinHandle<()> {
+        println!("Starting analog sampling thread.");
+
+        const NAME: &str = SAMPLING;
+        let inputs = Arc::clone(&self.analog_inputs);
+
+        thread::Builder::new()
+            .name(NAME.to_string())
+            .spawn(move || {
+                let _member = barrier.member(NAME);
+                println!("sampling: started.");
+
+                let inputs = inputs.lock().unwrap();
+                let mut samples: HashMap<u8, Vec<(Instant, f32)>> = HashMap::new();
+
+                let mut step = 0;
+                loop {
+                    // wait for next test
+                    if barrier.wait(NAME, &mut step, None).is_err() {
+                        continue;
+                    }
+
+                    let test = match test_container.read().unwrap().clone() {
+                        Some(test) => test,
+                        // no more tests to run
+                        None => break,
+                    };
+                    // Failures are reported with the data, once the test is over.
+                    let mut failure = None;
+                    let need_sampling = test.prep_sample(&inputs, &mut samples)
+                        .unwrap_or_else(|e| {
+                            failure = Some(e);
+                            false
+                        });
+                    if !need_sampling {
+                        println!("sampling: idling; not needed for this test");
+                        if barrier.wait(NAME, &mut step, None).is_err() {
+                            continue;
+                        }
+                    } else {
+                        // wait for test to begin
+                        println!("sampling: ready to begin test");
+                        if barrier.wait(NAME, &mut step, None).is_err() {
+                            continue;
+                        }
+
+                        failure = test.sample(&inputs, &mut samples).err();
+                    }
+
+                    if barrier.wait(NAME, &mut step, None).is_err() {
+                        continue;
+                    }
+
+                    // communicate results back
+                    if let Some(e) = failure {
+                        analog_schannel.send(Collected::Failed(e)).unwrap();
+                        continue;
+                    }
+                    for (pin_no, samples) in &samples {
+                        for sample in samples {
+                            analog_schannel.send(Collected::Data((*pin_no, *sample))).unwrap();
+                        }
+                    }
+                    analog_schannel.send(Collected::Done).unwrap(); // done communicating results
+                }
+            })
+            .expect("Could not spawn sampling thread.")
+    }
+
     fn launch_tracing(
         &self,
+        dut: &DeviceUnderTest,
         test_container: Arc<RwLock<Option<Test>>>,
-        barrier: Arc<Barrier>,
-        trace_schannel: SyncSender<Option<SerialTrace>>,
+        barrier: Arc<Rendezvous>,
+        trace_schannel: SyncSender<Collected<SerialTrace>>,
         uart: Option<&UART>,
+        events: Arc<DeviceEvents>,
     ) -> JoinHandle<()> {
+        let name = collector("stracing", &dut.name);
 
         if let Some(uart) = uart {
             println!("Starting tracing thread.");
-            let uart = self.pin_mapping.get_uart(uart)
+            let mut port = dut.pin_mapping.get_uart(uart)
                 .expect("Could not obtain UART from tracing thread.");
+            // Tracing notices the end of a test sooner than other collectors, unless told otherwise.
+            if !dut.pin_mapping.get_serial_config(uart).has_read_timeout() {
+                port.set_read_timeout(TRACING_READ_TIMEOUT)
+                    .expect("Could not set the read timeout of the tracing UART.");
+            }
+            let uart = port;
 
             thread::Builder::new()
-                .name("test-stracing".to_string())
+                .name(name.clone())
                 .spawn(move || {
+                    let _member = barrier.member(&name);
                     println!("stracing: started.");
 
                     let mut uart = uart;
                     let mut buffer: Vec<u8> = Vec::new();
                     let mut schedule: Vec<(Instant, usize)> = Vec::new();
-                    let mut bytes_rx;
 
+                    let mut step = 0;
                     loop {
                         // wait for next test
-                        barrier.wait();
+                        if barrier.wait(&name, &mut step, None).is_err() {
+                            continue;
+                        }
 
-                        if let Some(ref test) = *test_container.read().unwrap() {
-                            test.prep_tracing(&mut uart, &mut buffer, &mut schedule).unwrap();
+                        let test = match test_container.read().unwrap().clone() {
+                            Some(test) => test,
+                            // no more tests to run
+                            None => break,
+                        };
+                        // Failures are reported with the data, once the test is over.
+                        let mut failure = test.prep_tracing(uart.as_mut(), &mut buffer, &mut schedule).err();
 
-                            barrier.wait();
-                            bytes_rx = test.trace(
-                                &mut uart,
-                                &mut buffer,
-                                &mut schedule).unwrap();
+                        if barrier.wait(&name, &mut step, None).is_err() {
+                            continue;
+                        }
+                        let mut bytes_rx = 0;
+                        if failure.is_none() {
+                            match test.trace(uart.as_mut(), &mut buffer, &mut schedule, &events) {
+                                Ok(count) => bytes_rx = count,
+                                Err(e) => failure = Some(e),
+                            }
                             println!("stracing: received {} bytes over UART", bytes_rx);
-                        } else {
-                            // no more tests to run
-                            break;
                         }
 
-                        barrier.wait();
+                        if barrier.wait(&name, &mut step, None).is_err() {
+                            continue;
+                        }
 
+                        // communicate results back
+                        if let Some(e) = failure {
+                            trace_schannel.send(Collected::Failed(e)).unwrap();
+                            continue;
+                        }
                         let serial_traces = trace::reconstruct_serial(
                             &buffer.as_slice()[0..bytes_rx],
                             &schedule);
-                        // communicate results back
                         for trace in serial_traces {
-                            trace_schannel.send(Some(trace)).unwrap();
+                            trace_schannel.send(Collected::Data(trace)).unwrap();
                         }
-                        trace_schannel.send(None).unwrap(); // done communicating results
+                        trace_schannel.send(Collected::Done).unwrap(); // done communicating results
                     }
                 })
                 .expect("Could not spawn tracing thread.")
@@ -535,21 +1509,12 @@ impl Testbed {
             println!("No UART for serial tracing; will idle.");
 
             thread::Builder::new()
-                .name("test-stracing".to_string())
+                .name(name.clone())
                 .spawn(move || {
-
-                    loop {
-                        // wait for next test
-                        barrier.wait();
-                        if let Some(ref _test) = *test_container.read().unwrap() {
-                            barrier.wait();
-                        } else {
-                            // no more tests to run
-                            break;
-                        }
-                        barrier.wait();
-                        trace_schannel.send(None).unwrap(); // done communicating results
-                    }
+                    let _member = barrier.member(&name);
+                    idle(&name, &barrier, &test_container, || {
+                        trace_schannel.send(Collected::Done).unwrap(); // done communicating results
+                    });
                 })
                 .expect("Could not spawn tracing thread.")
         }
@@ -557,54 +1522,69 @@ impl Testbed {
 
     fn launch_memstat(
         &self,
+        dut: &DeviceUnderTest,
         test_container: Arc<RwLock<Option<Test>>>,
-        barrier: Arc<Barrier>,
-        mem_schannel: SyncSender<Option<MemoryTrace>>,
+        barrier: Arc<Rendezvous>,
+        mem_schannel: SyncSender<Collected<MemoryTrace>>,
         uart: Option<&UART>,
+        events: Arc<DeviceEvents>,
     ) -> JoinHandle<()> {
+        let name = collector("memtrack", &dut.name);
+
         if let Some(uart) = uart {
             println!("Starting memory tracking thread.");
-            let uart = self.pin_mapping.get_uart(uart)
+            let uart = dut.pin_mapping.get_uart(uart)
                 .expect("Could not obtain UART from tracing thread.");
 
             thread::Builder::new()
-                .name("test-memtrack".to_string())
+                .name(name.clone())
                 .spawn(move || {
+                    let _member = barrier.member(&name);
                     println!("memtrack: started.");
 
                     let mut uart = uart;
                     let mut buffer: Vec<u8> = Vec::new();
                     let mut schedule: Vec<MemoryTrace> = Vec::new();
-                    let mut bytes_remaining;
 
+                    let mut step = 0;
                     loop {
                         // wait for next test
-                        barrier.wait();
-
-                        if let Some(ref test) = *test_container.read().unwrap() {
-                            test.prep_memtrack(&mut uart, &mut buffer, &mut schedule).unwrap();
-
-                            barrier.wait();
-                            bytes_remaining = test.memtrack(
-                                &mut uart,
-                                &mut buffer,
-                                &mut schedule).unwrap();
-                            if bytes_remaining > 0 {
-                                println!("memtrack: {} bytes of unprocessed data!", bytes_remaining);
-                            } else {
-                                println!("memtrack: all data processed");
-                            }
-                        } else {
+                        if barrier.wait(&name, &mut step, None).is_err() {
+                            continue;
+                        }
+
+                        let test = match test_container.read().unwrap().clone() {
+                            Some(test) => test,
                             // no more tests to run
-                            break;
+                            None => break,
+                        };
+                        // Failures are reported with the data, once the test is over.
+                        let mut failure = test.prep_memtrack(uart.as_mut(), &mut buffer, &mut schedule).err();
+
+                        if barrier.wait(&name, &mut step, None).is_err() {
+                            continue;
+                        }
+                        if failure.is_none() {
+                            match test.memtrack(uart.as_mut(), &mut buffer, &mut schedule, &events) {
+                                Ok(0) => println!("memtrack: all data processed"),
+                                Ok(bytes_remaining) =>
+                                    println!("memtrack: {} bytes of unprocessed data!", bytes_remaining),
+                                Err(e) => failure = Some(e),
+                            }
                         }
 
-                        barrier.wait();
+                        if barrier.wait(&name, &mut step, None).is_err() {
+                            continue;
+                        }
 
+                        if let Some(e) = failure {
+                            mem_schannel.send(Collected::Failed(e)).unwrap();
+                            continue;
+                        }
                         for mem_event in &schedule {
-                            mem_schannel.send(Some(mem_event.clone())).unwrap();
+                            mem_schannel.send(Collected::Data(mem_event.clone())).unwrap();
                         }
-                        mem_schannel.send(None).unwrap(); // done communicating results
+                        mem_schannel.send(Collected::Done).unwrap(); // done communicating results
                     }
                 })
                 .expect("Could not spawn memory tracking thread.")
@@ -612,21 +1592,12 @@ impl Testbed {
             println!("No UART for memory tracking; will idle.");
 
             thread::Builder::new()
-                .name("test-memtrack".to_string())
+                .name(name.clone())
                 .spawn(move || {
-
-                    loop {
-                        // wait for next test
-                        barrier.wait();
-                        if let Some(ref _test) = *test_container.read().unwrap() {
-                            barrier.wait();
-                        } else {
-                            // no more tests to run
-                            break;
-                        }
-                        barrier.wait();
-                        mem_schannel.send(None).unwrap(); // done communicating results
-                    }
+                    let _member = barrier.member(&name);
+                    idle(&name, &barrier, &test_container, || {
+                        mem_schannel.send(Collected::Done).unwrap(); // done communicating results
+                    });
                 })
                 .expect("Could not spawn tracing thread.")
         }
@@ -634,74 +1605,96 @@ impl Testbed {
 
     fn launch_tracing_kind(
         &self,
+        dut: &DeviceUnderTest,
         kind: TraceKind,
         uart: &UART,
         test_container: Arc<RwLock<Option<Test>>>,
-        barrier: Arc<Barrier>,
-        schannel: SyncSender<Option<TraceData>>,
+        barrier: Arc<Rendezvous>,
+        schannel: SyncSender<Collected<Option<TraceData>>>,
     ) -> JoinHandle<()> {
-        let name = format!("test-{}", kind);
-        let uart = self.pin_mapping.get_uart(uart)
+        let name = collector(&kind.to_string(), &dut.name);
+        let uart = dut.pin_mapping.get_uart(uart)
             .expect("Could not obtain UART for tracing.");
 
         thread::Builder::new()
             .name(name.clone())
             .spawn(move || {
+                let _member = barrier.member(&name);
                 println!("trace-{}: starting", &name);
 
                 let mut buffer: Vec<u8> = Vec::new();
                 let mut uart = uart;
-                let mut trace_data;
 
+                let mut step = 0;
                 loop {
                     // Wait for next test.
-                    barrier.wait();
-
-                    if let Some(ref test) = *test_container.read().unwrap() {
-                        // Prepare for testing.
-                        // Break out allocating the space in the buffer prior to actually running testing
-                        // to minimize any jitter between the barrier and the collection starting.
-                        let prepared_buffer = trace::prepare(&mut buffer, &mut uart)
-                            .unwrap();
-
-                        barrier.wait();
-                        let t_stop_at = Instant::now() + test.max_runtime();
-                        trace_data = match trace::collect(&kind, &mut uart, prepared_buffer, t_stop_at) {
-                            Ok(trace_data) => Some(trace_data),
-                            Err(e) => {
-                                println!("trace-{}: tracing for {} failed: {}", name, kind, e);
-                                None
-                            },
-                        };
-                    } else {
+                    if barrier.wait(&name, &mut step, None).is_err() {
+                        continue;
+                    }
+
+                    let test = match test_container.read().unwrap().clone() {
+                        Some(test) => test,
                         // No more tests to run.
-                        break;
+                        None => break,
+                    };
+                    // Prepare for testing.
+                    // Break out allocating the space in the buffer prior to actually running testing
+                    // to minimize any jitter between the barrier and the collection starting.
+                    let prepared_buffer = trace::prepare(&mut buffer, uart.as_mut());
+
+                    if barrier.wait(&name, &mut step, None).is_err() {
+                        continue;
                     }
+                    let trace_data = match prepared_buffer {
+                        Ok(prepared_buffer) => {
+                            let t_stop_at = Instant::now() + test.max_runtime();
+                            match trace::collect(&kind, uart.as_mut(), prepared_buffer, t_stop_at) {
+                                Ok(trace_data) => Ok(Some(trace_data)),
+                                Err(e) => {
+                                    println!("trace-{}: tracing for {} failed: {}", name, kind, e);
+                                    Ok(None)
+                                },
+                            }
+                        },
+                        // Reported once the test is over.
+                        Err(e) => Err(TestingError::from(e)),
+                    };
 
                     // Post-testing wait.
-                    barrier.wait();
+                    if barrier.wait(&name, &mut step, None).is_err() {
+                        continue;
+                    }
 
                     // Send data back.
-                    schannel.send(trace_data).expect("failed to send trace data to main thread");
+                    match trace_data {
+                        Ok(trace_data) => {
+                            schannel.send(Collected::Data(trace_data)).expect("failed to send trace data to main thread");
+                            schannel.send(Collected::Done).expect("failed to send trace data to main thread");
+                        },
+                        Err(e) => schannel.send(Collected::Failed(e)).expect("failed to send trace data to main thread"),
+                    }
                 }
             }).expect("Could not spawn tracing thread.")
     }
 
     /// Load specified applications onto the device.
-    fn load_apps(&self, test: &Test) -> Result<()> {
-        println!("executor: loading/unloading {} software", self.platform_support.platform());
-        let currently_loaded = self.platform_support.loaded_software();
+    fn load_apps(&self, dut: &DeviceUnderTest, test: &Test) -> Result<()> {
+        println!("executor: loading/unloading {} software on '{}'", dut.platform_support.platform(), dut.name);
+        let app_ids = test.get_device_app_ids(&dut.name)
+            .cloned()
+            .unwrap_or_default();
+        let currently_loaded = dut.platform_support.loaded_software();
         for app_id in &currently_loaded {
-            if !test.get_app_ids().contains(app_id) {
+            if !app_ids.contains(app_id) {
                 println!("executor: removing '{}'", app_id);
-                self.platform_support.unload(app_id)?;
+                dut.platform_support.unload(app_id)?;
             }
         }
 
-        for app_name in test.get_app_ids() {
+        for app_name in &app_ids {
             if !currently_loaded.contains(app_name) {
                 println!("executor: loading '{}'", app_name);
-                self.platform_support.load(app_name)
+                dut.platform_support.load(app_name)
                     .map_err(|e| TestbedError::Software(e))?;
             }
         }
@@ -710,9 +1703,35 @@ impl Testbed {
     }
 }
 
+/// Meet the other threads at every step of each test without collecting data, until testing is over.
+fn idle<F>(name: &str, barrier: &Rendezvous, test_container: &RwLock<Option<Test>>, mut finish: F)
+where
+    F: FnMut(),
+{
+    let mut step = 0;
+    loop {
+        // wait for next test
+        if barrier.wait(name, &mut step, None).is_err() {
+            continue;
+        }
+        if test_container.read().unwrap().is_none() {
+            // no more tests to run
+            break;
+        }
+        if barrier.wait(name, &mut step, None).is_err()
+            || barrier.wait(name, &mut step, None).is_err() {
+            continue;
+        }
+        finish();
+    }
+}
+
 impl Display for Testbed {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Testbed\n{}", self.pin_mapping)?;
+        writeln!(f, "Testbed")?;
+        for dut in &self.devices {
+            write!(f, "Device '{}'\n{}", dut.name, dut.pin_mapping)?;
+        }
 
         write!(f, "\nEnergy meters:\n")?;
         if let Ok(meters) = self.energy_meters.lock() {
@@ -727,56 +1746,125 @@ impl Display for Testbed {
     }
 }
 
-/// Aggregated collection of test execution data.
+/// Data observed from a single device under test.
 #[derive(Debug)]
-pub struct Observation<'a> {
-    test: Test,
+pub struct DeviceObservation<'a> {
+    name: String,
     software_spec: Option<Spec>,
-    execution_result: Result<Execution>,
+    reset_strategy: Option<ResetStrategy>,
+    initial_levels: Vec<Response>,
     gpio_responses: Vec<Response>,
     traces: Vec<SerialTrace>,
     trace_info: Vec<&'a TraceKind>,
     trace_data: Vec<Option<TraceData>>,
+}
+
+impl<'a> DeviceObservation<'a> {
+    fn new(name: &str) -> DeviceObservation<'a> {
+        DeviceObservation {
+            name: name.to_string(),
+            software_spec: None,
+            reset_strategy: None,
+            initial_levels: Vec::new(),
+            gpio_responses: Vec::new(),
+            traces: Vec::new(),
+            trace_info: Vec::new(),
+            trace_data: Vec::new(),
+        }
+    }
+
+    /// Return the name of the device.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the software configuration used on the device for the test.
+    pub fn software_config(&self) -> Option<&Spec> {
+        self.software_spec.as_ref()
+    }
+
+    /// Return how the device was reset for the test, if it was.
+    pub fn reset_strategy(&self) -> Option<&ResetStrategy> {
+        self.reset_strategy.as_ref()
+    }
+
+    /// Return GPIO state changes observed on the device during the test.
+    pub fn gpio_responses(&self) -> &Vec<Response> {
+        &self.gpio_responses
+    }
+
+    /// Return the levels the watched device pins were at when the test started.
+    pub fn initial_levels(&self) -> &Vec<Response> {
+        &self.initial_levels
+    }
+
+    /** Return the level of a watched device pin at `t`.
+
+    The level is that of the last change at or before `t`,
+    or the level the pin started the test at if it had not changed yet.
+    Returns `None` for pins that were not watched.
+     */
+    pub fn level_at(&self, pin_no: u8, t: Instant) -> Option<Level> {
+        self.gpio_responses.iter()
+            .rev()
+            .find(|response| response.get_pin() == pin_no && response.get_time() <= t)
+            .or_else(|| self.initial_levels.iter().find(|level| level.get_pin() == pin_no))
+            .and_then(Response::get_level)
+    }
+
+    /// Return the traces received from the device during the test.
+    pub fn traces(&self) -> &Vec<SerialTrace> {
+        &self.traces
+    }
+}
+
+/** Aggregated collection of test execution data.
+
+Data from each device under test is kept separately in a [`DeviceObservation`].
+Accessors for device data without a device name return the [default device](DEFAULT_DEVICE)'s data.
+ */
+#[derive(Debug)]
+pub struct Observation<'a> {
+    test: Test,
+    repetition: u32,
+    execution_result: Result<Execution>,
+    devices: Vec<DeviceObservation<'a>>,
     energy_metrics: HashMap<String, Vec<(Instant, f32)>>,
+    analog_samples: HashMap<u8, Vec<(Instant, f32)>>,
 }
 
 impl<'a> Observation<'a> {
     fn completed(
         test: Test,
-        software_spec: Option<Spec>,
+        repetition: u32,
         execution_result: Result<Execution>,
-        gpio_responses: Vec<Response>,
-        traces: Vec<SerialTrace>,
-        trace_info: Vec<&'a TraceKind>,
-        trace_data: Vec<Option<TraceData>>,
-        energy_metrics: HashMap<String, Vec<(Instant, f32)>>
+        devices: Vec<DeviceObservation<'a>>,
+        energy_metrics: HashMap<String, Vec<(Instant, f32)>>,
+        analog_samples: HashMap<u8, Vec<(Instant, f32)>>,
     ) -> Observation<'a> {
         Observation {
             test,
-            software_spec,
+            repetition,
             execution_result,
-            gpio_responses,
-            traces,
-            trace_info,
-            trace_data,
+            devices,
             energy_metrics,
+            analog_samples,
         }
     }
 
     fn failed(
         test: Test,
-        software_spec: Option<Spec>,
+        repetition: u32,
+        devices: Vec<DeviceObservation<'a>>,
         error: TestbedError,
     ) -> Observation<'a> {
         Observation {
             test,
-            software_spec,
+            repetition,
             execution_result: Err(error),
-            gpio_responses: Vec::new(),
-            traces: Vec::new(),
-            trace_info: Vec::new(),
-            trace_data: Vec::new(),
+            devices,
             energy_metrics: HashMap::new(),
+            analog_samples: HashMap::new(),
         }
     }
 
@@ -785,37 +1873,80 @@ impl<'a> Observation<'a> {
         &self.test
     }
 
+    /// Return the parameter values the test was built from.
+    pub fn parameters(&self) -> &Parameters {
+        self.test.get_parameters()
+    }
+
+    /// Return which run of a [repeated](Test::with_repetitions) test the `Observation` is for, counting from 0.
+    pub fn repetition(&self) -> u32 {
+        self.repetition
+    }
+
     /// Return the execution metadata of running the test against the device.
     pub fn execution_result(&self) -> &Result<Execution> {
         &self.execution_result
     }
 
+    /// Return the data observed from each device under test.
+    pub fn devices(&self) -> &[DeviceObservation<'a>] {
+        &self.devices
+    }
+
+    /// Return the data observed from the named device under test.
+    pub fn device(&self, name: &str) -> Option<&DeviceObservation<'a>> {
+        self.devices.iter()
+            .find(|device| device.name == name)
+    }
+
+    fn default_device(&self) -> &DeviceObservation<'a> {
+        self.device(DEFAULT_DEVICE)
+            .expect("default device is not on the testbed")
+    }
+
     /// Return the software configuration used for the test.
     pub fn software_config(&self) -> Option<&Spec> {
-        self.software_spec.as_ref().clone()
+        self.default_device().software_config()
+    }
+
+    /// Return how the device was reset for the test, if it was.
+    pub fn reset_strategy(&self) -> Option<&ResetStrategy> {
+        self.default_device().reset_strategy()
     }
 
     /// Return GPIO state changes observed during the test.
     pub fn gpio_responses(&self) -> &Vec<Response> {
-        &self.gpio_responses
+        self.default_device().gpio_responses()
     }
 
     /// Return the traces received from the device during the test.
     pub fn traces(&self) -> &Vec<SerialTrace> {
-        &self.traces
+        self.default_device().traces()
     }
 
     /// Return data from all energy meters active during the test.
     pub fn energy_metrics(&self) -> &HashMap<String, Vec<(Instant, f32)>> {
         &self.energy_metrics
     }
+
+    /// Return voltage samples (in millivolts) of the analog device pins sampled during the test.
+    pub fn analog_samples(&self) -> &HashMap<u8, Vec<(Instant, f32)>> {
+        &self.analog_samples
+    }
 }
 
 impl<'a> Display for Observation<'a> {
     /// Pretty-print data observed during the test run.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Name of the test.
-        write!(f, "'{}' data\n", self.test.get_id())?;
+        write!(f, "'{}' data", self.test.get_id())?;
+        if self.test.get_repetitions() > 1 {
+            write!(f, " (run {}/{})", self.repetition + 1, self.test.get_repetitions())?;
+        }
+        writeln!(f)?;
+        if !self.test.get_parameters().is_empty() {
+            writeln!(f, "Parameters: {}", self.test.get_parameters())?;
+        }
 
         // How long the test lasted.
         // This is only possible to see if the test completed successfully.
@@ -824,38 +1955,50 @@ impl<'a> Display for Observation<'a> {
             Err(_e) => " - ".to_string(),
         };
         write!(f, "Duration: {}\n", duration_text)?;
+        if let Ok(e) = self.execution_result.as_ref() {
+            writeln!(f, "Operation lateness: max {:?}, mean {:?}", e.max_lateness(), e.mean_lateness())?;
+        }
 
-        // GPIO responses.
-        // We manually calculate the offset here since each Response does not
-        // carry the associated t0 to calculate the duration into the test.
-        write!(f, "--- GPIO timeline\n")?;
-        write!(f, " pin no. |   signal   | time\n")?;
-        for response in &self.gpio_responses {
-            // If execution is in error, there is no start time available.
-            let time = match self.execution_result.as_ref() {
-                Ok(e) => format!("{:?}", response.get_time() - e.get_start()),
-                Err(_e) => " - ".to_string(),
-            };
+        for device in &self.devices {
+            if self.devices.len() > 1 {
+                writeln!(f, "=== Device '{}'", device.name)?;
+            }
+            if let Some(ref strategy) = device.reset_strategy {
+                writeln!(f, "Reset: {}", strategy)?;
+            }
 
-            write!(f, " {:>7} | {:<10} | {}\n",
-                   response.get_pin(),
-                   response.get_output(),
-                   time)?;
-        }
-
-        write!(f, "\n\n")?;
-
-        // Trace data.
-        // There is a one-to-one correspondence between the info about the traces
-        // and the traces themselves.
-        let iter = self.trace_info.iter()
-            .zip(self.trace_data.iter());
-        for (info, data) in iter {
-            write!(f, "--- Traces ({})\n", info.label())?;
-            if let Some(data) = data {
-                write!(f, "{}\n", data.summary(info))?;
-            } else {
-                write!(f, "no data\n")?;
+            // GPIO responses.
+            // We manually calculate the offset here since each Response does not
+            // carry the associated t0 to calculate the duration into the test.
+            write!(f, "--- GPIO timeline\n")?;
+            write!(f, " pin no. |   signal   | time\n")?;
+            for response in &device.gpio_responses {
+                // If execution is in error, there is no start time available.
+                let time = match self.execution_result.as_ref() {
+                    Ok(e) => format!("{:?}", response.get_time() - e.get_start()),
+                    Err(_e) => " - ".to_string(),
+                };
+
+                write!(f, " {:>7} | {:<10} | {}\n",
+                       response.get_pin(),
+                       response.get_output(),
+                       time)?;
+            }
+
+            write!(f, "\n\n")?;
+
+            // Trace data.
+            // There is a one-to-one correspondence between the info about the traces
+            // and the traces themselves.
+            let iter = device.trace_info.iter()
+                .zip(device.trace_data.iter());
+            for (info, data) in iter {
+                write!(f, "--- Traces ({})\n", info.label())?;
+                if let Some(data) = data {
+                    write!(f, "{}\n", data.summary(info))?;
+                } else {
+                    write!(f, "no data\n")?;
+                }
             }
         }
 
@@ -4,9 +4,7 @@ use std::convert::From;
 use std::fmt;
 use std::time::{Duration, Instant};
 
-use rppal::uart;
-use rppal::uart::Uart;
-
+use crate::hal::SerialPort;
 use crate::io;
 
 type Result<T> = std::result::Result<T, String>;
@@ -280,6 +278,16 @@ impl PerformanceData {
             metrics: metrics.into_iter().collect(),
         }
     }
+
+    /// Returns the number of waypoints measured in each period.
+    pub fn waypoints(&self) -> u8 {
+        self.no_waypoints
+    }
+
+    /// Returns the measurements of each period, in the order they were received.
+    pub fn metrics(&self) -> &[PeriodMetric] {
+        &self.metrics
+    }
 }
 
 /// Set of measurements for a set of points taken within the same length of time.
@@ -406,7 +414,7 @@ const SERIAL_BUFFER_SIZE: usize = 64 * 1024;
 pub struct PreparedBuffer<'a>(&'a mut Vec<u8>);
 
 /// Prepare the a buffer and the UART for serial data collection.
-pub fn prepare<'a>(buffer: &'a mut Vec<u8>, uart: &mut Uart) -> io::Result<PreparedBuffer<'a>> {
+pub fn prepare<'a>(buffer: &'a mut Vec<u8>, uart: &mut dyn SerialPort) -> io::Result<PreparedBuffer<'a>> {
     buffer.clear();
     // Just use a constant size for now.
     // We have to push data into the buffer to make it possible to
@@ -414,14 +422,13 @@ pub fn prepare<'a>(buffer: &'a mut Vec<u8>, uart: &mut Uart) -> io::Result<Prepa
     buffer.reserve(SERIAL_BUFFER_SIZE);
     while buffer.len() < SERIAL_BUFFER_SIZE { buffer.push(0); }
 
-    uart.set_read_mode(0, Duration::from_millis(100))?;
-    uart.flush(uart::Queue::Input)?;
+    uart.flush_input()?;
 
     Ok(PreparedBuffer(buffer))
 }
 
 /// Collect tracing data from the given UART.
-pub fn collect(kind: &TraceKind, uart: &mut Uart, buffer: PreparedBuffer, until: Instant) -> Result<TraceData> {
+pub fn collect(kind: &TraceKind, uart: &mut dyn SerialPort, buffer: PreparedBuffer, until: Instant) -> Result<TraceData> {
     // Collecting data for each trace kind is the same.
     // We are just reading bytes from the chosen serial line.
 
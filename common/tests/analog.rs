@@ -0,0 +1,356 @@
+//! Driving analog inputs of the device under test.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use clockwise_common::comm::{Class as SignalClass, Direction, Signal};
+use clockwise_common::criteria::{AnalogCriterion, AnalogStat, Criterion, Crossing, GPIOCriterion, Latency};
+use clockwise_common::evaluation::{Evaluator, StandardEvaluator, Status};
+use clockwise_common::facility::{AnalogInput, AnalogOutput};
+use clockwise_common::hal::{I2cBus, SharedI2cBus, SimulatedDevice, Trigger};
+use clockwise_common::hal::sim::Effect;
+use clockwise_common::hw::{ADS1115, MCP4725};
+use clockwise_common::io::{self, Device, Mapping};
+use clockwise_common::test::{Operation, Test, TestingError};
+use clockwise_common::testbed::{Testbed, TestbedError};
+
+mod support;
+
+use support::NoPlatform;
+
+/// Bytes written to an I2C bus and the address each went to.
+type Writes = Arc<Mutex<Vec<(u16, Vec<u8>)>>>;
+
+/// I2C bus that records what is written to which address and answers register reads with `reply`.
+#[derive(Debug)]
+struct RecordingBus {
+    address: u16,
+    writes: Writes,
+    reply: [u8; 2],
+}
+
+impl RecordingBus {
+    fn shared(writes: &Writes, reply: [u8; 2]) -> SharedI2cBus {
+        SharedI2cBus::new(Box::new(RecordingBus {
+            address: 0,
+            writes: Arc::clone(writes),
+            reply,
+        }))
+    }
+}
+
+impl I2cBus for RecordingBus {
+    fn set_slave_address(&mut self, address: u16) -> io::Result<()> {
+        self.address = address;
+        Ok(())
+    }
+
+    fn read(&mut self, _buffer: &mut [u8]) -> io::Result<usize> {
+        Ok(0)
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.writes.lock().unwrap().push((self.address, buffer.to_vec()));
+        Ok(buffer.len())
+    }
+
+    fn write_read(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> io::Result<()> {
+        self.writes.lock().unwrap().push((self.address, write_buffer.to_vec()));
+        read_buffer.copy_from_slice(&self.reply);
+        Ok(())
+    }
+}
+
+/// Analog output that records the voltages it is asked for.
+#[derive(Debug)]
+struct RecordingOutput(Arc<Mutex<Vec<(Instant, u32)>>>);
+
+impl AnalogOutput for RecordingOutput {
+    fn max_voltage(&self) -> u32 {
+        3300
+    }
+
+    fn set_voltage(&mut self, millivolts: u32) -> Result<(), String> {
+        self.0.lock().unwrap().push((Instant::now(), millivolts));
+        Ok(())
+    }
+}
+
+/// Analog input that follows a first-order step response from 0 to 3000mV with a 10ms time constant.
+#[derive(Debug)]
+struct StepResponse(Instant);
+
+impl AnalogInput for StepResponse {
+    fn voltage(&self) -> Result<f32, String> {
+        let t = self.0.elapsed().as_secs_f32() * 1000.0;
+        Ok(3000.0 * (1.0 - (-t / 10.0).exp()))
+    }
+}
+
+/// Analog input whose converter has stopped answering.
+#[derive(Debug)]
+struct Unresponsive;
+
+impl AnalogInput for Unresponsive {
+    fn voltage(&self) -> Result<f32, String> {
+        Err("no acknowledge".to_string())
+    }
+}
+
+/// Analog input whose driver panics.
+#[derive(Debug)]
+struct Crashing;
+
+impl AnalogInput for Crashing {
+    fn voltage(&self) -> Result<f32, String> {
+        panic!("converter driver bug");
+    }
+}
+
+#[test]
+fn mcp4725_codes() {
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    let bus = RecordingBus::shared(&writes, [0, 0]);
+
+    let mut dac = MCP4725::new(&bus, 0x62, 3300).unwrap();
+    dac.set_voltage(1650).unwrap();
+    dac.set_voltage(5000).unwrap();
+
+    assert_eq!(*writes.lock().unwrap(), vec![
+        (0x62, vec![0x00, 0x00]),
+        (0x62, vec![0x08, 0x00]),
+        // Clamped to full scale.
+        (0x62, vec![0x0F, 0xFF]),
+    ]);
+}
+
+#[test]
+fn analog_inputs_drive_outputs() {
+    let device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (5, (Direction::In, SignalClass::Analog)),
+    ]);
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+
+    let driven = Arc::new(Mutex::new(Vec::new()));
+    let output: Box<dyn AnalogOutput> = Box::new(RecordingOutput(Arc::clone(&driven)));
+    let testbed = Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new())
+        .with_analog_outputs(vec![(5, output)]);
+
+    let tests = vec![
+        Test::new(
+            "ramp",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).input(Signal::Analog(1200), 5),
+              Operation::at(20).input(Signal::Analog(2400), 5)],
+            Vec::new(),
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    assert!(observations[0].execution_result().is_ok());
+
+    let driven = driven.lock().unwrap();
+    let levels: Vec<u32> = driven.iter().map(|(_t, mv)| *mv).collect();
+    assert_eq!(levels, vec![1200, 2400]);
+    let gap = driven[1].0 - driven[0].0;
+    assert!(gap >= Duration::from_millis(9) && gap < Duration::from_millis(15), "{:?}", gap);
+}
+
+#[test]
+fn ads1115_conversion() {
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    // Conversion finished; 0x2000 is 1024mV.
+    let bus = RecordingBus::shared(&writes, [0x80 | 0x20, 0x00]);
+
+    let adc = ADS1115::new(&bus, 0x48).unwrap();
+    assert!(adc.channel(4).is_err());
+    let ain2 = adc.channel(2).unwrap();
+    assert_eq!(ain2.convert().unwrap(), (0xA000u16 as i16) as f32 * 0.125);
+
+    let writes = writes.lock().unwrap();
+    // Single-shot conversion of AIN2 at ±4.096V, 860SPS.
+    assert_eq!(writes[0], (0x48, vec![0x01, 0xE3, 0xE3]));
+    assert_eq!(writes.last().unwrap(), &(0x48, vec![0x00]));
+}
+
+#[test]
+fn ads1115_gives_up_on_a_stuck_conversion() {
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    // The conversion never reports being finished.
+    let bus = RecordingBus::shared(&writes, [0x00, 0x00]);
+
+    let adc = ADS1115::new(&bus, 0x48).unwrap();
+    assert!(adc.channel(0).unwrap().voltage().is_err());
+}
+
+#[test]
+fn peripherals_share_an_i2c_bus() {
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    let bus = RecordingBus::shared(&writes, [0x80, 0x00]);
+
+    let mut low = MCP4725::new(&bus, 0x62, 3300).unwrap();
+    let mut high = MCP4725::new(&bus, 0x63, 3300).unwrap();
+    let adc = ADS1115::new(&bus, 0x48).unwrap();
+    low.set_voltage(3300).unwrap();
+    adc.channel(0).unwrap().convert().unwrap();
+    high.set_voltage(0).unwrap();
+
+    let addresses: Vec<u16> = writes.lock().unwrap().iter()
+        .map(|(address, _data)| *address)
+        .collect();
+    assert_eq!(addresses, vec![0x62, 0x63, 0x62, 0x48, 0x48, 0x48, 0x63]);
+}
+
+#[test]
+fn analog_inputs_are_not_latency_stimuli() {
+    let ms = Duration::from_millis;
+    let device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (14, (Direction::Out, SignalClass::Digital)),
+    ]);
+    let mapping = Mapping::new(device, &[(20, 13), (21, 14)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()
+            .when(20, Trigger::RisingEdge, ms(5), Effect::Toggle(21))));
+
+    let output: Box<dyn AnalogOutput> = Box::new(RecordingOutput(Arc::new(Mutex::new(Vec::new()))));
+    let testbed = Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new())
+        .with_analog_outputs(vec![(13, output)]);
+
+    // The same pin is driven to an analog voltage around a digital edge.
+    let tests = vec![
+        Test::new(
+            "mixed",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).input(Signal::Analog(1200), 13),
+              Operation::at(20).input(Signal::Digital(true), 13),
+              Operation::at(40).input(Signal::Analog(0), 13),
+              Operation::at(50).idle_sync(ms(20))],
+            &[Criterion::GPIO(GPIOCriterion::Latency(Latency::new(13, 14, ms(2), ms(15))))],
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let evaluation = StandardEvaluator::new().evaluate(&observations[0]);
+    let outcome = &evaluation.outcomes()[0];
+    assert_eq!(outcome.status(), Status::Pass, "{:?}", outcome.message());
+    assert_eq!(outcome.values().len(), 1);
+}
+
+#[test]
+fn analog_outputs_are_sampled() {
+    let device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (7, (Direction::Out, SignalClass::Analog)),
+    ]);
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+
+    let input: Box<dyn AnalogInput> = Box::new(StepResponse(Instant::now()));
+    let testbed = Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new())
+        .with_analog_inputs(vec![(7, input)]);
+
+    let tests = vec![
+        Test::new(
+            "settle",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).idle_sync(Duration::from_millis(100))],
+            &[Criterion::Analog(AnalogCriterion::new(7, AnalogStat::Max).with_max(3000.0)),
+              Criterion::Analog(AnalogCriterion::new(7, AnalogStat::Min).with_max(1500.0)),
+              Criterion::Analog(AnalogCriterion::new(7, AnalogStat::Crossings(1500.0, Crossing::Rising))
+                                .with_min(1.0)
+                                .with_max(1.0)),
+              Criterion::Analog(AnalogCriterion::new(7, AnalogStat::Crossings(1500.0, Crossing::Falling))
+                                .with_max(0.0)),
+              // Within 5% after three time constants.
+              Criterion::Analog(AnalogCriterion::new(7, AnalogStat::SettlingTime(3000.0, 150.0))
+                                .with_max(80.0))],
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let samples = &observations[0].analog_samples()[&7];
+    assert!(samples.len() > 10);
+    let evaluation = StandardEvaluator::new().evaluate(&observations[0]);
+    for outcome in evaluation.outcomes() {
+        assert_eq!(outcome.status(), Status::Pass, "{} ({:?})",
+                   outcome.source_criterion(), outcome.message());
+    }
+}
+
+#[test]
+fn sampling_failures_fail_the_run() {
+    let device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (7, (Direction::Out, SignalClass::Analog)),
+        (8, (Direction::Out, SignalClass::Analog)),
+    ]);
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+
+    let input: Box<dyn AnalogInput> = Box::new(Unresponsive);
+    let testbed = Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new())
+        .with_analog_inputs(vec![(7, input)]);
+
+    let idle = [Operation::at(0).idle_sync(Duration::from_millis(10))];
+    let sampled = |pin_no| vec![Criterion::Analog(AnalogCriterion::new(pin_no, AnalogStat::Max))];
+    let tests = vec![
+        Test::new("unresponsive", Vec::new(), Vec::new(), &idle, &sampled(7), false),
+        // Nothing samples pin 8.
+        Test::new("unwired", Vec::new(), Vec::new(), &idle, &sampled(8), false),
+        Test::new("unsampled", Vec::new(), Vec::new(), &idle, Vec::new(), false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    assert!(matches!(observations[0].execution_result(),
+                     Err(TestbedError::Collection(_, TestingError::Sampling(7, _)))),
+            "{:?}", observations[0].execution_result());
+    assert!(matches!(observations[1].execution_result(),
+                     Err(TestbedError::Collection(_, TestingError::NoSuchAnalogInput(8)))),
+            "{:?}", observations[1].execution_result());
+    assert!(observations[2].execution_result().is_ok());
+}
+
+#[test]
+fn exited_threads_fail_the_remaining_runs() {
+    let device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (7, (Direction::Out, SignalClass::Analog)),
+    ]);
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+
+    let input: Box<dyn AnalogInput> = Box::new(Crashing);
+    let testbed = Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new())
+        .with_analog_inputs(vec![(7, input)]);
+
+    let idle = [Operation::at(0).idle_sync(Duration::from_millis(10))];
+    let sampled = [Criterion::Analog(AnalogCriterion::new(7, AnalogStat::Max))];
+    let tests = vec![
+        Test::new("crashes", Vec::new(), Vec::new(), &idle, &sampled, false),
+        Test::new("unsampled", Vec::new(), Vec::new(), &idle, Vec::new(), false),
+        Test::new("sampled", Vec::new(), Vec::new(), &idle, &sampled, false),
+    ];
+    let start = Instant::now();
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    // The runs after the crash fail straight away instead of timing out.
+    assert_eq!(observations.len(), 3);
+    for observation in &observations {
+        match observation.execution_result() {
+            Err(TestbedError::Stopped(threads)) => assert_eq!(threads, &vec!["test-sampling".to_string()]),
+            other => panic!("expected the sampling thread to have stopped, got {:?}", other),
+        }
+    }
+    assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+}
@@ -0,0 +1,73 @@
+//! Running tests against several devices under test on one testbed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clockwise_common::comm::{Class as SignalClass, Direction, Signal};
+use clockwise_common::criteria::{Criterion, GPIOCriterion};
+use clockwise_common::evaluation::{Evaluator, StandardEvaluator, Status};
+use clockwise_common::hal::Trigger;
+use clockwise_common::hal::sim::{Effect, SimulatedDevice};
+use clockwise_common::io::{Device, Mapping};
+use clockwise_common::test::{Operation, Test};
+use clockwise_common::testbed::{DeviceUnderTest, Testbed, DEFAULT_DEVICE};
+
+mod support;
+
+use support::NoPlatform;
+
+/// Device with input pin 13 and output pin 14 that echoes edges on its input after 5ms.
+fn echoing_mapping(input: u8, output: u8) -> Mapping {
+    let device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (14, (Direction::Out, SignalClass::Digital)),
+    ]);
+    let sim = SimulatedDevice::new()
+        .when(input, Trigger::Both, Duration::from_millis(5), Effect::Toggle(output));
+
+    Mapping::new(device, &[(input, 13), (output, 14)], &[])
+        .unwrap()
+        .with_backend(Arc::new(sim))
+}
+
+#[test]
+fn devices_are_driven_and_observed_separately() {
+    let radio = DeviceUnderTest::new("radio", echoing_mapping(5, 6), Box::new(NoPlatform));
+    let testbed = Testbed::new(echoing_mapping(20, 21), Box::new(NoPlatform), HashMap::new(), None, None, Vec::new())
+        .with_device(radio);
+
+    let tests = vec![
+        Test::new(
+            "poke-radio",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).input(Signal::Digital(true), 13).on("radio"),
+              Operation::at(20).idle_sync(Duration::from_millis(20))],
+            &[Criterion::GPIO(GPIOCriterion::Any(14)),
+              Criterion::GPIO(GPIOCriterion::Any(14)).on("radio"),
+              Criterion::GPIO(GPIOCriterion::Any(14)).on("sensor")],
+            false)
+            .with_device_apps("radio", vec!["blink"]),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let observation = &observations[0];
+    assert!(observation.execution_result().is_ok());
+    let names: Vec<&str> = observation.devices().iter()
+        .map(|device| device.name())
+        .collect();
+    assert_eq!(names, vec![DEFAULT_DEVICE, "radio"]);
+
+    // Only the radio was driven, so only the radio responds.
+    assert!(observation.gpio_responses().is_empty());
+    let radio = observation.device("radio").unwrap();
+    assert_eq!(radio.gpio_responses().len(), 1);
+    assert_eq!(radio.gpio_responses()[0].get_pin(), 14);
+
+    let evaluation = StandardEvaluator::new().evaluate(observation);
+    let statuses: Vec<Status> = evaluation.outcomes().iter()
+        .map(|outcome| outcome.status())
+        .collect();
+    assert_eq!(statuses, vec![Status::Complete, Status::Complete, Status::Error]);
+}
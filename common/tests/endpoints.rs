@@ -0,0 +1,167 @@
+//! Feeding collectors from pseudo-terminal and TCP serial endpoints.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::TcpListener;
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clockwise_common::comm::{Class as SignalClass, Direction};
+use clockwise_common::events::{DeviceEvents, WaitCondition};
+use clockwise_common::hal::SimulatedDevice;
+use clockwise_common::io::{Device, Mapping, SerialConfig, UART};
+use clockwise_common::mem::StreamOperation;
+use clockwise_common::test::{Operation, Test};
+use clockwise_common::trace::{self, BenchmarkMetadata, TraceData, TraceKind};
+use clockwise_shared::mem::CounterId;
+
+fn mapping() -> Mapping {
+    let device = Device::new(&[(13, (Direction::In, SignalClass::Digital))]);
+    Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(std::sync::Arc::new(SimulatedDevice::new()))
+}
+
+#[test]
+fn memtrack_from_pty() {
+    let link = env::temp_dir().join(format!("clockwise-pty-{}", process::id()));
+    let uart = UART::Pty(link.to_str().unwrap().to_string());
+    let mapping = mapping()
+        .with_serial_config(uart.clone(), SerialConfig::default()
+                            .with_read_timeout(Duration::from_millis(10)));
+    let mut port = mapping.get_uart(&uart).unwrap();
+
+    // A local process writes a memory counter update once the test is running.
+    let writer = {
+        let link = link.clone();
+        thread::spawn(move || {
+            let mut tty = OpenOptions::new().write(true).open(link).unwrap();
+            thread::sleep(Duration::from_millis(20));
+            // Set PCB counter 6 to 3.
+            tty.write_all(&[0b1000_0001, 6, 0, 0, 0, 3, 0, 0, 0]).unwrap();
+        })
+    };
+
+    let test = Test::new(
+        "memtrack",
+        Vec::new(),
+        Vec::new(),
+        &[Operation::at(0).idle_sync(Duration::from_millis(100))],
+        Vec::new(),
+        false);
+    let mut buffer = Vec::new();
+    let mut schedule = Vec::new();
+    let events = DeviceEvents::new(&HashMap::new());
+    test.prep_memtrack(port.as_mut(), &mut buffer, &mut schedule).unwrap();
+    let remaining = test.memtrack(port.as_mut(), &mut buffer, &mut schedule, &events).unwrap();
+    writer.join().unwrap();
+
+    assert_eq!(remaining, 0);
+    assert_eq!(schedule.len(), 1);
+    assert_eq!(*schedule[0].counter(), CounterId::PCB(6));
+    assert_eq!(schedule[0].operation(), StreamOperation::Set);
+    assert_eq!(schedule[0].value(), 3);
+    let counter_set = WaitCondition::MemoryCounter(CounterId::PCB(6), 3);
+    assert_eq!(events.wait_for(&counter_set, Instant::now()), Some(schedule[0].time()));
+
+    drop(port);
+    assert!(!link.exists());
+}
+
+#[test]
+fn pty_is_shared_between_openings() {
+    let link = env::temp_dir().join(format!("clockwise-pty-shared-{}", process::id()));
+    let uart = UART::Pty(link.to_str().unwrap().to_string());
+    let mapping = mapping()
+        .with_serial_config(uart.clone(), SerialConfig::default()
+                            .with_read_timeout(Duration::from_millis(100)));
+
+    // Like the tracing and memory tracking threads opening the same UART.
+    let first = mapping.get_uart(&uart).unwrap();
+    let mut second = mapping.get_uart(&uart).unwrap();
+    let target = std::fs::read_link(&link).unwrap();
+
+    // Dropping one endpoint leaves the link to the other.
+    drop(first);
+    assert_eq!(std::fs::read_link(&link).unwrap(), target);
+    OpenOptions::new().write(true).open(&link).unwrap()
+        .write_all(b"boot").unwrap();
+    let mut buffer = [0; 8];
+    assert_eq!(second.read(&mut buffer).unwrap(), 4);
+    assert_eq!(&buffer[..4], b"boot");
+
+    drop(second);
+    assert!(!link.exists());
+}
+
+#[test]
+fn pty_writes_time_out_when_nothing_reads() {
+    let link = env::temp_dir().join(format!("clockwise-pty-full-{}", process::id()));
+    let uart = UART::Pty(link.to_str().unwrap().to_string());
+    let mapping = mapping();
+    let mut port = mapping.get_uart(&uart).unwrap();
+    port.set_write_timeout(Duration::from_millis(50)).unwrap();
+
+    // Nothing reads the slave side, so its buffer eventually fills up.
+    let chunk = [0x55; 4096];
+    let start = Instant::now();
+    let mut writes = 0;
+    while port.write(&chunk).unwrap() > 0 {
+        writes += 1;
+        assert!(writes < 10_000, "pseudo-terminal never filled up");
+    }
+    assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+}
+
+#[test]
+fn performance_trace_from_tcp_bridge() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    // Stand-in for ser2net: one waypoint container at 1kHz, then one period.
+    // The connection stays open until collection is over.
+    let (done, finished) = mpsc::channel::<()>();
+    let bridge = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        thread::sleep(Duration::from_millis(20));
+        let mut data = vec![0b0000_0001];
+        data.extend_from_slice(&1000u32.to_le_bytes());
+        data.push(0b1000_0000);
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(&15u64.to_le_bytes());
+        data.extend_from_slice(&64u32.to_le_bytes());
+        stream.write_all(&data).unwrap();
+        let _ = finished.recv();
+    });
+
+    let uart = UART::Tcp(address);
+    let mapping = mapping()
+        .with_serial_config(uart.clone(), SerialConfig::default()
+                            .with_read_timeout(Duration::from_millis(10)));
+    let mut port = mapping.get_uart(&uart).unwrap();
+    let kind = TraceKind::Performance(BenchmarkMetadata::new("bytes", &[]));
+
+    let mut buffer = Vec::new();
+    let prepared = trace::prepare(&mut buffer, port.as_mut()).unwrap();
+    let data = trace::collect(&kind, port.as_mut(), prepared, Instant::now() + Duration::from_millis(80))
+        .unwrap();
+    drop(done);
+    bridge.join().unwrap();
+
+    match data {
+        TraceData::Performance(ref performance) => {
+            // Timestamps are in counter ticks at 1kHz.
+            assert_eq!(performance.waypoints(), 1);
+            assert_eq!(performance.metrics().len(), 1, "{:?}", performance);
+            let period = &performance.metrics()[0];
+            assert_eq!(period.start_time(0), 0.005);
+            assert_eq!(period.end_time(0), 0.015);
+            assert_eq!(period.data_size(), 64);
+        },
+        _ => panic!("expected performance data"),
+    }
+}
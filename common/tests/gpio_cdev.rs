@@ -0,0 +1,75 @@
+/*! GPIO character device backend against a `gpio-sim` chip.
+
+These tests need a simulated chip with at least two lines, for example:
+
+```sh
+modprobe gpio-sim
+mkdir -p /sys/kernel/config/gpio-sim/clockwise/gpio-bank0
+echo 2 > /sys/kernel/config/gpio-sim/clockwise/gpio-bank0/num_lines
+echo 1 > /sys/kernel/config/gpio-sim/clockwise/live
+export CLOCKWISE_GPIO_SIM=/sys/devices/platform/$(cat /sys/kernel/config/gpio-sim/clockwise/dev_name)/$(cat /sys/kernel/config/gpio-sim/clockwise/gpio-bank0/chip_name)
+```
+
+The tests are ignored by default; run them with `cargo test --test gpio_cdev -- --ignored`.
+ */
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clockwise_common::hal::{Backend, GpioChip, Level, Trigger};
+
+/// Returns the sysfs directory of the simulated chip and the chip itself.
+fn sim_chip() -> (PathBuf, GpioChip) {
+    let sysfs = PathBuf::from(env::var_os("CLOCKWISE_GPIO_SIM")
+        .expect("CLOCKWISE_GPIO_SIM must name the sysfs directory of a gpio-sim chip"));
+    let dev = PathBuf::from("/dev").join(sysfs.file_name().unwrap());
+    let chip = GpioChip::new(&dev).unwrap();
+    (sysfs, chip)
+}
+
+fn sim_line(sysfs: &Path, line: u8, attr: &str) -> PathBuf {
+    sysfs.join(format!("sim_gpio{}", line)).join(attr)
+}
+
+#[test]
+#[ignore = "needs a gpio-sim chip named by CLOCKWISE_GPIO_SIM, see the module docs"]
+fn drives_output_lines() {
+    let (sysfs, chip) = sim_chip();
+
+    let mut pin = chip.output_pin(0).unwrap();
+    pin.set_high().unwrap();
+    assert_eq!(fs::read_to_string(sim_line(&sysfs, 0, "value")).unwrap().trim(), "1");
+    pin.set_low().unwrap();
+    assert_eq!(fs::read_to_string(sim_line(&sysfs, 0, "value")).unwrap().trim(), "0");
+}
+
+#[test]
+#[ignore = "needs a gpio-sim chip named by CLOCKWISE_GPIO_SIM, see the module docs"]
+fn timestamps_input_edges() {
+    let (sysfs, chip) = sim_chip();
+    fs::write(sim_line(&sysfs, 1, "pull"), "pull-down").unwrap();
+
+    let mut pin = chip.input_pin(1).unwrap();
+    assert_eq!(pin.read().unwrap(), Level::Low);
+    pin.set_interrupt(Trigger::Both).unwrap();
+
+    let before = Instant::now();
+    fs::write(sim_line(&sysfs, 1, "pull"), "pull-up").unwrap();
+    // Let the edge sit for a while so a late read would show.
+    std::thread::sleep(Duration::from_millis(20));
+
+    let (pin_no, level, t) = chip.poll_interrupts(&[pin.as_ref()], Some(Duration::from_secs(1)))
+        .unwrap()
+        .expect("no edge reported");
+    assert_eq!(pin_no, 1);
+    assert_eq!(level, Level::High);
+    assert!(t >= before && t - before < Duration::from_millis(10),
+            "edge reported {:?} after it was made", t.saturating_duration_since(before));
+    assert_eq!(pin.read().unwrap(), Level::High);
+
+    pin.clear_interrupt().unwrap();
+    fs::write(sim_line(&sysfs, 1, "pull"), "pull-down").unwrap();
+    assert_eq!(chip.poll_interrupts(&[pin.as_ref()], Some(Duration::from_millis(20))).unwrap(), None);
+}
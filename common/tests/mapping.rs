@@ -0,0 +1,101 @@
+//! Validating testbed-device pin mappings.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use clockwise_common::comm::{Class as SignalClass, Direction};
+use clockwise_common::hal::{Level, SimulatedDevice};
+use clockwise_common::io::{Device, IOError, Mapping, MappingConflict, Peripheral, ResetStrategy, UART};
+
+fn device() -> Device {
+    Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (14, (Direction::Out, SignalClass::Digital)),
+        (23, (Direction::In, SignalClass::Digital)),
+    ])
+}
+
+fn pin_reset(pin_no: u8) -> ResetStrategy {
+    ResetStrategy::Pin { pin_no, active: Level::Low, hold: Duration::from_millis(10) }
+}
+
+fn conflicts(result: clockwise_common::io::Result<Mapping>) -> Vec<MappingConflict> {
+    match result {
+        Err(IOError::InvalidMapping(conflicts)) => conflicts,
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("mapping should not be valid"),
+    }
+}
+
+#[test]
+fn valid_mapping() {
+    let mapping = Mapping::new(device().with_reset(pin_reset(23)),
+                               &[(17, 23), (20, 13), (21, 14)],
+                               &[Peripheral::I2C, Peripheral::UART(UART::PL011)]);
+    assert_eq!(mapping.unwrap().get_reset_pin(), Some(23));
+}
+
+#[test]
+fn reports_every_conflict() {
+    let result = Mapping::new(
+        device().with_reset(pin_reset(14)),
+        &[(2, 13), (20, 13), (20, 14), (14, 23), (5, 30)],
+        &[Peripheral::I2C, Peripheral::UART(UART::PL011)]);
+
+    assert_eq!(conflicts(result), vec![
+        MappingConflict::DuplicateHostPin(20),
+        MappingConflict::SharedDevicePin(13, vec![2, 20]),
+        MappingConflict::UndefinedPin(30),
+        MappingConflict::ResetDirection(14),
+        MappingConflict::UnmappedReset(14),
+        MappingConflict::ReservedPin(2, Peripheral::I2C),
+        MappingConflict::ReservedPin(14, Peripheral::UART(UART::PL011)),
+    ]);
+}
+
+#[test]
+fn peripheral_pins_free_unless_reserved() {
+    assert!(Mapping::new(device(), &[(2, 13), (15, 14)], &[]).is_ok());
+    // Only the PL011 uses GPIO pins.
+    assert!(Mapping::new(device(), &[(15, 14)],
+                         &[Peripheral::UART(UART::Custom("/dev/ttyUSB0".to_string()))]).is_ok());
+
+    let result = Mapping::new(device(), &[(3, 13), (15, 14)], &[Peripheral::UART(UART::PL011)]);
+    assert_eq!(conflicts(result), vec![MappingConflict::ReservedPin(15, Peripheral::UART(UART::PL011))]);
+}
+
+#[test]
+fn power_switch_pin_stays_free() {
+    let power_cycle = ResetStrategy::PowerCycle {
+        switch_pin: 3,
+        on: Level::High,
+        off: Duration::from_millis(100),
+    };
+    let result = Mapping::new(device().with_reset(power_cycle.clone()), &[(3, 13)], &[]);
+    assert_eq!(conflicts(result), vec![MappingConflict::MappedPowerSwitch(3)]);
+
+    let result = Mapping::new(device().with_reset(power_cycle), &[(20, 13)], &[Peripheral::I2C]);
+    assert_eq!(conflicts(result), vec![MappingConflict::ReservedPin(3, Peripheral::I2C)]);
+}
+
+#[test]
+fn spi_pins_must_be_free() {
+    let mapping = Mapping::new(device(), &[(8, 13), (17, 14)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+
+    // CE0 of SPI0 and CE1 of SPI1 are mapped.
+    assert!(matches!(mapping.get_spi(0, 0), Err(IOError::SPIUnavailable(0, 0))));
+    assert!(matches!(mapping.get_spi(1, 1), Err(IOError::SPIUnavailable(1, 1))));
+    // The bus is still available to peripherals on other chip selects; the backend has none.
+    assert!(matches!(mapping.get_spi(0, 1), Err(IOError::Backend(_))));
+
+    let result = Mapping::new(device(), &[(10, 13), (18, 14)], &[Peripheral::SPI { bus: 0, chip_select: 1 }]);
+    assert_eq!(conflicts(result), vec![MappingConflict::ReservedPin(10, Peripheral::SPI { bus: 0, chip_select: 1 })]);
+}
+
+#[test]
+fn mappings_are_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<Mapping>();
+}
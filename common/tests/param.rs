@@ -0,0 +1,56 @@
+//! Expanding parameterized test definitions.
+
+use std::time::Duration;
+
+use clockwise_common::comm::Signal;
+use clockwise_common::param::{ParamValue, ParameterizedTest};
+use clockwise_common::test::{Operation, Test};
+
+fn blink() -> ParameterizedTest {
+    ParameterizedTest::new("blink-{apps}-{delay}", |params| {
+        let apps = params.get("apps").and_then(ParamValue::as_set).unwrap();
+        let delay = params.get("delay").and_then(ParamValue::as_duration).unwrap();
+        Test::new(
+            "blink",
+            apps.iter().map(String::as_str),
+            ["led/on"],
+            &[Operation::at_time(delay).input(Signal::Digital(true), 13)],
+            Vec::new(),
+            true)
+    })
+        .with_parameter("apps", [&["blink"][..], &["blink", "radio"][..]])
+        .with_parameter("delay", [Duration::from_millis(10), Duration::from_millis(50)])
+}
+
+#[test]
+fn matrix_expands_into_named_tests() {
+    let tests = blink().expand();
+
+    let ids: Vec<&str> = tests.iter().map(|test| test.get_id()).collect();
+    assert_eq!(ids, vec![
+        "blink-blink-10ms",
+        "blink-blink-50ms",
+        "blink-blink+radio-10ms",
+        "blink-blink+radio-50ms",
+    ]);
+
+    let last = &tests[3];
+    assert_eq!(last.get_app_ids().len(), 2);
+    assert!(last.get_app_ids().contains("radio"));
+    assert!(last.get_trace_points().contains("led/on"));
+    assert_eq!(last.to_string().lines().nth(1), Some("Parameters: apps=blink+radio, delay=50ms"));
+
+    let params = last.get_parameters();
+    assert_eq!(params.get("delay"), Some(&ParamValue::Duration(Duration::from_millis(50))));
+    let names: Vec<&str> = params.iter().map(|(name, _value)| name).collect();
+    assert_eq!(names, vec!["apps", "delay"]);
+}
+
+#[test]
+fn parameter_without_values_expands_to_nothing() {
+    let empty: [u32; 0] = [];
+    let tests = blink()
+        .with_parameter("count", empty)
+        .expand();
+    assert!(tests.is_empty());
+}
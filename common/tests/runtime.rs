@@ -0,0 +1,94 @@
+//! Deriving how long a test runs.
+
+use std::time::Duration;
+
+use clockwise_common::comm::Signal;
+use clockwise_common::criteria::{Criterion, GPIOCriterion, SerialTraceCondition, SerialTraceCriterion, Timing};
+use clockwise_common::events::WaitCondition;
+use clockwise_common::test::{Operation, Test, DEFAULT_TAIL};
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn runtime_covers_the_last_operation() {
+    // Operations added out of order still end at the latest one.
+    let ops = [
+        Operation::at(50).idle_sync(ms(10)),
+        Operation::at(0).idle_sync(ms(5)),
+    ];
+    let test = Test::new("ops", Vec::new(), Vec::new(), &ops, &[Criterion::GPIO(GPIOCriterion::Any(14))], false);
+    assert_eq!(test.max_runtime(), ms(60) + DEFAULT_TAIL);
+}
+
+#[test]
+fn runtime_covers_criteria_windows() {
+    let boot = SerialTraceCriterion::new(&[
+        SerialTraceCondition::new(b"boot")
+            .with_timing(Timing::Absolute(ms(2000)), ms(100)),
+        SerialTraceCondition::new(b"ready")
+            .with_timing(Timing::Relative(ms(500)), ms(50)),
+        SerialTraceCondition::new(b"idle"),
+    ]);
+    assert_eq!(boot.get_window(), Some(ms(2650)));
+
+    let criteria = [Criterion::SerialTrace(boot).on("peer")];
+    let test = Test::new("boot", Vec::new(), Vec::new(), &[Operation::at(0).idle_sync(ms(10))], &criteria, false);
+    assert_eq!(test.max_runtime(), ms(2650) + DEFAULT_TAIL);
+
+    let test = test.with_tail(ms(100));
+    assert_eq!(test.max_runtime(), ms(2750));
+    assert_eq!(test.with_runtime(ms(500)).max_runtime(), ms(500));
+}
+
+#[test]
+fn untimed_criteria_do_not_extend_runtime() {
+    let criteria = [Criterion::SerialTrace(SerialTraceCriterion::new(&[SerialTraceCondition::new(b"boot")]))];
+    assert_eq!(criteria[0].get_window(), None);
+
+    let test = Test::new("untimed", Vec::new(), Vec::new(), &[Operation::at(20).idle_sync(ms(10))], &criteria, false)
+        .with_tail(Duration::ZERO);
+    assert_eq!(test.max_runtime(), ms(30));
+}
+
+#[test]
+fn relative_timing_after_untimed_conditions_is_open_ended() {
+    // "ready" follows "boot" whenever that comes, so its window is not known.
+    let boot = SerialTraceCriterion::new(&[
+        SerialTraceCondition::new(b"boot"),
+        SerialTraceCondition::new(b"ready")
+            .with_timing(Timing::Relative(ms(500)), ms(50)),
+    ]);
+    assert_eq!(boot.get_window(), None);
+
+    // An absolute condition bounds the ones after it again.
+    let boot = SerialTraceCriterion::new(&[
+        SerialTraceCondition::new(b"boot"),
+        SerialTraceCondition::new(b"ready")
+            .with_timing(Timing::Relative(ms(500)), ms(50)),
+        SerialTraceCondition::new(b"listening")
+            .with_timing(Timing::Absolute(ms(1000)), ms(100)),
+        SerialTraceCondition::new(b"idle")
+            .with_timing(Timing::Relative(ms(200)), ms(10)),
+    ]);
+    assert_eq!(boot.get_window(), Some(ms(1310)));
+}
+
+#[test]
+fn waits_extend_runtime_by_their_timeout_once() {
+    let ops = [
+        Operation::at(0).input(Signal::Digital(true), 13),
+        Operation::at(10).wait_for(WaitCondition::PinLevel(14, true.into()), ms(100)),
+        Operation::at(20).idle_sync(ms(5)),
+    ];
+    let test = Test::new("wait", Vec::new(), Vec::new(), &ops, &[Criterion::GPIO(GPIOCriterion::Any(14))], false)
+        .with_tail(Duration::ZERO);
+    assert_eq!(test.max_runtime(), ms(125));
+
+    // A wait that is the last operation counts once as well.
+    let last = [Operation::at(10).wait_for(WaitCondition::PinLevel(14, true.into()), ms(100))];
+    let test = Test::new("last", Vec::new(), Vec::new(), &last, &[Criterion::GPIO(GPIOCriterion::Any(14))], false)
+        .with_tail(Duration::ZERO);
+    assert_eq!(test.max_runtime(), ms(110));
+}
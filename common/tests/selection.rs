@@ -0,0 +1,53 @@
+//! Selecting which provided tests to run.
+
+use std::time::Duration;
+
+use clockwise_common::input::{Selector, TestSelection};
+use clockwise_common::test::{Operation, Test};
+
+fn suite() -> Vec<Test> {
+    let idle = [Operation::at(0).idle_sync(Duration::from_millis(10))];
+    vec![
+        Test::new("blink", ["blink"], Vec::new(), &idle, Vec::new(), true)
+            .with_tags(["smoke"]),
+        Test::new("radio-tx", ["radio"], ["radio/tx"], &idle, Vec::new(), true)
+            .with_tags(["smoke", "energy"]),
+        Test::new("radio-rx", Vec::new(), ["radio/rx"], &idle, Vec::new(), true)
+            .with_device_apps("peer", ["radio"])
+            .with_tags(["energy"]),
+    ]
+}
+
+fn selected(selection: &TestSelection) -> Vec<String> {
+    selection.filter(suite())
+        .map(|test| test.get_id().to_string())
+        .collect()
+}
+
+#[test]
+fn everything_is_selected_by_default() {
+    assert_eq!(selected(&TestSelection::new()), vec!["blink", "radio-tx", "radio-rx"]);
+}
+
+#[test]
+fn includes_and_excludes_combine() {
+    let smoke = TestSelection::new()
+        .with_include(Selector::Tag("smoke".to_string()));
+    assert_eq!(selected(&smoke), vec!["blink", "radio-tx"]);
+
+    let radio = TestSelection::new()
+        .with_include(Selector::Id("radio-*".to_string()))
+        .with_exclude(Selector::TracePoint("radio/tx".to_string()));
+    assert_eq!(selected(&radio), vec!["radio-rx"]);
+
+    // Applications on any device under test count.
+    let radio_app = TestSelection::new()
+        .with_include(Selector::App("radio".to_string()));
+    assert_eq!(selected(&radio_app), vec!["radio-tx", "radio-rx"]);
+
+    let nightly = TestSelection::new()
+        .with_include(Selector::Tag("energy".to_string()))
+        .with_include(Selector::Id("blink".to_string()))
+        .with_exclude(Selector::Tag("smoke".to_string()));
+    assert_eq!(selected(&nightly), vec!["radio-rx"]);
+}
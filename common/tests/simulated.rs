@@ -0,0 +1,902 @@
+//! End-to-end testbed runs against a simulated device under test.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clockwise_common::comm::{Class as SignalClass, Direction, Signal};
+use clockwise_common::criteria::{
+    Count,
+    Criterion,
+    EdgeCondition,
+    EdgeSequence,
+    EnergyCriterion,
+    EnergyStat,
+    GPIOCriterion,
+    Latency,
+    SerialTraceCondition,
+    SerialTraceCriterion,
+    Timing,
+    Waveform,
+};
+use clockwise_common::evaluation::{self, Distribution, Evaluator, StandardEvaluator, Status};
+use clockwise_common::events::WaitCondition;
+use clockwise_common::hal::{Backend, I2cBus, InputPin, Level, OutputPin, SerialPort, SpiBus, Trigger};
+use clockwise_common::hal::sim::{Effect, SimulatedDevice};
+use clockwise_common::io::{self, Device, IOError, Mapping, Parity, ResetStrategy, SerialConfig, UART};
+use clockwise_common::test::{Operation, Test, TestingError};
+use clockwise_common::testbed::{Testbed, TestbedError};
+
+mod support;
+
+use support::NoPlatform;
+
+const TRACE_UART: &str = "sim-trace";
+
+/// Testbed wired to a simulated device.
+///
+/// Device pin 13 (testbed pin 20) is an input, device pin 14 (testbed pin 21) is an output,
+/// and device pin 23 (testbed pin 17) holds the device in reset when low.
+fn simulated_testbed(sim: SimulatedDevice) -> Testbed {
+    let device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (14, (Direction::Out, SignalClass::Digital)),
+        (23, (Direction::In, SignalClass::Digital)),
+    ])
+        .with_reset(ResetStrategy::Pin {
+            pin_no: 23,
+            active: Level::Low,
+            hold: Duration::from_millis(10),
+        });
+
+    let mapping = Mapping::new(device, &[(17, 23), (20, 13), (21, 14)], &[])
+        .unwrap()
+        .with_backend(Arc::new(sim.with_reset(17, Level::Low)));
+
+    Testbed::new(
+        mapping,
+        Box::new(NoPlatform),
+        HashMap::new(),
+        Some(UART::Custom(TRACE_UART.to_string())),
+        None,
+        Vec::new())
+}
+
+#[test]
+fn responses_and_traces_from_simulated_device() {
+    let sim = SimulatedDevice::new()
+        .when(20, Trigger::Both, Duration::from_millis(5), Effect::Toggle(21))
+        .at(Duration::from_millis(100),
+            Effect::Transmit(UART::Custom(TRACE_UART.to_string()), b"boot".to_vec()));
+    let testbed = simulated_testbed(sim);
+
+    let tests = vec![
+        Test::new(
+            "toggle-once",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).input(Signal::Digital(true), 13),
+              Operation::at(20).idle_sync(Duration::from_millis(150))],
+            &[Criterion::GPIO(GPIOCriterion::Any(14)),
+              Criterion::SerialTrace(SerialTraceCriterion::new(&[
+                  SerialTraceCondition::new(b"boot")
+                      .with_timing(Timing::Absolute(Duration::from_millis(100)),
+                                   Duration::from_millis(20))]))],
+            true),
+        Test::new(
+            "toggle-twice",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).input(Signal::Digital(false), 13),
+              Operation::at(50).input(Signal::Digital(true), 13),
+              Operation::at(60).idle_sync(Duration::from_millis(20))],
+            &[Criterion::GPIO(GPIOCriterion::Any(14))],
+            true),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+    assert_eq!(observations.len(), 2);
+
+    // The device answers the rising edge at 10ms with a rising edge at 15ms.
+    let first = &observations[0];
+    let t0 = first.execution_result().as_ref().unwrap().get_start();
+    let responses = first.gpio_responses();
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].get_pin(), 14);
+    assert_eq!(responses[0].get_output(), Signal::Digital(true));
+    let offset = responses[0].get_offset(t0);
+    assert!(offset >= Duration::from_millis(15) && offset < Duration::from_millis(25),
+            "response at {:?}", offset);
+
+    let evaluation = StandardEvaluator::new().evaluate(first);
+    assert_eq!(evaluation.status(), Status::Pass);
+
+    // Each edge the testbed drives toggles the output again.
+    // Resetting the device does not change the levels of its pins,
+    // so both the input and the output start high from the previous test.
+    let second = &observations[1];
+    let levels: Vec<Signal> = second.gpio_responses().iter()
+        .map(|response| response.get_output())
+        .collect();
+    assert_eq!(levels, vec![Signal::Digital(false), Signal::Digital(true)]);
+}
+
+#[test]
+fn operations_are_applied_on_schedule() {
+    let testbed = simulated_testbed(SimulatedDevice::new());
+
+    let tests = vec![
+        Test::new(
+            "pulse-train",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at_micros(1_000).input(Signal::Digital(true), 13),
+              Operation::at_micros(1_250).input(Signal::Digital(false), 13),
+              Operation::at_micros(1_500).input(Signal::Digital(true), 13),
+              Operation::at(2).idle_sync(Duration::from_millis(5))],
+            Vec::new(),
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let execution = observations[0].execution_result().as_ref().unwrap();
+    let t0 = execution.get_start();
+    let scheduled: Vec<Duration> = execution.get_applied_operations().iter()
+        .map(|applied| applied.get_scheduled() - t0)
+        .collect();
+    assert_eq!(scheduled, vec![
+        Duration::from_micros(1_000),
+        Duration::from_micros(1_250),
+        Duration::from_micros(1_500),
+        Duration::from_millis(2),
+    ]);
+    for applied in execution.get_applied_operations() {
+        assert!(applied.get_applied() >= applied.get_scheduled());
+        assert_eq!(applied.lateness(), applied.get_applied() - applied.get_scheduled());
+    }
+    assert!(execution.mean_lateness() <= execution.max_lateness());
+    assert!(execution.max_lateness() < Duration::from_millis(5), "{:?}", execution.max_lateness());
+}
+
+#[test]
+fn stimulus_actions_expand_into_pin_changes() {
+    let sim = SimulatedDevice::new()
+        .when(20, Trigger::Both, Duration::from_millis(1), Effect::Toggle(21));
+    let testbed = simulated_testbed(sim);
+
+    let tests = vec![
+        Test::new(
+            "stimuli",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).square_wave(13, 1000.0, 25.0, 3).unwrap(),
+              Operation::at(20).pulse(13, Level::High, Duration::from_micros(500)),
+              Operation::at(30).bits(13, &[true, true, false, true], Duration::from_micros(400)),
+              Operation::at(40).toggle(13),
+              Operation::at(50).bounce(13, Level::High, 2, Duration::from_micros(300)),
+              Operation::at(55).idle_sync(Duration::from_millis(5))],
+            &[Criterion::GPIO(GPIOCriterion::Any(14))],
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let execution = observations[0].execution_result().as_ref().unwrap();
+    let t0 = execution.get_start();
+    let scheduled: Vec<u128> = execution.get_applied_operations().iter()
+        .map(|applied| (applied.get_scheduled() - t0).as_micros())
+        .collect();
+    assert_eq!(scheduled, vec![
+        10_000, 10_250, 11_000, 11_250, 12_000, 12_250,
+        20_000, 20_500,
+        // The repeated high bit is not driven again.
+        30_000, 30_800, 31_200,
+        40_000,
+        50_000, 50_300, 50_600, 50_900, 51_200,
+        55_000,
+    ]);
+
+    // Every pin change, including the toggle, is an edge the device echoes.
+    assert_eq!(observations[0].gpio_responses().len(), 17);
+}
+
+#[test]
+fn full_and_empty_square_waves_are_steady_levels() {
+    let sim = SimulatedDevice::new()
+        .when(20, Trigger::Both, Duration::from_millis(1), Effect::Toggle(21));
+    let testbed = simulated_testbed(sim);
+
+    let tests = vec![
+        Test::new(
+            "steady",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).square_wave(13, 1000.0, 100.0, 3).unwrap(),
+              Operation::at(20).square_wave(13, 1000.0, 0.0, 3).unwrap(),
+              Operation::at(30).idle_sync(Duration::from_millis(5))],
+            &[Criterion::GPIO(GPIOCriterion::Any(14))],
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let execution = observations[0].execution_result().as_ref().unwrap();
+    let t0 = execution.get_start();
+    let applied: Vec<(u128, Option<Level>)> = execution.get_applied_operations().iter()
+        .map(|applied| ((applied.get_scheduled() - t0).as_micros(), applied.get_level()))
+        .collect();
+    // Full duty holds the pin high for every cycle and leaves it low; no duty holds it low.
+    assert_eq!(applied, vec![
+        (10_000, Some(Level::High)),
+        (13_000, Some(Level::Low)),
+        (20_000, Some(Level::Low)),
+        (30_000, None),
+    ]);
+
+    // Only the rising and the falling edge of the full wave reach the device.
+    assert_eq!(observations[0].gpio_responses().len(), 2);
+}
+
+#[test]
+fn square_waves_need_a_positive_frequency_and_a_percentage_duty() {
+    assert_eq!(Operation::at(0).square_wave(13, 0.0, 50.0, 3).unwrap_err(),
+               "square wave frequency must be positive and finite, got 0 Hz");
+    assert!(Operation::at(0).square_wave(13, f64::NAN, 50.0, 3).is_err());
+    assert_eq!(Operation::at(0).square_wave(13, 1000.0, 150.0, 3).unwrap_err(),
+               "square wave duty cycle must be between 0 and 100%, got 150%");
+    assert!(Operation::at(0).square_wave(13, 1000.0, f64::NAN, 3).is_err());
+}
+
+#[test]
+fn gpio_criteria_judge_edges_levels_and_pulses() {
+    let ms = Duration::from_millis;
+    let sim = SimulatedDevice::new()
+        .at(ms(45), Effect::Set(21, Level::High))
+        .at(ms(105), Effect::Set(21, Level::Low))
+        .at(ms(245), Effect::Set(21, Level::High))
+        .at(ms(305), Effect::Set(21, Level::Low));
+    let testbed = simulated_testbed(sim);
+
+    // The output is high 45ms-105ms and 245ms-305ms.
+    let tolerance = ms(20);
+    let tests = vec![
+        Test::new(
+            "output-pulses",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).idle_sync(ms(320))],
+            &[Criterion::GPIO(GPIOCriterion::Edges(14, Trigger::RisingEdge, Count::Exactly(2))),
+              Criterion::GPIO(GPIOCriterion::Edges(14, Trigger::Both, Count::AtMost(3))),
+              Criterion::GPIO(GPIOCriterion::LevelAt(14, ms(20), Level::Low)),
+              Criterion::GPIO(GPIOCriterion::LevelAt(14, ms(75), Level::High)),
+              Criterion::GPIO(GPIOCriterion::LevelAt(14, ms(175), Level::High)),
+              Criterion::GPIO(GPIOCriterion::PulseWidth(14, Level::High, ms(40), ms(80))),
+              Criterion::GPIO(GPIOCriterion::Sequence(EdgeSequence::new(&[
+                  EdgeCondition::new(14, Level::High).with_timing(Timing::Absolute(ms(45)), tolerance),
+                  EdgeCondition::new(14, Level::Low).with_timing(Timing::Relative(ms(60)), tolerance),
+                  EdgeCondition::new(14, Level::High).with_timing(Timing::Relative(ms(140)), tolerance),
+              ]))),
+              Criterion::GPIO(GPIOCriterion::Sequence(EdgeSequence::new(&[
+                  EdgeCondition::new(14, Level::High).with_timing(Timing::Absolute(ms(150)), tolerance),
+              ])))],
+            true),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let evaluation = StandardEvaluator::new().evaluate(&observations[0]);
+    let statuses: Vec<Status> = evaluation.outcomes().iter()
+        .map(|outcome| outcome.status())
+        .collect();
+    assert_eq!(statuses, vec![
+        Status::Pass,
+        Status::Fail,
+        Status::Pass,
+        Status::Pass,
+        Status::Fail,
+        Status::Pass,
+        Status::Pass,
+        Status::Fail,
+    ]);
+    assert_eq!(evaluation.outcomes()[1].values(), &[4.0]);
+    assert_eq!(evaluation.outcomes()[5].values().len(), 2);
+}
+
+#[test]
+fn latency_is_measured_per_stimulus() {
+    let ms = Duration::from_millis;
+    // The device only responds to rising edges.
+    let sim = SimulatedDevice::new()
+        .when(20, Trigger::RisingEdge, ms(5), Effect::Toggle(21));
+    let testbed = simulated_testbed(sim);
+
+    let rising = Latency::new(13, 14, ms(2), ms(20)).with_stimulus_edges(Trigger::RisingEdge);
+    let tests = vec![
+        Test::new(
+            "latency",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).input(Signal::Digital(true), 13),
+              Operation::at(40).input(Signal::Digital(false), 13),
+              Operation::at(70).input(Signal::Digital(true), 13),
+              Operation::at(100).input(Signal::Digital(false), 13),
+              Operation::at(110).idle_sync(ms(20))],
+            &[Criterion::GPIO(GPIOCriterion::Latency(rising.clone())),
+              Criterion::GPIO(GPIOCriterion::Latency(Latency::new(13, 14, ms(2), ms(20)))),
+              Criterion::GPIO(GPIOCriterion::Latency(Latency::new(13, 14, ms(0), ms(2))
+                  .with_stimulus_edges(Trigger::RisingEdge)))],
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let evaluation = StandardEvaluator::new().evaluate(&observations[0]);
+    let statuses: Vec<Status> = evaluation.outcomes().iter()
+        .map(|outcome| outcome.status())
+        .collect();
+    assert_eq!(statuses, vec![Status::Pass, Status::Fail, Status::Fail]);
+
+    let latencies = evaluation.outcomes()[0].values();
+    assert_eq!(latencies.len(), 2);
+    assert!(latencies.iter().all(|latency| (4.0..10.0).contains(latency)), "{:?}", latencies);
+
+    // Falling inputs get no response and are missed.
+    let missed = evaluation.outcomes()[1].message().unwrap();
+    assert!(missed.starts_with("2 of 4 responded"), "{}", missed);
+}
+
+#[test]
+fn waveform_criteria_measure_frequency_and_duty_cycle() {
+    let ms = Duration::from_millis;
+    // Blinks at 10Hz with a 30% duty cycle from 60ms on.
+    let sim = (0..4).fold(SimulatedDevice::new(), |sim, period| sim
+        .at(ms(60 + 100 * period), Effect::Set(21, Level::High))
+        .at(ms(90 + 100 * period), Effect::Set(21, Level::Low)));
+    let testbed = simulated_testbed(sim);
+
+    let blink = Waveform::new(14, 10.0, 5.0);
+    let tests = vec![
+        Test::new(
+            "blink",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).idle_sync(ms(420))],
+            &[Criterion::GPIO(GPIOCriterion::Waveform(blink.clone()
+                  .with_duty_cycle(30.0, 5.0)
+                  .with_max_jitter(ms(2)))),
+              Criterion::GPIO(GPIOCriterion::Waveform(Waveform::new(14, 20.0, 5.0))),
+              Criterion::GPIO(GPIOCriterion::Waveform(blink.clone().with_duty_cycle(50.0, 5.0))),
+              Criterion::GPIO(GPIOCriterion::Waveform(blink.with_window(ms(290), ms(420))))],
+            true),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let evaluation = StandardEvaluator::new().evaluate(&observations[0]);
+    let statuses: Vec<Status> = evaluation.outcomes().iter()
+        .map(|outcome| outcome.status())
+        .collect();
+    assert_eq!(statuses, vec![Status::Pass, Status::Fail, Status::Fail, Status::Fail]);
+
+    // Frequency, jitter and duty cycle.
+    let values = evaluation.outcomes()[0].values();
+    assert!((values[0] - 10.0).abs() < 0.5, "{:?}", values);
+    assert!((values[2] - 30.0).abs() < 5.0, "{:?}", values);
+    let message = evaluation.outcomes()[0].message().unwrap();
+    assert!(message.ends_with("duty over 3 periods"), "{}", message);
+}
+
+#[test]
+fn waveforms_are_analyzed_over_the_whole_runtime() {
+    let ms = Duration::from_millis;
+    let sim = (0..4).fold(SimulatedDevice::new(), |sim, period| sim
+        .at(ms(60 + 100 * period), Effect::Set(21, Level::High))
+        .at(ms(90 + 100 * period), Effect::Set(21, Level::Low)));
+    let testbed = simulated_testbed(sim);
+
+    // Nothing is driven; the device blinks during the tail.
+    let tests = vec![
+        Test::new(
+            "blink",
+            Vec::new(),
+            Vec::new(),
+            &[],
+            &[Criterion::GPIO(GPIOCriterion::Waveform(Waveform::new(14, 10.0, 5.0).with_duty_cycle(30.0, 5.0)))],
+            true)
+            .with_tail(ms(420)),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let evaluation = StandardEvaluator::new().evaluate(&observations[0]);
+    let outcome = &evaluation.outcomes()[0];
+    assert_eq!(outcome.status(), Status::Pass, "{:?}", outcome.message());
+}
+
+#[test]
+fn serial_writes_are_timestamped() {
+    let console = UART::Custom("sim-console".to_string());
+    let sim = SimulatedDevice::new()
+        .when_received(console.clone(), b"led on", Duration::from_millis(2), Effect::Set(21, Level::High));
+    let testbed = simulated_testbed(sim);
+
+    let tests = vec![
+        Test::new(
+            "console-command",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(10).serial_write(console, b"led on\r\n"),
+              Operation::at(20).idle_sync(Duration::from_millis(10))],
+            &[Criterion::GPIO(GPIOCriterion::Any(14))],
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let execution = observations[0].execution_result().as_ref().unwrap();
+    let sent = execution.get_applied_operations()[0].get_applied();
+    let responses = observations[0].gpio_responses();
+    assert_eq!(responses.len(), 1);
+    let latency = responses[0].get_time() - sent;
+    assert!(latency >= Duration::from_millis(2) && latency < Duration::from_millis(5), "{:?}", latency);
+}
+
+#[test]
+fn waits_reschedule_the_rest_of_the_timeline() {
+    let sim = SimulatedDevice::new()
+        .at(Duration::from_millis(40), Effect::Set(21, Level::High))
+        .at(Duration::from_millis(70),
+            Effect::Transmit(UART::Custom(TRACE_UART.to_string()), b"radio ready\r\n".to_vec()));
+    let testbed = simulated_testbed(sim);
+
+    let tests = vec![
+        Test::new(
+            "wait-for-device",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).wait_for(WaitCondition::PinLevel(14, Level::High), Duration::from_millis(200)),
+              Operation::at(5).input(Signal::Digital(true), 13),
+              Operation::at(10).wait_for(WaitCondition::SerialTrace(b"ready".to_vec()), Duration::from_millis(200)),
+              Operation::at(15).input(Signal::Digital(false), 13)],
+            Vec::new(),
+            true),
+        Test::new(
+            "wait-times-out",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).wait_for(WaitCondition::SerialTrace(b"never".to_vec()), Duration::from_millis(20)),
+              Operation::at(5).input(Signal::Digital(true), 13)],
+            Vec::new(),
+            true),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    let execution = observations[0].execution_result().as_ref().unwrap();
+    let t0 = execution.get_start();
+    let applied = execution.get_applied_operations();
+    assert_eq!(applied.len(), 4);
+    let pin_high = applied[0].get_condition_met().unwrap() - t0;
+    assert!(pin_high >= Duration::from_millis(35) && pin_high < Duration::from_millis(50), "{:?}", pin_high);
+    let ready = applied[2].get_condition_met().unwrap() - t0;
+    assert!(ready >= Duration::from_millis(65) && ready < Duration::from_millis(80), "{:?}", ready);
+
+    // Later operations keep their spacing from the wait they follow.
+    assert_eq!(applied[1].get_scheduled(), applied[0].get_condition_met().unwrap() + Duration::from_millis(5));
+    assert_eq!(applied[2].get_scheduled(), applied[1].get_scheduled() + Duration::from_millis(5));
+    assert_eq!(applied[3].get_scheduled(), applied[2].get_condition_met().unwrap() + Duration::from_millis(5));
+    assert!(applied[1].get_condition_met().is_none());
+
+    match observations[1].execution_result() {
+        Err(TestbedError::Execution(TestingError::WaitTimeout(WaitCondition::SerialTrace(bytes)))) =>
+            assert_eq!(bytes, b"never"),
+        other => panic!("expected a wait timeout, got {:?}", other),
+    }
+}
+
+#[test]
+fn repeated_runs_are_aggregated() {
+    let sim = SimulatedDevice::new()
+        .at(Duration::from_millis(20),
+            Effect::Transmit(UART::Custom(TRACE_UART.to_string()), b"boot".to_vec()));
+    let testbed = simulated_testbed(sim);
+
+    let tests = vec![
+        Test::new(
+            "boot-timing",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).idle_sync(Duration::from_millis(40))],
+            &[Criterion::SerialTrace(SerialTraceCriterion::new(&[
+                SerialTraceCondition::new(b"boot")
+                    .with_timing(Timing::Absolute(Duration::from_millis(20)),
+                                 Duration::from_millis(15))]))],
+            true)
+            .with_repetitions(4),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+    let runs: Vec<u32> = observations.iter()
+        .map(|observation| observation.repetition())
+        .collect();
+    assert_eq!(runs, vec![0, 1, 2, 3]);
+
+    let evaluator = StandardEvaluator::new();
+    let evaluations: Vec<_> = observations.iter()
+        .map(|observation| evaluator.evaluate(observation))
+        .collect();
+    let aggregates = evaluation::aggregate(&evaluations);
+    assert_eq!(aggregates.len(), 1);
+    let aggregate = &aggregates[0];
+    assert_eq!(aggregate.runs(), 4);
+    assert_eq!(aggregate.count(Status::Pass), 4);
+    assert_eq!(aggregate.pass_rate(), 1.0);
+
+    // One distribution per aligned trace, with a sample from every run.
+    let offsets = aggregate.criteria()[0].values();
+    assert_eq!(offsets.len(), 1);
+    assert_eq!(offsets[0].len(), 4);
+    let mean = offsets[0].mean().unwrap();
+    assert!((20.0..35.0).contains(&mean), "{}", mean);
+    assert!(offsets[0].min().unwrap() <= offsets[0].percentile(50.0).unwrap());
+    assert!(offsets[0].percentile(50.0).unwrap() <= offsets[0].max().unwrap());
+
+    let distribution = Distribution::new(vec![4.0, 1.0, 3.0, 2.0, f64::NAN]);
+    assert_eq!(distribution.len(), 4);
+    assert_eq!(distribution.mean(), Some(2.5));
+    assert_eq!(distribution.percentile(50.0), Some(2.5));
+    assert_eq!(distribution.percentile(100.0), Some(4.0));
+    assert!((distribution.stddev().unwrap() - 1.291).abs() < 0.001);
+}
+
+#[test]
+fn held_in_reset_device_does_not_respond() {
+    let sim = SimulatedDevice::new()
+        .when(20, Trigger::RisingEdge, Duration::from_millis(5), Effect::Set(21, Level::High));
+    let testbed = simulated_testbed(sim);
+
+    // Driving the reset pin low from the timeline keeps the device from reacting.
+    let tests = vec![
+        Test::new(
+            "in-reset",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).input(Signal::Digital(false), 23),
+              Operation::at(10).input(Signal::Digital(true), 13),
+              Operation::at(20).idle_sync(Duration::from_millis(30))],
+            &[Criterion::GPIO(GPIOCriterion::Any(14))],
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    assert_eq!(observations.len(), 1);
+    assert!(observations[0].execution_result().is_ok());
+    assert!(observations[0].gpio_responses().is_empty());
+}
+
+#[test]
+fn reset_strategies_are_recorded() {
+    let device = Device::new(&[(13, (Direction::In, SignalClass::Digital))]);
+    let power_cycle = ResetStrategy::PowerCycle {
+        switch_pin: 4,
+        on: Level::High,
+        off: Duration::from_millis(10),
+    };
+    // Switching power off holds the device in reset; it boots when power is back.
+    let sim = SimulatedDevice::new()
+        .with_reset(4, Level::Low)
+        .at(Duration::from_millis(50),
+            Effect::Transmit(UART::Custom(TRACE_UART.to_string()), b"boot".to_vec()));
+    let mapping = Mapping::new(device.with_reset(power_cycle), &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(sim));
+    let testbed = Testbed::new(
+        mapping,
+        Box::new(NoPlatform),
+        HashMap::new(),
+        Some(UART::Custom(TRACE_UART.to_string())),
+        None,
+        Vec::new());
+
+    let tests = vec![
+        Test::new(
+            "power-cycle",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).idle_sync(Duration::from_millis(100))],
+            &[Criterion::SerialTrace(SerialTraceCriterion::new(&[
+                SerialTraceCondition::new(b"boot")
+                    .with_timing(Timing::Absolute(Duration::from_millis(50)),
+                                 Duration::from_millis(20))]))],
+            true),
+        Test::new(
+            "no-reset",
+            Vec::new(),
+            Vec::new(),
+            &[Operation::at(0).idle_sync(Duration::from_millis(10))],
+            Vec::new(),
+            false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    match observations[0].reset_strategy() {
+        Some(ResetStrategy::PowerCycle { switch_pin: 4, .. }) => (),
+        other => panic!("unexpected reset strategy: {:?}", other),
+    }
+    let evaluation = StandardEvaluator::new().evaluate(&observations[0]);
+    assert_eq!(evaluation.status(), Status::Pass);
+    assert!(observations[1].reset_strategy().is_none());
+}
+
+#[test]
+fn failed_reset_command_does_not_stop_the_suite() {
+    let device = Device::new(&[(13, (Direction::In, SignalClass::Digital))])
+        .with_reset(ResetStrategy::command("false", vec![]));
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+    let testbed = Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new());
+
+    let tests = vec![
+        Test::new("reset", Vec::new(), Vec::new(), &[Operation::at(0).idle_sync(Duration::from_millis(10))],
+                  Vec::new(), true),
+        Test::new("no-reset", Vec::new(), Vec::new(), &[Operation::at(0).idle_sync(Duration::from_millis(10))],
+                  Vec::new(), false),
+    ];
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    assert!(matches!(observations[0].execution_result(), Err(TestbedError::Reset(IOError::ResetCommand(_)))));
+    assert!(observations[1].execution_result().is_ok());
+}
+
+#[test]
+fn hung_reset_command_is_killed_at_the_deadline() {
+    let device = Device::new(&[(13, (Direction::In, SignalClass::Digital))])
+        .with_reset(ResetStrategy::command("sleep", ["30"]));
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+    let testbed = Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new());
+
+    let idle = [Operation::at(0).idle_sync(Duration::from_millis(10))];
+    let tests = vec![
+        Test::new("reset", Vec::new(), Vec::new(), &idle, Vec::new(), true)
+            .with_timeout(Duration::from_millis(300)),
+        Test::new("no-reset", Vec::new(), Vec::new(), &idle, Vec::new(), false),
+    ];
+    let start = Instant::now();
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    match observations[0].execution_result() {
+        Err(TestbedError::Reset(IOError::ResetTimeout(program))) => assert_eq!(program, "sleep"),
+        other => panic!("expected a reset timeout, got {:?}", other),
+    }
+    assert!(observations[1].execution_result().is_ok());
+    // The command is killed again after the bounded reset that follows the abandoned run.
+    assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+}
+
+#[test]
+fn serial_ports_use_configured_read_timeout() {
+    let device = Device::new(&[(13, (Direction::In, SignalClass::Digital))]);
+    let fast = UART::Custom("sim-fast".to_string());
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()))
+        .with_serial_config(fast.clone(), SerialConfig::new(921_600)
+                            .with_parity(Parity::None)
+                            .with_read_timeout(Duration::from_millis(10)));
+
+    assert_eq!(mapping.get_serial_config(&fast).get_baud_rate(), 921_600);
+    let slow = UART::Custom("sim-slow".to_string());
+    assert_eq!(mapping.get_serial_config(&slow), SerialConfig::default());
+    // Unconfigured ports keep the read timeout of whatever collects from them.
+    assert!(mapping.get_serial_config(&fast).has_read_timeout());
+    assert!(!mapping.get_serial_config(&slow).has_read_timeout());
+
+    let mut buffer = [0; 8];
+    let mut port = mapping.get_uart(&fast).unwrap();
+    let start = Instant::now();
+    assert_eq!(port.read(&mut buffer).unwrap(), 0);
+    let waited = start.elapsed();
+    assert!(waited >= Duration::from_millis(10) && waited < Duration::from_millis(100),
+            "read gave up after {:?}", waited);
+}
+
+/// Simulated device whose serial ports stop responding for `stall` on their first read.
+#[derive(Debug)]
+struct Stalling {
+    sim: SimulatedDevice,
+    stall: Duration,
+    stalled: Arc<AtomicBool>,
+}
+
+#[derive(Debug)]
+struct StallingPort {
+    port: Box<dyn SerialPort>,
+    stall: Duration,
+    stalled: Arc<AtomicBool>,
+}
+
+impl SerialPort for StallingPort {
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.port.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.port.set_write_timeout(timeout)
+    }
+
+    fn flush_input(&mut self) -> io::Result<()> {
+        self.port.flush_input()
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if !self.stalled.swap(true, Ordering::SeqCst) {
+            thread::sleep(self.stall);
+        }
+        self.port.read(buffer)
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.port.write(buffer)
+    }
+}
+
+impl Backend for Stalling {
+    fn output_pin(&self, pin_no: u8) -> io::Result<Box<dyn OutputPin>> {
+        self.sim.output_pin(pin_no)
+    }
+
+    fn input_pin(&self, pin_no: u8) -> io::Result<Box<dyn InputPin>> {
+        self.sim.input_pin(pin_no)
+    }
+
+    fn poll_interrupts(&self,
+                       pins: &[&dyn InputPin],
+                       timeout: Option<Duration>) -> io::Result<Option<(u8, Level, Instant)>> {
+        self.sim.poll_interrupts(pins, timeout)
+    }
+
+    fn serial(&self, uart: &UART, config: &SerialConfig) -> io::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(StallingPort {
+            port: self.sim.serial(uart, config)?,
+            stall: self.stall,
+            stalled: Arc::clone(&self.stalled),
+        }))
+    }
+
+    fn i2c(&self) -> io::Result<Box<dyn I2cBus>> {
+        self.sim.i2c()
+    }
+
+    fn spi(&self, bus: u8, chip_select: u8) -> io::Result<Box<dyn SpiBus>> {
+        self.sim.spi(bus, chip_select)
+    }
+}
+
+/// Testbed whose tracing thread stops responding for `stall` once it starts reading.
+fn stalling_testbed(stall: Duration) -> Testbed {
+    let device = Device::new(&[(13, (Direction::In, SignalClass::Digital))]);
+    let mapping = Mapping::new(device, &[(20, 13)], &[])
+        .unwrap()
+        .with_backend(Arc::new(Stalling {
+            sim: SimulatedDevice::new(),
+            stall,
+            stalled: Arc::new(AtomicBool::new(false)),
+        }));
+    Testbed::new(
+        mapping,
+        Box::new(NoPlatform),
+        HashMap::new(),
+        Some(UART::Custom(TRACE_UART.to_string())),
+        None,
+        Vec::new())
+}
+
+#[test]
+fn hung_test_times_out_and_the_suite_continues() {
+    let stall = Duration::from_millis(1500);
+    let testbed = stalling_testbed(stall);
+
+    let idle = [Operation::at(0).idle_sync(Duration::from_millis(10))];
+    let tests = vec![
+        Test::new("hangs", Vec::new(), Vec::new(), &idle, Vec::new(), false)
+            .with_timeout(Duration::from_millis(300)),
+        Test::new("skipped", Vec::new(), Vec::new(), &idle, Vec::new(), false),
+        Test::new("recovers", Vec::new(), Vec::new(), &idle, Vec::new(), false),
+    ];
+    let start = Instant::now();
+    // Tests are taken as they run, so the stall is over by the time the last one is.
+    let mut tests = tests.into_iter().inspect(|test| if test.get_id() == "recovers" {
+        thread::sleep(stall);
+    });
+    let observations = testbed.execute(&mut tests);
+
+    assert_eq!(observations.len(), 3);
+    match observations[0].execution_result() {
+        Err(TestbedError::Timeout(limit, busy)) => {
+            assert_eq!(*limit, Duration::from_millis(300));
+            assert_eq!(busy, &vec!["test-stracing-dut".to_string()]);
+        },
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    // The tracing thread is not waited for while it does not respond.
+    match observations[1].execution_result() {
+        Err(TestbedError::Stopped(threads)) => assert_eq!(threads, &vec!["test-stracing-dut".to_string()]),
+        other => panic!("expected the run to be skipped, got {:?}", other),
+    }
+    assert!(observations[2].execution_result().is_ok());
+    assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+}
+
+#[test]
+fn collectors_that_never_return_are_not_waited_for() {
+    let testbed = stalling_testbed(Duration::from_secs(3600));
+
+    let idle = [Operation::at(0).idle_sync(Duration::from_millis(10))];
+    let tests = vec![
+        Test::new("hangs", Vec::new(), Vec::new(), &idle, Vec::new(), false)
+            .with_timeout(Duration::from_millis(300)),
+        Test::new("next", Vec::new(), Vec::new(), &idle, Vec::new(), false)
+            .with_timeout(Duration::from_secs(5)),
+    ];
+    let start = Instant::now();
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    assert_eq!(observations.len(), 2);
+    assert!(matches!(observations[0].execution_result(), Err(TestbedError::Timeout(..))));
+    match observations[1].execution_result() {
+        Err(TestbedError::Stopped(threads)) => assert_eq!(threads, &vec!["test-stracing-dut".to_string()]),
+        other => panic!("expected the run to be skipped, got {:?}", other),
+    }
+    // Neither the second run nor the end of the suite waits for the hung thread.
+    assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+}
+
+#[test]
+fn execution_stops_at_the_deadline() {
+    let testbed = simulated_testbed(SimulatedDevice::new());
+
+    // The data is collected long before the idle would end.
+    let idle = [Operation::at(0).idle_sync(Duration::from_secs(2))];
+    let tests = vec![
+        Test::new("overlong", Vec::new(), Vec::new(), &idle, Vec::new(), false)
+            .with_runtime(Duration::from_millis(50))
+            .with_timeout(Duration::from_millis(300)),
+    ];
+    let start = Instant::now();
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    match observations[0].execution_result() {
+        Err(TestbedError::Timeout(limit, busy)) => {
+            assert_eq!(*limit, Duration::from_millis(300));
+            assert_eq!(busy, &vec!["executor".to_string()]);
+        },
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    assert!(start.elapsed() < Duration::from_secs(1), "{:?}", start.elapsed());
+}
+
+#[test]
+fn collection_failures_fail_the_run() {
+    let testbed = simulated_testbed(SimulatedDevice::new());
+
+    let idle = [Operation::at(0).idle_sync(Duration::from_millis(10))];
+    let tests = vec![
+        // No energy meters are configured.
+        Test::new("unmetered", Vec::new(), Vec::new(), &idle,
+                  &[Criterion::Energy(EnergyCriterion::new("system", EnergyStat::Total))], false),
+        // The device has no pin 15 to watch.
+        Test::new("unwatched", Vec::new(), Vec::new(), &idle, &[Criterion::GPIO(GPIOCriterion::Any(15))], false),
+        Test::new("watched", Vec::new(), Vec::new(), &idle, &[Criterion::GPIO(GPIOCriterion::Any(14))], false),
+    ];
+    let start = Instant::now();
+    let observations = testbed.execute(&mut tests.into_iter());
+
+    match observations[0].execution_result() {
+        Err(TestbedError::Collection(thread, TestingError::NoSuchMeter(meter))) => {
+            assert_eq!(thread, "test-metering");
+            assert_eq!(meter, "system");
+        },
+        other => panic!("expected a metering failure, got {:?}", other),
+    }
+    match observations[1].execution_result() {
+        Err(TestbedError::Collection(thread, TestingError::IO(_))) => assert_eq!(thread, "test-observer-dut"),
+        other => panic!("expected an observer failure, got {:?}", other),
+    }
+    assert!(observations[2].execution_result().is_ok());
+    assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+}
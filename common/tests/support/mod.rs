@@ -0,0 +1,33 @@
+//! Helpers shared by the integration tests.
+
+use std::collections::HashSet;
+
+use clockwise_common::sw::{Platform, PlatformSupport};
+use clockwise_common::sw::error::SoftwareError;
+use clockwise_common::sw::instrument::Spec;
+
+/// Platform that accepts any software request without doing anything.
+#[derive(Debug)]
+pub struct NoPlatform;
+
+impl PlatformSupport for NoPlatform {
+    fn platform(&self) -> Platform {
+        Platform::Tock
+    }
+
+    fn load(&self, _name: &str) -> Result<(), SoftwareError> {
+        Ok(())
+    }
+
+    fn unload(&self, _name: &str) -> Result<(), SoftwareError> {
+        Ok(())
+    }
+
+    fn loaded_software(&self) -> HashSet<String> {
+        HashSet::new()
+    }
+
+    fn reconfigure(&self, trace_points: &Vec<String>) -> Result<Spec, SoftwareError> {
+        Ok(Spec::new(trace_points.iter().map(|tp| tp.as_str())))
+    }
+}
@@ -0,0 +1,69 @@
+//! Timestamping serial trace data.
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clockwise_common::events::DeviceEvents;
+use clockwise_common::hal::SerialPort;
+use clockwise_common::io;
+use clockwise_common::test::{Operation, Test};
+
+/// Port whose first read blocks for `delay` before returning `data`, like a read waiting on a slow device.
+#[derive(Debug)]
+struct SlowPort {
+    delay: Duration,
+    data: Option<Vec<u8>>,
+}
+
+impl SerialPort for SlowPort {
+    fn set_read_timeout(&mut self, _timeout: Duration) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_write_timeout(&mut self, _timeout: Duration) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn flush_input(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self.data.take() {
+            Some(data) => {
+                thread::sleep(self.delay);
+                buffer[..data.len()].copy_from_slice(&data);
+                Ok(data.len())
+            },
+            None => {
+                thread::sleep(Duration::from_millis(1));
+                Ok(0)
+            },
+        }
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        Ok(buffer.len())
+    }
+}
+
+#[test]
+fn traces_are_timestamped_when_the_read_returns() {
+    let delay = Duration::from_millis(40);
+    let test = Test::new("trace", Vec::new(), Vec::new(), &[Operation::at(0).idle_sync(Duration::from_millis(10))],
+                         Vec::new(), false);
+    let mut port = SlowPort { delay, data: Some(b"boot".to_vec()) };
+    let mut buffer = vec![0; 64];
+    let mut schedule = Vec::new();
+    let events = DeviceEvents::new(&HashMap::new());
+
+    let start = Instant::now();
+    assert_eq!(test.trace(&mut port, &mut buffer, &mut schedule, &events).unwrap(), 4);
+
+    // Stamping the data with the start of the read would place it `delay` too early.
+    assert_eq!(schedule.len(), 1);
+    let (received_at, count) = schedule[0];
+    assert_eq!(count, 4);
+    assert!(received_at - start >= delay, "trace stamped {:?} after the start", received_at - start);
+}
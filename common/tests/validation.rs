@@ -0,0 +1,138 @@
+//! Checking tests against the testbed before running them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clockwise_common::comm::{Class as SignalClass, Direction, Signal};
+use clockwise_common::criteria::{
+    Criterion,
+    EnergyCriterion,
+    EnergyStat,
+    GPIOCriterion,
+    Latency,
+    SerialTraceCondition,
+    SerialTraceCriterion,
+};
+use clockwise_common::events::WaitCondition;
+use clockwise_common::hal::sim::SimulatedDevice;
+use clockwise_common::io::{Device, Mapping, ResetStrategy};
+use clockwise_common::test::{Operation, Test};
+use clockwise_common::testbed::{TestConflict, Testbed, DEFAULT_DEVICE};
+
+mod support;
+
+use support::NoPlatform;
+
+/// Testbed whose device has input pin 13, output pin 14, and output pin 15 that is not wired up.
+fn testbed() -> Testbed {
+    testbed_with_reset(None)
+}
+
+/// Like [`testbed`], with the device reset by `reset` if given.
+fn testbed_with_reset(reset: Option<ResetStrategy>) -> Testbed {
+    let mut device = Device::new(&[
+        (13, (Direction::In, SignalClass::Digital)),
+        (14, (Direction::Out, SignalClass::Digital)),
+        (15, (Direction::Out, SignalClass::Digital)),
+    ]);
+    if let Some(strategy) = reset {
+        device = device.with_reset(strategy);
+    }
+    let mapping = Mapping::new(device, &[(20, 13), (21, 14)], &[])
+        .unwrap()
+        .with_backend(Arc::new(SimulatedDevice::new()));
+
+    Testbed::new(mapping, Box::new(NoPlatform), HashMap::new(), None, None, Vec::new())
+}
+
+#[test]
+fn fitting_test_has_no_conflicts() {
+    let test = Test::new(
+        "echo",
+        Vec::new(),
+        Vec::new(),
+        &[Operation::at(0).input(Signal::Digital(true), 13),
+          Operation::at(10).wait_for(WaitCondition::PinLevel(14, true.into()), Duration::from_millis(10))],
+        &[Criterion::GPIO(GPIOCriterion::Any(14)),
+          Criterion::GPIO(GPIOCriterion::Latency(
+              Latency::new(13, 14, Duration::ZERO, Duration::from_millis(5))))],
+        false);
+    assert!(testbed().validate(&test).is_empty());
+
+    // The stimulus of a latency criterion must be a device input.
+    let reversed = Test::new(
+        "reversed",
+        Vec::new(),
+        Vec::new(),
+        &[Operation::at(0).idle_sync(Duration::from_millis(10))],
+        &[Criterion::GPIO(GPIOCriterion::Latency(
+            Latency::new(14, 14, Duration::ZERO, Duration::from_millis(5))))],
+        false);
+    let conflicts: Vec<String> = testbed().validate(&reversed).iter()
+        .map(|conflict| conflict.to_string())
+        .collect();
+    assert_eq!(conflicts, vec![TestConflict::PinDirection(DEFAULT_DEVICE.to_string(), 14, Direction::In).to_string()]);
+}
+
+#[test]
+fn every_conflict_is_reported_once() {
+    let dut = DEFAULT_DEVICE.to_string();
+    let test = Test::new(
+        "misfit",
+        Vec::new(),
+        ["boot"],
+        &[Operation::at(0).input(Signal::Digital(true), 14),
+          Operation::at(5).input(Signal::Digital(false), 14),
+          Operation::at(10).toggle(99),
+          Operation::at(15).input(Signal::Analog(1200), 13),
+          Operation::at(20).toggle(13).on("radio"),
+          Operation::at(25).wait_for(WaitCondition::SerialTrace(b"ready".to_vec()), Duration::from_millis(10))],
+        &[Criterion::GPIO(GPIOCriterion::Any(13)),
+          Criterion::GPIO(GPIOCriterion::Any(15)),
+          Criterion::Energy(EnergyCriterion::new("system", EnergyStat::Total)),
+          Criterion::SerialTrace(SerialTraceCriterion::new(&[SerialTraceCondition::new(b"boot")]))],
+        false);
+
+    let mut conflicts: Vec<String> = testbed().validate(&test).iter()
+        .map(|conflict| conflict.to_string())
+        .collect();
+    conflicts.sort();
+
+    let mut expected: Vec<String> = [
+        TestConflict::PinDirection(dut.clone(), 14, Direction::In),
+        TestConflict::UndefinedPin(dut.clone(), 99),
+        TestConflict::NoAnalogOutput(dut.clone(), 13),
+        TestConflict::NoSuchDevice("radio".to_string()),
+        TestConflict::NoTracingUart(dut.clone()),
+        TestConflict::PinDirection(dut.clone(), 13, Direction::Out),
+        TestConflict::UnmappedPin(dut, 15),
+        TestConflict::NoSuchMeter("system".to_string()),
+        TestConflict::UntracedTracePoints,
+    ].iter()
+        .map(|conflict| conflict.to_string())
+        .collect();
+    expected.sort();
+
+    assert_eq!(conflicts, expected);
+}
+
+#[test]
+fn reset_strategies_are_checked() {
+    let dut = DEFAULT_DEVICE.to_string();
+    let resetting = Test::new("reset", Vec::new(), Vec::new(), &[Operation::at(0).idle_sync(Duration::from_millis(10))],
+                              Vec::new(), true);
+    let conflicts = |reset| testbed_with_reset(reset).validate(&resetting);
+
+    assert_eq!(conflicts(None), vec![TestConflict::NoReset]);
+    assert!(conflicts(Some(ResetStrategy::command("sh", ["-c", "true"]))).is_empty());
+    assert_eq!(conflicts(Some(ResetStrategy::command("/nonexistent/reset-tool", []))),
+               vec![TestConflict::NoResetProgram(dut.clone(), "/nonexistent/reset-tool".to_string())]);
+    assert_eq!(conflicts(Some(ResetStrategy::command("clockwise-no-such-reset-tool", ["--now"]))),
+               vec![TestConflict::NoResetProgram(dut, "clockwise-no-such-reset-tool".to_string())]);
+
+    // Tests that do not reset do not need a working strategy.
+    let idle = Test::new("idle", Vec::new(), Vec::new(), &[Operation::at(0).idle_sync(Duration::from_millis(10))],
+                         Vec::new(), false);
+    assert!(testbed_with_reset(Some(ResetStrategy::command("/nonexistent/reset-tool", []))).validate(&idle).is_empty());
+}
@@ -6,7 +6,7 @@ use core::fmt::{self, Display};
 use crate::serialize;
 
 /// Memory statistic category.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum CounterId {
     /// Custom grant allocation total.
     CustomGrant(u32),
@@ -1,10 +1,9 @@
 //! Hard-coded Testbed and Test providers.
 
 use std::collections::HashMap;
-use std::path::Path;
-use std::thread;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use clockwise_common::comm::{Direction, Class as SignalClass};
 #[allow(unused_imports)]
@@ -18,13 +17,14 @@ use clockwise_common::criteria::{
     SerialTraceCriterion,
 };
 use clockwise_common::facility::EnergyMetering;
+use clockwise_common::hal::{Backend, GpioChip, Level, RaspberryPi};
 use clockwise_common::hw::INA219;
 use clockwise_common::input::{TestProvider, TestbedProvider};
-use clockwise_common::io;
 use clockwise_common::io::{
     Device,
-    DeviceInputs,
     Mapping,
+    Peripheral,
+    ResetStrategy,
     UART,
 };
 use clockwise_common::sw::platform::Tock;
@@ -34,11 +34,22 @@ use clockwise_common::trace::{TraceKind, BenchmarkMetadata};
 
 /// Testbed created from code compiled into the binary.
 #[derive(Debug)]
-pub struct HardCodedTestbed {  }
+pub struct HardCodedTestbed {
+    gpio_chip: Option<PathBuf>,
+}
 
 impl HardCodedTestbed {
     pub fn new() -> HardCodedTestbed {
-        HardCodedTestbed {  }
+        HardCodedTestbed {
+            gpio_chip: None,
+        }
+    }
+
+    /// Drive the testbed through the GPIO character device at `path` instead of the Raspberry Pi peripherals.
+    pub fn with_gpio_chip(self, path: &Path) -> Self {
+        Self {
+            gpio_chip: Some(path.to_path_buf()),
+        }
     }
 }
 
@@ -53,26 +64,20 @@ impl TestbedProvider for HardCodedTestbed {
             (23, (Direction::In, SignalClass::Digital)),  // reset
         ];
 
-        // reset functions
-        let hold_reset_fn: Rc<dyn Fn(&mut DeviceInputs) -> io::Result<()>> = Rc::new(
-            |to_device| {
-                let reset_pin = to_device.get_pin_mut(23)?;
-                reset_pin.set_low();
-                thread::sleep(Duration::from_millis(10));
-
-                Ok(())
-            });
-
-        let release_reset_fn: Rc<dyn Fn(&mut DeviceInputs) -> io::Result<()>> = Rc::new(
-            |to_device| {
-                let reset_pin = to_device.get_pin_mut(23)?;
-                reset_pin.set_high();
-
-                Ok(())
+        let device = Device::new(&host_to_device_pins)
+            .with_reset(ResetStrategy::Pin {
+                pin_no: 23,
+                active: Level::Low,
+                hold: Duration::from_millis(10),
             });
 
-        let device = Device::new(&host_to_device_pins)
-            .with_reset(hold_reset_fn.clone(), release_reset_fn.clone());
+        // I/O backend
+        let backend: Arc<dyn Backend> = match self.gpio_chip {
+            Some(ref path) => Arc::new(GpioChip::new(path)
+                .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?
+                .with_i2c_bus(1)),
+            None => Arc::new(RaspberryPi::new()),
+        };
 
         let mapping = Mapping::new(
             device,
@@ -80,12 +85,14 @@ impl TestbedProvider for HardCodedTestbed {
             &[(17, 23), // Reset
               (20, 13),
             ],
-            Some(23),
-        ).unwrap();
+            // INA219 on I2C, benchmark tracing on the PL011.
+            &[Peripheral::I2C, Peripheral::UART(UART::PL011)],
+        ).map_err(|e| e.to_string())?
+            .with_backend(backend);
 
         // Energy metering
         let ina219: Box<dyn EnergyMetering> = Box::new(
-            INA219::new(mapping.get_i2c().unwrap(), 0x40).unwrap());
+            INA219::new(&mapping.get_i2c().unwrap(), 0x40).unwrap());
         let energy_meters: HashMap<String, Box<dyn EnergyMetering>> = (vec![
             ("system".to_string(), ina219)
         ]).into_iter()
@@ -94,7 +101,7 @@ impl TestbedProvider for HardCodedTestbed {
         // Tracing capabilities
         let tracing = {
             let benchmark_tracing = TraceKind::Performance(
-                BenchmarkMetadata::new(&[
+                BenchmarkMetadata::new("bytes", &[
                 ]));
 
             vec![
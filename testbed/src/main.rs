@@ -2,7 +2,7 @@
 
 use std::process;
 
-use clockwise_common::evaluation::{Evaluator, StandardEvaluator};
+use clockwise_common::evaluation::{self, Evaluator, StandardEvaluator};
 
 mod input;
 mod opts;
@@ -27,8 +27,24 @@ fn main() {
     let testbed = result.unwrap();
     print!("{}\n", testbed);
 
-    let mut tests = configuration.get_test_adapter().tests();
-    let observations = testbed.execute(&mut tests);
+    let tests: Vec<_> = configuration.get_selection()
+        .filter(configuration.get_test_adapter().tests())
+        .collect();
+
+    // Check the whole suite against the testbed before running any of it.
+    let mut valid = true;
+    for test in &tests {
+        for conflict in testbed.validate(test) {
+            println!("Test '{}': {}", test.get_id(), conflict);
+            valid = false;
+        }
+    }
+    if !valid {
+        println!("Tests do not fit the testbed.");
+        process::exit(1);
+    }
+
+    let observations = testbed.execute(&mut tests.into_iter());
 
     // Show test observation data.
     for obs in &observations {
@@ -40,11 +56,23 @@ fn main() {
     // Later it may be advantageous to allow another kind of evaluator,
     // say, for instance, if a provider wanted to evaluate its own data.
     let evaluator = StandardEvaluator::new();
-    let evaluation_iter = observations.iter()
-        .map(|obs| evaluator.evaluate(obs));
+    let evaluations: Vec<_> = observations.iter()
+        .map(|obs| evaluator.evaluate(obs))
+        .collect();
 
     println!("Results Summary:");
-    for evaluation in evaluation_iter {
+    for evaluation in &evaluations {
         println!("{}", evaluation);
     }
+
+    // Summarize tests that ran more than once.
+    let repeated: Vec<_> = evaluation::aggregate(&evaluations).into_iter()
+        .filter(|aggregate| aggregate.runs() > 1)
+        .collect();
+    if !repeated.is_empty() {
+        println!("Repeated Tests Summary:");
+        for aggregate in &repeated {
+            println!("{}", aggregate);
+        }
+    }
 }
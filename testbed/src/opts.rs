@@ -5,7 +5,7 @@ use std::fmt;
 use std::fmt::Display;
 use std::path::Path;
 
-use clockwise_common::input::{TestProvider, TestbedProvider};
+use clockwise_common::input::{Selector, TestProvider, TestSelection, TestbedProvider};
 use getopts::Options;
 
 use crate::input::hard_code::{
@@ -62,15 +62,18 @@ impl From<getopts::Fail> for Error {
 pub struct Configuration {
     testbed_reader: Box<dyn TestbedProvider>,
     test_adapter: Box<dyn TestProvider>,
+    selection: TestSelection,
 }
 
 impl Configuration {
     fn new(testbed_reader: Box<dyn TestbedProvider>,
-           test_adapter: Box<dyn TestProvider>) -> Configuration
+           test_adapter: Box<dyn TestProvider>,
+           selection: TestSelection) -> Configuration
     {
         Configuration {
             testbed_reader,
             test_adapter,
+            selection,
         }
     }
 
@@ -81,12 +84,37 @@ impl Configuration {
     pub fn get_test_adapter(&self) -> &dyn TestProvider {
         self.test_adapter.as_ref()
     }
+
+    /// Which of the provided tests to run.
+    pub fn get_selection(&self) -> &TestSelection {
+        &self.selection
+    }
+}
+
+/// Parse a test selector: `tag:NAME`, `app:NAME`, `trace:POINT`, or `id:GLOB` (the default).
+fn parse_selector(spec: &str) -> Result<Selector> {
+    let selector = match spec.split_once(':') {
+        Some(("id", pattern)) => Selector::Id(pattern.to_string()),
+        Some(("tag", tag)) => Selector::Tag(tag.to_string()),
+        Some(("app", app_id)) => Selector::App(app_id.to_string()),
+        Some(("trace", trace_point)) => Selector::TracePoint(trace_point.to_string()),
+        Some((kind, _value)) => {
+            let msg = format!("{} is not a kind of test selector", kind);
+            return Err(Error::Invalid(msg));
+        },
+        None => Selector::Id(spec.to_string()),
+    };
+
+    Ok(selector)
 }
 
 fn create_options() -> Options {
     let mut opts = Options::new();
     opts.optopt("b", "testbed-format", "select a testbed input format", "FORMAT");
     opts.optopt("t", "test-format", "select a test input format", "FORMAT");
+    opts.optopt("g", "gpio-chip", "drive the hard-coded testbed through a GPIO character device", "PATH");
+    opts.optmulti("i", "include", "only run tests matching a selector (id:GLOB, tag:NAME, app:NAME, trace:POINT; a bare GLOB matches ids)", "SELECTOR");
+    opts.optmulti("x", "exclude", "skip tests matching a selector", "SELECTOR");
     opts.optflag("h", "help", "show help");
 
     opts
@@ -103,12 +131,21 @@ pub fn parse<'a>() -> Result<Configuration> {
         let brief = format!("Usage: {} [ options ] <config-specific options>", &cli_args[0]);
         Err(Error::Help(opts.usage(&brief)))
     } else {
+        let hard_coded_testbed = || match matches.opt_str("gpio-chip") {
+            Some(path) => HardCodedTestbed::new().with_gpio_chip(Path::new(&path)),
+            None => HardCodedTestbed::new(),
+        };
+
         let testbed_reader: Box<dyn TestbedProvider> = if matches.opt_present("testbed-format") {
             let format = matches.opt_str("testbed-format")
                 .ok_or(Error::ArgumentMissing("testbed-format"))?;
             match format.as_str() {
                 "code" => {
-                    Ok(Box::new(HardCodedTestbed::new()) as Box<dyn TestbedProvider>)
+                    Ok(Box::new(hard_coded_testbed()) as Box<dyn TestbedProvider>)
+                },
+
+                "lib" if matches.opt_present("gpio-chip") => {
+                    Err(Error::Invalid("gpio-chip only applies to the code testbed format".to_string()))
                 },
 
                 "lib" => {
@@ -124,7 +161,7 @@ pub fn parse<'a>() -> Result<Configuration> {
             }?
         } else {
             // Default to the hard-coded testbed.
-            Box::new(HardCodedTestbed::new())
+            Box::new(hard_coded_testbed())
         };
 
         let test_adapter: Box<dyn TestProvider> = if matches.opt_present("test-format") {
@@ -153,6 +190,14 @@ pub fn parse<'a>() -> Result<Configuration> {
             Box::new(HardCodedTests::new())
         };
 
-        Ok(Configuration::new(testbed_reader, test_adapter))
+        let mut selection = TestSelection::new();
+        for spec in matches.opt_strs("include") {
+            selection = selection.with_include(parse_selector(&spec)?);
+        }
+        for spec in matches.opt_strs("exclude") {
+            selection = selection.with_exclude(parse_selector(&spec)?);
+        }
+
+        Ok(Configuration::new(testbed_reader, test_adapter, selection))
     }
 }